@@ -0,0 +1,23 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Embed the git commit the binary is built from as `CI_GIT_COMMIT`
+///
+/// The variable is empty when building outside a git checkout, e.g. from crates.io.
+fn main() {
+    let commit = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=CI_GIT_COMMIT={}", commit);
+
+    for path in ["../.git/HEAD", "../.git/refs"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+}
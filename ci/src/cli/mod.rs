@@ -1,17 +1,26 @@
 use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::{
     generate,
-    shells::{Bash, Fish, Zsh},
+    shells::{Bash, Fish, Shell, Zsh},
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use codeinput::core::{
-    commands::{self, infer_owners::{InferScope, InferAlgorithm}},
-    types::{CacheEncoding, OutputFormat},
+    commands::{
+        self,
+        audit::AuditFormat,
+        contacts::ContactFormat,
+        export::ExportFormat,
+        infer_owners::{InferAlgorithm, InferScope},
+        list_files::{JsonShape, OwnerDisplay, OwnerMatch},
+        list_owners::OwnerSort,
+    },
+    common::find_repo_root,
+    types::{CacheEncoding, OutputFormat, OwnerMerge, OwnerType, PatternAnchor},
 };
 use codeinput::utils::app_config::AppConfig;
-use codeinput::utils::error::Result;
-use codeinput::utils::types::LogLevel;
+use codeinput::utils::error::{ErrorKind, Result};
+use codeinput::utils::types::{HashMode, LogLevel};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -42,6 +51,41 @@ pub struct Cli {
     )]
     pub log_level: Option<LogLevel>,
 
+    /// How much repository state to hash when checking the cache (full, head-only, none)
+    #[arg(
+        name = "hash_mode",
+        long = "hash-mode",
+        value_name = "MODE",
+        global = true
+    )]
+    pub hash_mode: Option<HashMode>,
+
+    /// Files resolved per parallel task when building the cache (0 to pick from the file count)
+    #[arg(
+        name = "chunk_size",
+        long = "chunk-size",
+        value_name = "N",
+        global = true
+    )]
+    pub chunk_size: Option<usize>,
+
+    /// Stop once the output exceeds this many bytes (0 for no limit)
+    #[arg(
+        name = "max_output_bytes",
+        long = "max-output-bytes",
+        value_name = "N",
+        global = true
+    )]
+    pub max_output_bytes: Option<u64>,
+
+    /// Report cache build progress as JSON lines on stderr instead of a status line
+    #[arg(name = "progress_json", long = "progress-json", global = true)]
+    pub progress_json: bool,
+
+    /// Analyze from the root of the enclosing git repository instead of the given path
+    #[arg(long, global = true)]
+    pub repo_root_auto: bool,
+
     /// Subcommands
     #[clap(subcommand)]
     command: Commands,
@@ -56,7 +100,7 @@ enum Commands {
     )]
     Codeowners {
         #[clap(subcommand)]
-        subcommand: CodeownersSubcommand,
+        subcommand: Box<CodeownersSubcommand>,
     },
     #[clap(
         name = "completion",
@@ -72,7 +116,21 @@ enum Commands {
         about = "Show Configuration",
         long_about = None,
     )]
-    Config,
+    Config {
+        /// Show which layer each value comes from: default, file, env or cli
+        #[arg(long)]
+        show_sources: bool,
+    },
+    #[clap(
+        name = "version",
+        about = "Show version and build information",
+        long_about = None,
+    )]
+    Version {
+        /// Print the version, git commit, build profile and enabled features as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand, PartialEq, Debug)]
@@ -83,8 +141,16 @@ enum CompletionSubcommand {
     Zsh,
     #[clap(about = "generate the autocompletion script for fish")]
     Fish,
+    #[clap(about = "install the autocompletion script into the shell's completion directory")]
+    Install {
+        /// Shell to install completions for: bash|zsh|fish
+        #[arg(value_name = "SHELL", value_parser = parse_shell)]
+        shell: Shell,
+    },
 }
 
+// Parsed once per run, so the size of the flag-heavy variants doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, PartialEq, Debug)]
 pub(crate) enum CodeownersSubcommand {
     #[clap(
@@ -103,6 +169,62 @@ pub(crate) enum CodeownersSubcommand {
         /// Output format: json|bincode
         #[arg(long, value_name = "FORMAT", default_value = "bincode", value_parser = parse_cache_encoding)]
         format: CacheEncoding,
+
+        /// Print a summary of what would be parsed without writing the cache
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Print a timing breakdown of each phase to stderr
+        #[arg(long)]
+        profile: bool,
+
+        /// Match CODEOWNERS patterns case-insensitively
+        #[arg(long)]
+        ignore_case: bool,
+
+        /// Owners from the winning rule only, or the union of all matching rules: winner|union
+        #[arg(long, value_name = "MODE", default_value = "winner", value_parser = parse_owner_merge)]
+        owner_merge: OwnerMerge,
+
+        /// Anchor patterns at the directory of their CODEOWNERS file or at the repo root: file-dir|repo-root
+        #[arg(long, value_name = "MODE", default_value = "file-dir", value_parser = parse_anchor)]
+        anchor: PatternAnchor,
+
+        /// Follow symlinked files and directories while walking the repository
+        #[arg(long)]
+        follow_links: bool,
+
+        /// List every path that could not be read while walking the repository
+        #[arg(long)]
+        verbose: bool,
+
+        /// Only re-resolve files changed in git, updating the existing cache
+        #[arg(long, conflicts_with = "dry_run")]
+        changed_only: bool,
+
+        /// TOML file mapping old owner identifiers to new ones
+        #[arg(long, value_name = "FILE")]
+        alias_map: Option<PathBuf>,
+
+        /// Merge into the existing cache instead of replacing it, for repos parsed in pieces
+        #[arg(long, conflicts_with_all = ["dry_run", "changed_only"])]
+        append: bool,
+
+        /// TOML file mapping owner emails to the handles of the same people
+        #[arg(long, value_name = "FILE")]
+        identity_map: Option<PathBuf>,
+
+        /// Count walked paths that resolve to the same file, e.g. through symlinks, only once
+        #[arg(long)]
+        dedupe_paths: bool,
+
+        /// Read a CODEOWNERS document from stdin and print its entries as JSON
+        #[arg(long, conflicts_with_all = [
+            "path", "cache_file", "format", "dry_run", "profile", "ignore_case", "owner_merge",
+            "anchor", "follow_links", "verbose", "changed_only", "alias_map", "append",
+            "identity_map", "dedupe_paths",
+        ])]
+        stdin: bool,
     },
 
     #[clap(
@@ -118,11 +240,35 @@ pub(crate) enum CodeownersSubcommand {
         #[arg(long, value_name = "LIST")]
         tags: Option<String>,
 
+        /// Hide files with any of the specified tags
+        #[arg(long, value_name = "LIST")]
+        exclude_tags: Option<String>,
+
         /// Only show files owned by these owners
         #[arg(long, value_name = "LIST")]
         owners: Option<String>,
 
-        /// Show only unowned files
+        /// Only show paths matching this glob (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        only: Vec<String>,
+
+        /// Hide paths matching this glob, even if they match --only (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+
+        /// Require any or all of the --owners: any|all
+        #[arg(long, value_name = "MODE", default_value = "any", value_parser = parse_owner_match)]
+        owner_match: OwnerMatch,
+
+        /// Only show files with an owner matching this regular expression
+        #[arg(long, value_name = "RE", conflicts_with = "owners")]
+        owner_regex: Option<String>,
+
+        /// Only show files with a tag matching this regular expression
+        #[arg(long, value_name = "RE", conflicts_with = "tags")]
+        tag_regex: Option<String>,
+
+        /// Show only unowned files (no owners, or only NOOWNER)
         #[arg(long)]
         unowned: bool,
 
@@ -130,13 +276,69 @@ pub(crate) enum CodeownersSubcommand {
         #[arg(long)]
         show_all: bool,
 
-        /// Output format: text|json|bincode
+        /// Show only files that no CODEOWNERS file in any parent directory applies to
+        #[arg(long)]
+        no_governing_codeowners: bool,
+
+        /// Show only files owned by a single user with no team as backup
+        #[arg(long, conflicts_with = "unowned")]
+        spof: bool,
+
+        /// With --spof, only show files whose owner owns at least N files
+        #[arg(long, value_name = "N", requires = "spof")]
+        min_impact: Option<usize>,
+
+        /// Show only files whose last commit is by this author (name or email; walks git history)
+        #[arg(long, value_name = "AUTHOR")]
+        modified_by: Option<String>,
+
+        /// Output format: text|json|bincode|html
         #[arg(long, value_name = "FORMAT", default_value = "text", value_parser = parse_output_format)]
         format: OutputFormat,
 
         /// Custom cache file location
         #[arg(long, value_name = "FILE", default_value = ".codeowners.cache")]
         cache_file: Option<PathBuf>,
+
+        /// Include the CODEOWNERS rule that decided each file's owners (JSON only)
+        #[arg(long)]
+        with_rule: bool,
+
+        /// How owners are shown in text output: identifier|type|both
+        #[arg(long, value_name = "MODE", default_value = "identifier", value_parser = parse_owner_display)]
+        owner_display: OwnerDisplay,
+
+        /// Print only the number of matching files
+        #[arg(long)]
+        count_only: bool,
+
+        /// Print shell-quoted path=...;owners=...;tags=... lines (text only)
+        #[arg(long, conflicts_with = "count_only")]
+        parseable: bool,
+
+        /// Link file paths in the text table (OSC 8), even in terminals not known to support it
+        #[arg(long)]
+        hyperlinks: bool,
+
+        /// Wrap JSON output in an object with the query, total and results
+        #[arg(long)]
+        json_envelope: bool,
+
+        /// Print only the table rows, without the header row and total line
+        #[arg(long, conflicts_with_all = ["count_only", "parseable"])]
+        no_header: bool,
+
+        /// Lay out JSON output as an array of files or an object keyed by path: array|map
+        #[arg(long, value_name = "SHAPE", default_value = "array", value_parser = parse_json_shape)]
+        json_shape: JsonShape,
+
+        /// Write the output to this file instead of stdout
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Write a JSON index of each owner's and tag's record offsets in the --output file
+        #[arg(long, value_name = "FILE", requires = "output")]
+        index_out: Option<PathBuf>,
     },
 
     #[clap(
@@ -148,10 +350,57 @@ pub(crate) enum CodeownersSubcommand {
         #[arg(default_value = ".")]
         path: Option<PathBuf>,
 
-        /// Output format: text|json|bincode
+        /// Only show owners of this type: user|team|email|unowned|unknown
+        #[arg(long = "type", value_name = "TYPE", value_parser = parse_owner_type)]
+        owner_type: Option<OwnerType>,
+
+        /// Group owners under their type
+        #[arg(long)]
+        group_by_type: bool,
+
+        /// Show how many email owners belong to each email domain instead
+        #[arg(long, conflicts_with_all = ["owner_type", "group_by_type"])]
+        email_domains: bool,
+
+        /// Show only owners of unknown type, with the rules declaring them
+        #[arg(long, conflicts_with_all = ["owner_type", "group_by_type", "email_domains"])]
+        show_unknown: bool,
+
+        /// With --show-unknown, suggest the closest known owner for each unknown one
+        #[arg(long, requires = "show_unknown")]
+        suggest: bool,
+
+        /// Show only owners declared in CODEOWNERS that own no file
+        #[arg(
+            long,
+            conflicts_with_all = ["owner_type", "group_by_type", "email_domains", "show_unknown"]
+        )]
+        orphaned: bool,
+
+        /// Cap sample files per owner in text output and the embedded file list in JSON output
+        #[arg(long, value_name = "N")]
+        max_files: Option<usize>,
+
+        /// Show the distinct tags of the files each owner owns
+        #[arg(long, conflicts_with_all = ["email_domains", "show_unknown", "orphaned"])]
+        with_tags: bool,
+
+        /// Output format: text|json|bincode|html
         #[arg(long, value_name = "FORMAT", default_value = "text", value_parser = parse_output_format)]
         format: OutputFormat,
 
+        /// Wrap JSON output in an object with the query, total and results
+        #[arg(long, conflicts_with_all = ["email_domains", "show_unknown", "orphaned"])]
+        json_envelope: bool,
+
+        /// Print only the table rows, without type headings, header rows and total line
+        #[arg(long, conflicts_with_all = ["email_domains", "show_unknown", "orphaned"])]
+        no_header: bool,
+
+        /// Order owners by number of files, by type (teams, users, emails) or by name: files|type|name
+        #[arg(long, value_name = "ORDER", default_value = "files", value_parser = parse_owner_sort)]
+        sort: OwnerSort,
+
         /// Custom cache file location
         #[arg(long, value_name = "FILE", default_value = ".codeowners.cache")]
         cache_file: Option<PathBuf>,
@@ -165,20 +414,44 @@ pub(crate) enum CodeownersSubcommand {
         #[arg(default_value = ".")]
         path: Option<PathBuf>,
 
-        /// Output format: text|json|bincode
-        #[arg(long, value_name = "FORMAT", default_value = "text", value_parser = parse_output_format)]
+        /// Show only tags declared in CODEOWNERS that apply to no file
+        #[arg(long)]
+        unused: bool,
+
+        /// Show how many files each pair of tags shares instead
+        #[arg(long, conflicts_with_all = ["unused", "max_files", "json_envelope"])]
+        cooccurrence: bool,
+
+        /// Cap sample files per tag in text output and the embedded file list in JSON output
+        #[arg(long, value_name = "N")]
+        max_files: Option<usize>,
+
+        /// Output format: text|json|bincode|html, or dot with --cooccurrence
+        #[arg(long, value_name = "FORMAT", default_value = "text", value_parser = parse_tags_format)]
         format: OutputFormat,
 
         /// Custom cache file location
         #[arg(long, value_name = "FILE", default_value = ".codeowners.cache")]
         cache_file: Option<PathBuf>,
+
+        /// Wrap JSON output in an object with the query, total and results
+        #[arg(long, conflicts_with = "unused")]
+        json_envelope: bool,
+
+        /// Print only the table rows, without the header row and total line
+        #[arg(long, conflicts_with_all = ["unused", "cooccurrence"])]
+        no_header: bool,
     },
     #[clap(
         name = "list-rules",
         about = "Display all CODEOWNERS rules from the cache"
     )]
     ListRules {
-        /// Output format: text|json|bincode
+        /// Directory path to analyze (default: current directory)
+        #[arg(default_value = ".")]
+        path: Option<PathBuf>,
+
+        /// Output format: text|json|bincode|html
         #[arg(long, value_name = "FORMAT", default_value = "text", value_parser = parse_output_format)]
         format: OutputFormat,
 
@@ -188,11 +461,11 @@ pub(crate) enum CodeownersSubcommand {
     },
     #[clap(
         name = "inspect",
-        about = "Inspect ownership and tags for a specific file"
+        about = "Inspect ownership and tags for a specific file or directory"
     )]
     Inspect {
-        /// File path to inspect
-        #[arg(value_name = "FILE")]
+        /// File or directory path to inspect
+        #[arg(value_name = "PATH")]
         file_path: PathBuf,
 
         /// Directory path to analyze (default: current directory)
@@ -200,13 +473,194 @@ pub(crate) enum CodeownersSubcommand {
         repo: Option<PathBuf>,
 
         /// Output format: text|json|bincode
-        #[arg(long, value_name = "FORMAT", default_value = "text", value_parser = parse_output_format)]
+        #[arg(long, value_name = "FORMAT", default_value = "text", value_parser = parse_inspect_format)]
         format: OutputFormat,
 
         /// Custom cache file location
         #[arg(long, value_name = "FILE", default_value = ".codeowners.cache")]
         cache_file: Option<PathBuf>,
     },
+    #[clap(
+        name = "resolve",
+        about = "Resolve owners and tags for a list of paths"
+    )]
+    Resolve {
+        /// File with one path per line, or - to read from stdin
+        #[arg(long, value_name = "FILE|-")]
+        paths_from: PathBuf,
+
+        /// Directory path to analyze (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        repo: Option<PathBuf>,
+
+        /// Output format: text|json|html
+        #[arg(long, value_name = "FORMAT", default_value = "text", value_parser = parse_resolve_format)]
+        format: OutputFormat,
+
+        /// Custom cache file location
+        #[arg(long, value_name = "FILE", default_value = ".codeowners.cache")]
+        cache_file: Option<PathBuf>,
+    },
+    #[clap(
+        name = "mentions",
+        about = "Print the owners of a list of paths as @mentions for a PR comment"
+    )]
+    Mentions {
+        /// File with one path per line, or - to read from stdin
+        #[arg(long, value_name = "FILE|-")]
+        paths_from: PathBuf,
+
+        /// Directory path to analyze (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        repo: Option<PathBuf>,
+
+        /// Custom cache file location
+        #[arg(long, value_name = "FILE", default_value = ".codeowners.cache")]
+        cache_file: Option<PathBuf>,
+    },
+    #[clap(
+        name = "export",
+        about = "Export resolved ownership of every file in a flat format"
+    )]
+    Export {
+        /// Directory path to analyze (default: current directory)
+        #[arg(default_value = ".")]
+        path: Option<PathBuf>,
+
+        /// Export format: flat
+        #[arg(long, value_name = "FORMAT", default_value = "flat", value_parser = parse_export_format)]
+        format: ExportFormat,
+
+        /// Skip files without owners
+        #[arg(long)]
+        only_owned: bool,
+
+        /// Custom cache file location
+        #[arg(long, value_name = "FILE", default_value = ".codeowners.cache")]
+        cache_file: Option<PathBuf>,
+    },
+    #[clap(
+        name = "contacts",
+        about = "Export email owners with the number of files they own"
+    )]
+    Contacts {
+        /// Directory path to analyze (default: current directory)
+        #[arg(default_value = ".")]
+        path: Option<PathBuf>,
+
+        /// Output format: json|csv
+        #[arg(long, value_name = "FORMAT", default_value = "json", value_parser = parse_contact_format)]
+        format: ContactFormat,
+
+        /// Add separate local part and domain fields
+        #[arg(long)]
+        split_address: bool,
+
+        /// Custom cache file location
+        #[arg(long, value_name = "FILE", default_value = ".codeowners.cache")]
+        cache_file: Option<PathBuf>,
+    },
+    #[clap(
+        name = "verify-cache",
+        about = "Check that the cache on disk matches the repository"
+    )]
+    VerifyCache {
+        /// Directory path to analyze (default: current directory)
+        #[arg(default_value = ".")]
+        path: Option<PathBuf>,
+
+        /// Custom cache file location
+        #[arg(long, value_name = "FILE", default_value = ".codeowners.cache")]
+        cache_file: Option<PathBuf>,
+
+        /// Print a stale cache's differences but exit with code 0
+        #[arg(long)]
+        exit_zero: bool,
+    },
+    #[clap(
+        name = "flatten",
+        about = "Print one CODEOWNERS file equivalent to all CODEOWNERS files in the repository"
+    )]
+    Flatten {
+        /// Directory path to analyze (default: current directory)
+        #[arg(default_value = ".")]
+        path: Option<PathBuf>,
+
+        /// Custom cache file location
+        #[arg(long, value_name = "FILE", default_value = ".codeowners.cache")]
+        cache_file: Option<PathBuf>,
+    },
+    #[clap(name = "dump", about = "Write the whole cache to stdout")]
+    Dump {
+        /// Directory path to analyze (default: current directory)
+        #[arg(default_value = ".")]
+        path: Option<PathBuf>,
+
+        /// Output format: json|bincode
+        #[arg(long, value_name = "FORMAT", default_value = "bincode", value_parser = parse_cache_encoding)]
+        format: CacheEncoding,
+
+        /// Custom cache file location
+        #[arg(long, value_name = "FILE", default_value = ".codeowners.cache")]
+        cache_file: Option<PathBuf>,
+    },
+    #[clap(
+        name = "cache-info",
+        about = "Print the encoding, hash and sizes of a cache file"
+    )]
+    CacheInfo {
+        /// Cache file to inspect
+        #[arg(value_name = "FILE", default_value = ".codeowners.cache")]
+        file: PathBuf,
+    },
+    #[clap(
+        name = "audit",
+        about = "Report CODEOWNERS rules that are shadowed by other rules"
+    )]
+    Audit {
+        /// Directory path to analyze (default: current directory)
+        #[arg(default_value = ".")]
+        path: Option<PathBuf>,
+
+        /// Also fail on tags not matching this regular expression (default: lowercase letters, digits, `/`, `_` and `-`)
+        #[arg(
+            long,
+            value_name = "RE",
+            num_args = 0..=1,
+            default_missing_value = commands::audit::DEFAULT_TAG_PATTERN
+        )]
+        strict_tags: Option<String>,
+
+        /// Warn about rules with a pattern but no owners, which leave files unowned on purpose
+        #[arg(long)]
+        warn_ownerless: bool,
+
+        /// Output format: text|junit
+        #[arg(long, value_name = "FORMAT", default_value = "text", value_parser = parse_audit_format)]
+        format: AuditFormat,
+
+        /// Custom cache file location
+        #[arg(long, value_name = "FILE", default_value = ".codeowners.cache")]
+        cache_file: Option<PathBuf>,
+
+        /// Print the problems found but exit with code 0
+        #[arg(long)]
+        exit_zero: bool,
+    },
+    #[clap(name = "merge", about = "Merge several caches into one")]
+    Merge {
+        /// Cache files to merge; later caches win on conflicting files and rules
+        #[arg(required = true, value_name = "CACHE")]
+        caches: Vec<PathBuf>,
+
+        /// File to write the merged cache to
+        #[arg(long, short, value_name = "FILE")]
+        output: PathBuf,
+
+        /// Output format: json|bincode
+        #[arg(long, value_name = "FORMAT", default_value = "bincode", value_parser = parse_cache_encoding)]
+        format: CacheEncoding,
+    },
     #[clap(
         name = "infer-owners",
         about = "Infer file ownership from git history and blame information"
@@ -260,7 +714,7 @@ pub fn cli_match() -> Result<()> {
 
     // Execute the subcommand
     match &cli.command {
-        Commands::Codeowners { subcommand } => codeowners(subcommand)?,
+        Commands::Codeowners { subcommand } => codeowners(subcommand, cli.repo_root_auto)?,
         Commands::Completion { subcommand } => {
             let mut app = Cli::command();
             match subcommand {
@@ -273,58 +727,445 @@ pub fn cli_match() -> Result<()> {
                 CompletionSubcommand::Fish => {
                     generate(Fish, &mut app, "codeinput", &mut std::io::stdout());
                 }
+                CompletionSubcommand::Install { shell } => install_completion(*shell, &mut app)?,
             }
         }
-        Commands::Config => commands::config::run()?,
+        Commands::Config { show_sources } => commands::config::run(*show_sources)?,
+        Commands::Version { json: true } => {
+            println!("{}", serde_json::to_string_pretty(&version_info()).unwrap())
+        }
+        Commands::Version { json: false } => {
+            let info = version_info();
+            println!(
+                "codeinput {} ({}, {})",
+                info["version"].as_str().unwrap_or_default(),
+                info["commit"].as_str().unwrap_or("unknown commit"),
+                info["profile"].as_str().unwrap_or_default()
+            );
+        }
     }
 
     Ok(())
 }
 
+/// Version, git commit, build profile and enabled cargo features of this binary
+fn version_info() -> serde_json::Value {
+    let commit = env!("CI_GIT_COMMIT");
+    let profile = if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    };
+    let features: Vec<&str> = [
+        ("termlog", cfg!(feature = "termlog")),
+        ("journald", cfg!(feature = "journald")),
+        ("syslog", cfg!(feature = "syslog")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect();
+
+    serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "commit": (!commit.is_empty()).then_some(commit),
+        "profile": profile,
+        "features": features,
+    })
+}
+
+/// Write the completion script for `shell` into its conventional completion directory.
+/// Falls back to printing the script to stdout when no directory can be determined.
+fn install_completion(shell: Shell, app: &mut clap::Command) -> Result<()> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+
+    let Some(target) = home.and_then(|home| completion_install_path(shell, &home)) else {
+        eprintln!(
+            "Could not determine the completion directory for {}, printing to stdout instead",
+            shell
+        );
+        generate(shell, app, "codeinput", &mut std::io::stdout());
+        return Ok(());
+    };
+
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut file = std::fs::File::create(&target)?;
+    generate(shell, app, "codeinput", &mut file);
+
+    println!("Installed {} completions to {}", shell, target.display());
+
+    Ok(())
+}
+
+/// Conventional per-user completion script location for a shell
+fn completion_install_path(shell: Shell, home: &Path) -> Option<PathBuf> {
+    let xdg_dir = |var: &str, default: &str| {
+        std::env::var_os(var)
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .unwrap_or_else(|| home.join(default))
+    };
+
+    match shell {
+        Shell::Bash => Some(
+            xdg_dir("XDG_DATA_HOME", ".local/share").join("bash-completion/completions/codeinput"),
+        ),
+        Shell::Zsh => {
+            // Prefer a user-owned directory already on $fpath, if exported
+            let fpath_dir = std::env::var("FPATH").ok().and_then(|fpath| {
+                fpath
+                    .split(':')
+                    .map(PathBuf::from)
+                    .find(|dir| dir.starts_with(home))
+            });
+            Some(
+                fpath_dir
+                    .unwrap_or_else(|| home.join(".zsh/completions"))
+                    .join("_codeinput"),
+            )
+        }
+        Shell::Fish => {
+            Some(xdg_dir("XDG_CONFIG_HOME", ".config").join("fish/completions/codeinput.fish"))
+        }
+        _ => None,
+    }
+}
+
 /// Handle codeowners subcommands
-pub(crate) fn codeowners(subcommand: &CodeownersSubcommand) -> Result<()> {
+pub(crate) fn codeowners(subcommand: &CodeownersSubcommand, repo_root_auto: bool) -> Result<()> {
+    // Resolve the analysis root, optionally walking up to the git repository root
+    let root = |path: Option<&Path>| -> PathBuf {
+        let path = path.unwrap_or_else(|| Path::new("."));
+        if repo_root_auto {
+            find_repo_root(path)
+        } else {
+            path.to_path_buf()
+        }
+    };
+
     match subcommand {
+        CodeownersSubcommand::Parse { stdin: true, .. } => commands::parse::run_stdin(),
         CodeownersSubcommand::Parse {
             path,
             cache_file,
             format,
-        } => commands::parse::run(path, cache_file.as_deref(), *format),
+            dry_run,
+            profile,
+            ignore_case,
+            owner_merge,
+            anchor,
+            follow_links,
+            verbose,
+            changed_only,
+            alias_map,
+            append,
+            identity_map,
+            dedupe_paths,
+            ..
+        } => commands::parse::run(
+            &root(Some(path)),
+            cache_file.as_deref(),
+            *format,
+            &commands::parse::ParseOptions {
+                dry_run: *dry_run,
+                profile: *profile,
+                ignore_case: *ignore_case,
+                owner_merge: *owner_merge,
+                anchor: *anchor,
+                follow_links: *follow_links,
+                verbose: *verbose,
+                changed_only: *changed_only,
+                alias_map: alias_map.as_deref(),
+                append: *append,
+                identity_map: identity_map.as_deref(),
+                dedupe_paths: *dedupe_paths,
+            },
+        ),
         CodeownersSubcommand::ListFiles {
             path,
             tags,
+            exclude_tags,
             owners,
+            only,
+            exclude,
+            owner_match,
+            owner_regex,
+            tag_regex,
             unowned,
             show_all,
+            no_governing_codeowners,
+            spof,
+            min_impact,
+            modified_by,
+            format,
+            cache_file,
+            with_rule,
+            owner_display,
+            count_only,
+            parseable,
+            hyperlinks,
+            json_envelope,
+            no_header,
+            json_shape,
+            output,
+            index_out,
+        } => {
+            // Compile the regular expressions before loading the cache
+            let owner_regex = owner_regex
+                .as_deref()
+                .map(commands::list_files::compile_regex)
+                .transpose()?;
+            let tag_regex = tag_regex
+                .as_deref()
+                .map(commands::list_files::compile_regex)
+                .transpose()?;
+            let repo = root(path.as_deref());
+            let paths = commands::list_files::PathGlobs::new(&repo, only, exclude)?;
+            commands::list_files::run(
+                Some(&repo),
+                &commands::list_files::FileFilter {
+                    tags: tags.as_deref(),
+                    exclude_tags: exclude_tags.as_deref(),
+                    owners: owners.as_deref(),
+                    owner_match: *owner_match,
+                    owner_regex: owner_regex.as_ref(),
+                    tag_regex: tag_regex.as_ref(),
+                    unowned: *unowned,
+                    show_all: *show_all,
+                    no_governing_codeowners: *no_governing_codeowners,
+                    spof: *spof,
+                    min_impact: *min_impact,
+                    paths: paths.as_ref(),
+                    modified_by: modified_by.as_deref(),
+                },
+                format,
+                cache_file.as_deref(),
+                &commands::list_files::ListOptions {
+                    with_rule: *with_rule,
+                    owner_display: *owner_display,
+                    count_only: *count_only,
+                    parseable: *parseable,
+                    hyperlinks: *hyperlinks,
+                    json_envelope: *json_envelope,
+                    no_header: *no_header,
+                    json_shape: *json_shape,
+                    output: output.as_deref(),
+                    index_out: index_out.as_deref(),
+                },
+            )
+        }
+        CodeownersSubcommand::ListOwners {
+            path,
+            show_unknown: true,
+            suggest,
             format,
             cache_file,
-        } => commands::list_files::run(
-            path.as_deref(),
-            tags.as_deref(),
-            owners.as_deref(),
-            *unowned,
-            *show_all,
+            ..
+        } => commands::list_owners::run_unknown(
+            Some(&root(path.as_deref())),
             format,
             cache_file.as_deref(),
+            *suggest,
         ),
         CodeownersSubcommand::ListOwners {
             path,
+            orphaned: true,
+            format,
+            cache_file,
+            ..
+        } => commands::list_owners::run_orphaned(
+            Some(&root(path.as_deref())),
+            format,
+            cache_file.as_deref(),
+        ),
+        CodeownersSubcommand::ListOwners {
+            path,
+            email_domains: true,
+            format,
+            cache_file,
+            ..
+        } => commands::list_owners::run_email_domains(
+            Some(&root(path.as_deref())),
+            format,
+            cache_file.as_deref(),
+        ),
+        CodeownersSubcommand::ListOwners {
+            path,
+            owner_type,
+            group_by_type,
+            max_files,
+            with_tags,
+            format,
+            cache_file,
+            json_envelope,
+            no_header,
+            sort,
+            ..
+        } => commands::list_owners::run(
+            Some(&root(path.as_deref())),
+            owner_type.as_ref(),
+            format,
+            cache_file.as_deref(),
+            &commands::list_owners::OwnerListOptions {
+                group_by_type: *group_by_type,
+                max_files: *max_files,
+                with_tags: *with_tags,
+                json_envelope: *json_envelope,
+                no_header: *no_header,
+                sort: *sort,
+            },
+        ),
+        CodeownersSubcommand::ListTags {
+            path,
+            unused: true,
             format,
             cache_file,
-        } => commands::list_owners::run(path.as_deref(), format, cache_file.as_deref()),
+            ..
+        } => commands::list_tags::run_unused(
+            Some(&root(path.as_deref())),
+            format,
+            cache_file.as_deref(),
+        ),
         CodeownersSubcommand::ListTags {
             path,
+            cooccurrence: true,
             format,
             cache_file,
-        } => commands::list_tags::run(path.as_deref(), format, cache_file.as_deref()),
-        CodeownersSubcommand::ListRules { format, cache_file } => {
-            commands::list_rules::run(format, cache_file.as_deref())
-        }
+            ..
+        } => commands::list_tags::run_cooccurrence(
+            Some(&root(path.as_deref())),
+            format,
+            cache_file.as_deref(),
+        ),
+        CodeownersSubcommand::ListTags {
+            path,
+            max_files,
+            format,
+            cache_file,
+            json_envelope,
+            no_header,
+            ..
+        } => commands::list_tags::run(
+            Some(&root(path.as_deref())),
+            *max_files,
+            format,
+            cache_file.as_deref(),
+            *json_envelope,
+            *no_header,
+        ),
+        CodeownersSubcommand::ListRules {
+            path,
+            format,
+            cache_file,
+        } => commands::list_rules::run(Some(&root(path.as_deref())), format, cache_file.as_deref()),
         CodeownersSubcommand::Inspect {
             file_path,
             repo,
             format,
             cache_file,
-        } => commands::inspect::run(file_path, repo.as_deref(), format, cache_file.as_deref()),
+        } => {
+            // A relative path still names a file below the given directory, not the detected root
+            let file_path = if repo_root_auto && file_path.is_relative() {
+                std::env::current_dir()?
+                    .join(repo.as_deref().unwrap_or_else(|| Path::new(".")))
+                    .join(file_path)
+            } else {
+                file_path.clone()
+            };
+            commands::inspect::run(
+                &file_path,
+                Some(&root(repo.as_deref())),
+                format,
+                cache_file.as_deref(),
+            )
+        }
+        CodeownersSubcommand::Resolve {
+            paths_from,
+            repo,
+            format,
+            cache_file,
+        } => commands::resolve::run(
+            paths_from,
+            Some(&root(repo.as_deref())),
+            format,
+            cache_file.as_deref(),
+        ),
+        CodeownersSubcommand::Mentions {
+            paths_from,
+            repo,
+            cache_file,
+        } => commands::mentions::run(
+            paths_from,
+            Some(&root(repo.as_deref())),
+            cache_file.as_deref(),
+        ),
+        CodeownersSubcommand::Export {
+            path,
+            format,
+            only_owned,
+            cache_file,
+        } => commands::export::run(
+            Some(&root(path.as_deref())),
+            *format,
+            *only_owned,
+            cache_file.as_deref(),
+        ),
+        CodeownersSubcommand::Contacts {
+            path,
+            format,
+            split_address,
+            cache_file,
+        } => commands::contacts::run(
+            Some(&root(path.as_deref())),
+            *format,
+            *split_address,
+            cache_file.as_deref(),
+        ),
+        CodeownersSubcommand::VerifyCache {
+            path,
+            cache_file,
+            exit_zero,
+        } => gate(
+            commands::verify_cache::run(Some(&root(path.as_deref())), cache_file.as_deref()),
+            *exit_zero,
+        ),
+        CodeownersSubcommand::Merge {
+            caches,
+            output,
+            format,
+        } => commands::merge::run(caches, output, *format),
+        CodeownersSubcommand::Flatten { path, cache_file } => {
+            commands::flatten::run(Some(&root(path.as_deref())), cache_file.as_deref())
+        }
+        CodeownersSubcommand::Dump {
+            path,
+            format,
+            cache_file,
+        } => commands::dump::run(Some(&root(path.as_deref())), *format, cache_file.as_deref()),
+        CodeownersSubcommand::CacheInfo { file } => commands::cache_info::run(file),
+        CodeownersSubcommand::Audit {
+            path,
+            strict_tags,
+            warn_ownerless,
+            format,
+            cache_file,
+            exit_zero,
+        } => {
+            let strict_tags = strict_tags
+                .as_deref()
+                .map(commands::list_files::compile_regex)
+                .transpose()?;
+            let result = commands::audit::run(
+                Some(&root(path.as_deref())),
+                strict_tags.as_ref(),
+                *warn_ownerless,
+                *format,
+                cache_file.as_deref(),
+            );
+            gate(result, *exit_zero)
+        }
         CodeownersSubcommand::InferOwners {
             path,
             scope,
@@ -335,7 +1176,7 @@ pub(crate) fn codeowners(subcommand: &CodeownersSubcommand) -> Result<()> {
             cache_file,
             output,
         } => commands::infer_owners::run(
-            path.as_deref(),
+            Some(&root(path.as_deref())),
             scope,
             algorithm,
             *lookback_days,
@@ -352,7 +1193,44 @@ fn parse_output_format(s: &str) -> std::result::Result<OutputFormat, String> {
         "text" => Ok(OutputFormat::Text),
         "json" => Ok(OutputFormat::Json),
         "bincode" => Ok(OutputFormat::Bincode),
-        _ => Err(format!("Invalid output format: {}", s)),
+        "html" => Ok(OutputFormat::Html),
+        _ => Err(format!(
+            "Invalid output format: {}. Valid options: text, json, bincode, html",
+            s
+        )),
+    }
+}
+
+/// Output formats of `list-tags`, the only command with a graph to render as dot
+fn parse_tags_format(s: &str) -> std::result::Result<OutputFormat, String> {
+    match s.to_lowercase().as_str() {
+        "dot" => Ok(OutputFormat::Dot),
+        _ => parse_output_format(s).map_err(|_| {
+            format!(
+                "Invalid output format: {}. Valid options: text, json, bincode, html, dot",
+                s
+            )
+        }),
+    }
+}
+
+fn parse_inspect_format(s: &str) -> std::result::Result<OutputFormat, String> {
+    match parse_output_format(s) {
+        Ok(OutputFormat::Html) | Err(_) => Err(format!(
+            "Invalid output format: {}. Valid options: text, json, bincode",
+            s
+        )),
+        format => format,
+    }
+}
+
+fn parse_resolve_format(s: &str) -> std::result::Result<OutputFormat, String> {
+    match parse_output_format(s) {
+        Ok(OutputFormat::Bincode) | Err(_) => Err(format!(
+            "Invalid output format: {}. Valid options: text, json, html",
+            s
+        )),
+        format => format,
     }
 }
 
@@ -364,6 +1242,18 @@ fn parse_cache_encoding(s: &str) -> std::result::Result<CacheEncoding, String> {
     }
 }
 
+fn parse_shell(s: &str) -> std::result::Result<Shell, String> {
+    match s.to_lowercase().as_str() {
+        "bash" => Ok(Shell::Bash),
+        "zsh" => Ok(Shell::Zsh),
+        "fish" => Ok(Shell::Fish),
+        _ => Err(format!(
+            "Invalid shell: {}. Valid options: bash, zsh, fish",
+            s
+        )),
+    }
+}
+
 fn parse_infer_scope(s: &str) -> std::result::Result<InferScope, String> {
     match s.to_lowercase().as_str() {
         "all" => Ok(InferScope::All),
@@ -372,6 +1262,132 @@ fn parse_infer_scope(s: &str) -> std::result::Result<InferScope, String> {
     }
 }
 
+fn parse_audit_format(s: &str) -> std::result::Result<AuditFormat, String> {
+    match s.to_lowercase().as_str() {
+        "text" => Ok(AuditFormat::Text),
+        "junit" => Ok(AuditFormat::Junit),
+        _ => Err(format!(
+            "Invalid audit format: {}. Valid options: text, junit",
+            s
+        )),
+    }
+}
+
+fn parse_export_format(s: &str) -> std::result::Result<ExportFormat, String> {
+    match s.to_lowercase().as_str() {
+        "flat" => Ok(ExportFormat::Flat),
+        _ => Err(format!("Invalid export format: {}. Valid options: flat", s)),
+    }
+}
+
+fn parse_contact_format(s: &str) -> std::result::Result<ContactFormat, String> {
+    match s.to_lowercase().as_str() {
+        "json" => Ok(ContactFormat::Json),
+        "csv" => Ok(ContactFormat::Csv),
+        _ => Err(format!(
+            "Invalid contact format: {}. Valid options: json, csv",
+            s
+        )),
+    }
+}
+
+fn parse_owner_match(s: &str) -> std::result::Result<OwnerMatch, String> {
+    match s.to_lowercase().as_str() {
+        "any" => Ok(OwnerMatch::Any),
+        "all" => Ok(OwnerMatch::All),
+        _ => Err(format!(
+            "Invalid owner match: {}. Valid options: any, all",
+            s
+        )),
+    }
+}
+
+/// Result of a gating command, which fails when it finds problems
+///
+/// With `exit_zero`, a validation failure is reported on stderr but not
+/// returned, so the process exits with code 0 after printing the findings.
+/// Other failures, e.g. a cache that can't be written, are still returned.
+fn gate(result: Result<()>, exit_zero: bool) -> Result<()> {
+    match result {
+        Err(error) if exit_zero && error.kind == ErrorKind::Validation => {
+            eprintln!("{} (ignored with --exit-zero)", error);
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+fn parse_anchor(s: &str) -> std::result::Result<PatternAnchor, String> {
+    match s.to_lowercase().as_str() {
+        "file-dir" => Ok(PatternAnchor::FileDir),
+        "repo-root" => Ok(PatternAnchor::RepoRoot),
+        _ => Err(format!(
+            "Invalid anchor: {}. Valid options: file-dir, repo-root",
+            s
+        )),
+    }
+}
+
+fn parse_json_shape(s: &str) -> std::result::Result<JsonShape, String> {
+    match s.to_lowercase().as_str() {
+        "array" => Ok(JsonShape::Array),
+        "map" => Ok(JsonShape::Map),
+        _ => Err(format!(
+            "Invalid JSON shape: {}. Valid options: array, map",
+            s
+        )),
+    }
+}
+
+fn parse_owner_merge(s: &str) -> std::result::Result<OwnerMerge, String> {
+    match s.to_lowercase().as_str() {
+        "winner" => Ok(OwnerMerge::Winner),
+        "union" => Ok(OwnerMerge::Union),
+        _ => Err(format!(
+            "Invalid owner merge: {}. Valid options: winner, union",
+            s
+        )),
+    }
+}
+
+fn parse_owner_type(s: &str) -> std::result::Result<OwnerType, String> {
+    match s.to_lowercase().as_str() {
+        "user" => Ok(OwnerType::User),
+        "team" => Ok(OwnerType::Team),
+        "email" => Ok(OwnerType::Email),
+        "unowned" => Ok(OwnerType::Unowned),
+        "unknown" => Ok(OwnerType::Unknown),
+        _ => Err(format!(
+            "Invalid owner type: {}. Valid options: user, team, email, unowned, unknown",
+            s
+        )),
+    }
+}
+
+fn parse_owner_display(s: &str) -> std::result::Result<OwnerDisplay, String> {
+    match s.to_lowercase().as_str() {
+        "identifier" => Ok(OwnerDisplay::Identifier),
+        "type" => Ok(OwnerDisplay::Type),
+        "both" => Ok(OwnerDisplay::Both),
+        _ => Err(format!(
+            "Invalid owner display: {}. Valid options: identifier, type, both",
+            s
+        )),
+    }
+}
+
+fn parse_owner_sort(s: &str) -> std::result::Result<OwnerSort, String> {
+    match s.to_lowercase().as_str() {
+        "files" => Ok(OwnerSort::Files),
+        "type" => Ok(OwnerSort::Type),
+        "name" => Ok(OwnerSort::Name),
+        _ => Err(format!(
+            "Invalid owner sort: {}. Valid options: files, type, name",
+            s
+        )),
+    }
+}
+
 fn parse_infer_algorithm(s: &str) -> std::result::Result<InferAlgorithm, String> {
     match s.to_lowercase().as_str() {
         "commits" => Ok(InferAlgorithm::Commits),
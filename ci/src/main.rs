@@ -7,10 +7,39 @@ extern crate better_panic;
 pub(crate) mod cli;
 
 use codeinput::utils::app_config::AppConfig;
-use codeinput::utils::error::Result;
+use codeinput::utils::error::{Error, ErrorKind, Result};
+
+/// Exit code for failures without a more specific kind
+const EXIT_FAILURE: i32 = 1;
+/// Exit code when input or repository contents fail validation
+const EXIT_VALIDATION: i32 = 2;
+/// Exit code when the cache file is missing or unreadable
+const EXIT_CACHE_MISSING: i32 = 3;
+/// Exit code for filesystem IO failures
+const EXIT_IO: i32 = 4;
+
+/// Map an error to its documented process exit code
+fn exit_code(error: &Error) -> i32 {
+    match error.kind {
+        ErrorKind::Other => EXIT_FAILURE,
+        ErrorKind::Validation => EXIT_VALIDATION,
+        ErrorKind::CacheMissing => EXIT_CACHE_MISSING,
+        ErrorKind::Io => EXIT_IO,
+    }
+}
 
 /// The main entry point of the application.
-fn main() -> Result<()> {
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("Error: {}", error);
+        if let Some(source) = std::error::Error::source(&error) {
+            eprintln!("Caused by: {}", source);
+        }
+        std::process::exit(exit_code(&error));
+    }
+}
+
+fn run() -> Result<()> {
     // Human Panic. Only enabled when *not* debugging.
     #[cfg(not(debug_assertions))]
     {
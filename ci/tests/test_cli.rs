@@ -1 +1,535 @@
-// CLI tests placeholder - currently no tests implemented
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn test_completion_install_writes_to_home() {
+    let home = TempDir::new().unwrap();
+
+    Command::cargo_bin("ci")
+        .unwrap()
+        .env("HOME", home.path())
+        .env_remove("XDG_DATA_HOME")
+        .env_remove("XDG_CONFIG_HOME")
+        .args(["completion", "install", "fish"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed fish completions to"));
+
+    let script = home.path().join(".config/fish/completions/codeinput.fish");
+    assert!(script.is_file());
+    assert!(std::fs::read_to_string(script)
+        .unwrap()
+        .contains("complete -c codeinput"));
+}
+
+#[test]
+fn test_inspect_outside_repo_exits_with_validation_code() {
+    let repo = TempDir::new().unwrap();
+    git2::Repository::init(repo.path()).unwrap();
+    std::fs::write(repo.path().join("CODEOWNERS"), "*.rs @rust-team\n").unwrap();
+    std::fs::write(repo.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+    Command::cargo_bin("ci")
+        .unwrap()
+        .args(["codeowners", "inspect", "/outside/the/repo.rs", "--repo"])
+        .arg(repo.path())
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("is not within repository"));
+}
+
+#[test]
+fn test_inspect_from_subdirectory_with_repo_root_auto() {
+    let repo = TempDir::new().unwrap();
+    git2::Repository::init(repo.path()).unwrap();
+    let src = repo.path().join("src");
+    std::fs::create_dir_all(&src).unwrap();
+    std::fs::write(
+        repo.path().join("CODEOWNERS"),
+        "/main.rs @root-team\n/src/ @src-team\n",
+    )
+    .unwrap();
+    // Same file name at the root, which the path must not be taken relative to
+    std::fs::write(repo.path().join("main.rs"), "fn main() {}\n").unwrap();
+    std::fs::write(src.join("main.rs"), "fn main() {}\n").unwrap();
+
+    Command::cargo_bin("ci")
+        .unwrap()
+        .current_dir(&src)
+        .args([
+            "--repo-root-auto",
+            "codeowners",
+            "inspect",
+            "main.rs",
+            "--format",
+            "json",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"file_path\": \"src/main.rs\""))
+        .stdout(predicate::str::contains("@src-team"))
+        .stdout(predicate::str::contains("@root-team").not());
+
+    // The cache is stored at the detected root
+    assert!(repo.path().join(".codeowners.cache").is_file());
+    assert!(!src.join(".codeowners.cache").exists());
+}
+
+#[test]
+fn test_audit_exit_zero_prints_failures_and_succeeds() {
+    let repo = TempDir::new().unwrap();
+    git2::Repository::init(repo.path()).unwrap();
+    std::fs::write(repo.path().join("CODEOWNERS"), "*.rs @rust-team #Rust\n").unwrap();
+    std::fs::write(repo.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+    let audit = || {
+        let mut command = Command::cargo_bin("ci").unwrap();
+        command
+            .args(["codeowners", "audit"])
+            .arg(repo.path())
+            .arg("--strict-tags");
+        command
+    };
+
+    audit()
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains("#Rust"));
+    audit()
+        .arg("--exit-zero")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#Rust"))
+        .stderr(predicate::str::contains("ignored with --exit-zero"));
+}
+
+#[test]
+fn test_audit_junit_without_cache_prints_only_xml() {
+    let repo = TempDir::new().unwrap();
+    git2::Repository::init(repo.path()).unwrap();
+    std::fs::write(repo.path().join("CODEOWNERS"), "*.rs @rust-team\n").unwrap();
+    std::fs::write(repo.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+    let output = Command::cargo_bin("ci")
+        .unwrap()
+        .args(["codeowners", "audit"])
+        .arg(repo.path())
+        .args(["--format", "junit"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(repo.path().join(".codeowners.cache").is_file());
+
+    // Building the missing cache reports its progress on stderr only
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let report = roxmltree::Document::parse(&stdout).unwrap();
+    assert_eq!(report.root_element().tag_name().name(), "testsuites");
+    assert_eq!(report.root_element().attribute("tests"), Some("1"));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Parsing CODEOWNERS files"));
+}
+
+#[test]
+fn test_append_per_service_cache_is_reused_by_list_files() {
+    let repo = TempDir::new().unwrap();
+    git2::Repository::init(repo.path()).unwrap();
+    std::fs::write(repo.path().join("README.md"), "# Monorepo\n").unwrap();
+    for (service, owner) in [("api", "@org/backend"), ("web", "@org/frontend")] {
+        let service_path = repo.path().join(service);
+        std::fs::create_dir_all(&service_path).unwrap();
+        std::fs::write(service_path.join("CODEOWNERS"), format!("* {}\n", owner)).unwrap();
+        std::fs::write(service_path.join("main.rs"), "fn main() {}\n").unwrap();
+    }
+
+    // One CI job per service, each appending to the cache at the repository root
+    for service in ["api", "web"] {
+        Command::cargo_bin("ci")
+            .unwrap()
+            .current_dir(repo.path())
+            .args(["codeowners", "parse", service, "--append", "--cache-file"])
+            .arg(repo.path().join(".codeowners.cache"))
+            .assert()
+            .success();
+    }
+
+    Command::cargo_bin("ci")
+        .unwrap()
+        .current_dir(repo.path())
+        .args(["codeowners", "list-files", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("@org/backend"))
+        .stdout(predicate::str::contains("@org/frontend"))
+        // A rebuild would have walked the whole repository
+        .stdout(predicate::str::contains("README.md").not())
+        .stderr(predicate::str::contains("Parsing CODEOWNERS").not());
+}
+
+#[test]
+fn test_max_output_bytes_caps_owner_and_tag_reports() {
+    let repo = TempDir::new().unwrap();
+    git2::Repository::init(repo.path()).unwrap();
+    std::fs::write(
+        repo.path().join("CODEOWNERS"),
+        "*.rs @rust-team #rust\n*.md @docs-team docs@example.com org/docs #docs\n",
+    )
+    .unwrap();
+    std::fs::write(repo.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+    for report in [
+        ["list-owners", "--email-domains"],
+        ["list-owners", "--show-unknown"],
+        ["list-owners", "--orphaned"],
+        ["list-tags", "--unused"],
+    ] {
+        Command::cargo_bin("ci")
+            .unwrap()
+            .args(["--max-output-bytes", "8", "codeowners", report[0]])
+            .arg(repo.path())
+            .args([report[1], "--format", "json"])
+            .assert()
+            .code(4)
+            .stderr(predicate::str::contains("--max-output-bytes"));
+    }
+}
+
+#[test]
+fn test_max_output_bytes_caps_every_command() {
+    let repo = TempDir::new().unwrap();
+    git2::Repository::init(repo.path()).unwrap();
+    std::fs::write(
+        repo.path().join("CODEOWNERS"),
+        "*.rs @rust-team #rust\n*.md docs@example.com #docs\n",
+    )
+    .unwrap();
+    std::fs::write(repo.path().join("main.rs"), "fn main() {}\n").unwrap();
+    std::fs::write(repo.path().join("README.md"), "# Readme\n").unwrap();
+
+    for command in [
+        "list-rules",
+        "export",
+        "contacts",
+        "flatten",
+        "dump",
+        "audit",
+    ] {
+        Command::cargo_bin("ci")
+            .unwrap()
+            .args(["--max-output-bytes", "8", "codeowners", command])
+            .arg(repo.path())
+            .assert()
+            .code(4)
+            .stderr(predicate::str::contains("--max-output-bytes"));
+    }
+
+    Command::cargo_bin("ci")
+        .unwrap()
+        .args(["--max-output-bytes", "8", "codeowners", "inspect", "main.rs"])
+        .arg("--repo")
+        .arg(repo.path())
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("--max-output-bytes"));
+
+    for command in ["resolve", "mentions"] {
+        assert_cmd::Command::cargo_bin("ci")
+            .unwrap()
+            .args(["--max-output-bytes", "8", "codeowners", command])
+            .args(["--paths-from", "-", "--repo"])
+            .arg(repo.path())
+            .write_stdin("main.rs\nREADME.md\n")
+            .assert()
+            .code(4)
+            .stderr(predicate::str::contains("--max-output-bytes"));
+    }
+}
+
+#[test]
+fn test_parse_stdin_prints_entries_as_json() {
+    let dir = TempDir::new().unwrap();
+
+    assert_cmd::Command::cargo_bin("ci")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["codeowners", "parse", "--stdin"])
+        .write_stdin("# comment\n*.rs @rust-team #rust\n/docs/ @docs-team\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"pattern\": \"*.rs\""))
+        .stdout(predicate::str::contains("\"pattern\": \"/docs/\""))
+        .stdout(predicate::str::contains("<stdin>"));
+
+    // Nothing is written to the working directory
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+    // Options that only apply to walking a repository are refused
+    for flag in ["--follow-links", "--dedupe-paths", "--changed-only"] {
+        assert_cmd::Command::cargo_bin("ci")
+            .unwrap()
+            .current_dir(dir.path())
+            .args(["codeowners", "parse", "--stdin", flag])
+            .write_stdin("*.rs @rust-team\n")
+            .assert()
+            .code(2)
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+}
+
+#[test]
+fn test_env_vars_override_config_file() {
+    let dir = TempDir::new().unwrap();
+    let config_file = dir.path().join("codeinput.toml");
+    std::fs::write(&config_file, "cache_file = \"from-file.cache\"\n").unwrap();
+
+    Command::cargo_bin("ci")
+        .unwrap()
+        .env("CODEINPUT_CACHE_FILE", "from-env.cache")
+        .env("CODEINPUT_SKIP_DIRS", "node_modules,target")
+        .arg("--config")
+        .arg(&config_file)
+        .arg("config")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("from-env.cache"))
+        .stdout(predicate::str::contains("node_modules, target"))
+        .stdout(predicate::str::contains("from-file.cache").not());
+}
+
+#[test]
+fn test_config_read_from_embedded_tool_table() {
+    let dir = TempDir::new().unwrap();
+    let config_file = dir.path().join("pyproject.toml");
+    std::fs::write(
+        &config_file,
+        "[project]\nname = \"demo\"\n\n[tool.codeinput]\ncache_file = \"from-table.cache\"\n\n[tool.other]\ncache_file = \"other-tool.cache\"\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("ci")
+        .unwrap()
+        .env_remove("CODEINPUT_CACHE_FILE")
+        .arg("--config")
+        .arg(&config_file)
+        .arg("config")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("from-table.cache"))
+        .stdout(predicate::str::contains("other-tool.cache").not());
+}
+
+#[test]
+fn test_config_show_sources_labels_each_layer() {
+    let dir = TempDir::new().unwrap();
+    let config_file = dir.path().join("codeinput.toml");
+    std::fs::write(
+        &config_file,
+        "cache_file = \"from-file.cache\"\nhash_mode = \"none\"\n",
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("ci")
+        .unwrap()
+        .env("CODEINPUT_CACHE_TTL_SECS", "60")
+        .arg("--config")
+        .arg(&config_file)
+        .args(["--hash-mode", "head-only", "config", "--show-sources"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let source_of = |setting: &str| {
+        let row = stdout
+            .lines()
+            .find(|line| line.contains(setting))
+            .unwrap_or_else(|| panic!("no row for {}", setting));
+        row.split('│').map(str::trim).nth(3).unwrap().to_string()
+    };
+    assert_eq!(source_of("Hash Mode"), "cli");
+    assert_eq!(source_of("Cache TTL"), "env");
+    assert_eq!(source_of("Cache File"), "file");
+    assert_eq!(source_of("Parallel Threshold"), "default");
+}
+
+#[test]
+fn test_version_json_reports_build_info() {
+    let output = Command::cargo_bin("ci")
+        .unwrap()
+        .args(["version", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let info: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+    assert!(matches!(
+        info["profile"].as_str(),
+        Some("debug" | "release")
+    ));
+    assert!(info["features"].is_array());
+}
+
+#[test]
+fn test_dump_stale_cache_prints_only_json() {
+    let repo = TempDir::new().unwrap();
+    let git = git2::Repository::init(repo.path()).unwrap();
+    std::fs::write(repo.path().join("CODEOWNERS"), "*.rs @rust-team\n").unwrap();
+    std::fs::write(repo.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+    Command::cargo_bin("ci")
+        .unwrap()
+        .args(["codeowners", "parse"])
+        .arg(repo.path())
+        .assert()
+        .success();
+    // Staging a new file makes the cache stale
+    std::fs::write(repo.path().join("lib.rs"), "pub fn lib() {}\n").unwrap();
+    let mut index = git.index().unwrap();
+    index.add_path(std::path::Path::new("lib.rs")).unwrap();
+    index.write().unwrap();
+
+    let output = Command::cargo_bin("ci")
+        .unwrap()
+        .args(["codeowners", "dump"])
+        .arg(repo.path())
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let dumped: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(dumped["files"].as_array().map(Vec::len), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Parsing CODEOWNERS files"));
+}
+
+#[test]
+fn test_mentions_without_cache_prints_only_mentions() {
+    let repo = TempDir::new().unwrap();
+    git2::Repository::init(repo.path()).unwrap();
+    std::fs::write(repo.path().join("CODEOWNERS"), "*.rs @org/backend @alice\n").unwrap();
+    std::fs::write(repo.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+    assert_cmd::Command::cargo_bin("ci")
+        .unwrap()
+        .current_dir(repo.path())
+        .args(["codeowners", "mentions", "--paths-from", "-"])
+        .write_stdin("main.rs\n")
+        .assert()
+        .success()
+        .stdout("@org/backend @alice\n")
+        .stderr(predicate::str::contains("Parsing CODEOWNERS files"));
+}
+
+#[test]
+fn test_contacts_csv_without_cache_prints_only_csv() {
+    let repo = TempDir::new().unwrap();
+    git2::Repository::init(repo.path()).unwrap();
+    std::fs::write(repo.path().join("CODEOWNERS"), "*.rs alice@example.com\n").unwrap();
+    std::fs::write(repo.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+    Command::cargo_bin("ci")
+        .unwrap()
+        .args(["codeowners", "contacts"])
+        .arg(repo.path())
+        .args(["--format", "csv"])
+        .assert()
+        .success()
+        .stdout("email,files\nalice@example.com,1\n")
+        .stderr(predicate::str::contains("Parsing CODEOWNERS files"));
+}
+
+#[test]
+fn test_export_without_cache_prints_only_flat_lines() {
+    let repo = TempDir::new().unwrap();
+    git2::Repository::init(repo.path()).unwrap();
+    std::fs::write(repo.path().join("CODEOWNERS"), "*.rs @rust-team\n").unwrap();
+    std::fs::write(repo.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+    Command::cargo_bin("ci")
+        .unwrap()
+        .current_dir(repo.path())
+        .args(["codeowners", "export", "--only-owned"])
+        .assert()
+        .success()
+        .stdout("./main.rs\t@rust-team\n")
+        .stderr(predicate::str::contains("Parsing CODEOWNERS files"));
+}
+
+#[test]
+fn test_dot_format_is_only_accepted_by_list_tags() {
+    let repo = TempDir::new().unwrap();
+
+    for command in ["list-files", "list-owners", "list-rules", "inspect"] {
+        let mut args = vec!["codeowners", command];
+        if command == "inspect" {
+            args.push("main.rs");
+        }
+        Command::cargo_bin("ci")
+            .unwrap()
+            .current_dir(repo.path())
+            .args(&args)
+            .args(["--format", "dot"])
+            .assert()
+            .code(2)
+            .stderr(predicate::str::contains("Invalid output format: dot"));
+    }
+    // Rejected before a cache is built
+    assert!(!repo.path().join(".codeowners.cache").exists());
+
+    git2::Repository::init(repo.path()).unwrap();
+    std::fs::write(
+        repo.path().join("CODEOWNERS"),
+        "*.rs @rust-team #rust #core\n",
+    )
+    .unwrap();
+    std::fs::write(repo.path().join("main.rs"), "fn main() {}\n").unwrap();
+    Command::cargo_bin("ci")
+        .unwrap()
+        .current_dir(repo.path())
+        .args([
+            "codeowners",
+            "list-tags",
+            "--cooccurrence",
+            "--format",
+            "dot",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("graph"));
+}
+
+#[test]
+fn test_unsupported_formats_are_rejected_by_inspect_and_resolve() {
+    let repo = TempDir::new().unwrap();
+
+    Command::cargo_bin("ci")
+        .unwrap()
+        .current_dir(repo.path())
+        .args(["codeowners", "inspect", "main.rs", "--format", "html"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "Valid options: text, json, bincode",
+        ));
+    Command::cargo_bin("ci")
+        .unwrap()
+        .current_dir(repo.path())
+        .args([
+            "codeowners",
+            "resolve",
+            "--paths-from",
+            "-",
+            "--format",
+            "bincode",
+        ])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Valid options: text, json, html"));
+    // Rejected before a cache is built
+    assert!(!repo.path().join(".codeowners.cache").exists());
+}
@@ -0,0 +1,227 @@
+use codeinput::core::cache::build_cache;
+use codeinput::core::common::{find_codeowners_files, find_files};
+use codeinput::core::owner_resolver::build_owners_map;
+use codeinput::core::parser::parse_codeowners;
+use codeinput::core::tag_resolver::build_tags_map;
+use codeinput::core::types::{
+    codeowners_entry_matchers, codeowners_entry_to_matcher, CodeownersEntry,
+    CodeownersEntryMatcher, FileEntry, Owner, OwnerMerge, OwnerType, PatternAnchor, Tag,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use std::fs;
+use std::hint::black_box;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn create_test_owner(identifier: &str, owner_type: OwnerType) -> Owner {
+    Owner {
+        identifier: identifier.to_string(),
+        owner_type,
+    }
+}
+
+fn create_test_tag(name: &str) -> Tag {
+    Tag(name.to_string())
+}
+
+/// Synthetic 25,000-file cache with 50 teams, 10 users and 20 tags
+fn create_synthetic_files() -> (Vec<FileEntry>, Vec<Owner>, Vec<Tag>) {
+    let mut owners: Vec<Owner> = (0..50)
+        .map(|i| create_test_owner(&format!("@org/team-{}", i), OwnerType::Team))
+        .collect();
+    owners.extend((0..10).map(|i| create_test_owner(&format!("@user-{}", i), OwnerType::User)));
+
+    let tags: Vec<Tag> = (0..20)
+        .map(|i| create_test_tag(&format!("tag-{}", i)))
+        .collect();
+
+    let files = (0..25000)
+        .map(|i| {
+            let mut file_owners = vec![owners[i % 50].clone()];
+            if i % 3 == 0 {
+                file_owners.push(owners[50 + i % 10].clone());
+            }
+
+            let mut file_tags = vec![tags[i % 20].clone()];
+            if i % 5 == 0 {
+                file_tags.push(tags[(i + 7) % 20].clone());
+            }
+
+            FileEntry {
+                path: PathBuf::from(format!(
+                    "src/module_{}/submodule_{}/file_{}.rs",
+                    i / 1000,
+                    (i / 100) % 10,
+                    i
+                )),
+                owners: file_owners,
+                tags: file_tags,
+                inline_override: false,
+            }
+        })
+        .collect();
+
+    (files, owners, tags)
+}
+
+fn bench_build_owners_map_mega_large(c: &mut Criterion) {
+    let (files, owners, _) = create_synthetic_files();
+
+    c.bench_function("build_owners_map_mega_large", |b| {
+        b.iter(|| build_owners_map(black_box(&files), black_box(owners.clone())))
+    });
+}
+
+fn bench_build_tags_map_mega_large(c: &mut Criterion) {
+    let (files, _, tags) = create_synthetic_files();
+
+    c.bench_function("build_tags_map_mega_large", |b| {
+        b.iter(|| build_tags_map(black_box(&files), black_box(tags.clone())))
+    });
+}
+
+/// Temp repository with `codeowners_count` directories, each holding a
+/// CODEOWNERS file and `files_per_dir` target files
+fn create_temp_repo(codeowners_count: usize, files_per_dir: usize) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let base_path = temp_dir.path();
+
+    fs::write(
+        base_path.join("CODEOWNERS"),
+        "* @org/everyone #all\n*.md @org/docs #docs\n",
+    )
+    .unwrap();
+
+    for dir in 0..codeowners_count {
+        let module_dir = base_path.join(format!("module_{}", dir));
+        fs::create_dir_all(module_dir.join("nested")).unwrap();
+
+        fs::write(
+            module_dir.join("CODEOWNERS"),
+            format!(
+                "*.rs @org/team-{} #rust\n/nested/ @user-{} #nested\n*.md @org/docs #docs\n",
+                dir,
+                dir % 10
+            ),
+        )
+        .unwrap();
+
+        for i in 0..files_per_dir {
+            let (subdir, ext) = match i % 4 {
+                0 => ("nested", "rs"),
+                1 => ("nested", "md"),
+                2 => ("", "md"),
+                _ => ("", "rs"),
+            };
+            fs::write(
+                module_dir.join(subdir).join(format!("file_{}.{}", i, ext)),
+                "",
+            )
+            .unwrap();
+        }
+    }
+
+    temp_dir
+}
+
+fn parse_entries(base_path: &Path) -> Vec<CodeownersEntry> {
+    find_codeowners_files(base_path)
+        .unwrap()
+        .iter()
+        .flat_map(|file| parse_codeowners(file).unwrap())
+        .collect()
+}
+
+fn bench_build_cache_end_to_end(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_cache_end_to_end");
+    group.sample_size(10);
+
+    for (name, codeowners_count, files_per_dir) in
+        [("small", 5, 40), ("medium", 20, 100), ("large", 50, 200)]
+    {
+        let repo = create_temp_repo(codeowners_count, files_per_dir);
+        let base_path = repo.path();
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(name),
+            &base_path,
+            |b, base_path| {
+                b.iter_batched(
+                    || parse_entries(base_path),
+                    |entries| {
+                        let (files, _) = find_files(base_path, false).unwrap();
+                        build_cache(
+                            black_box(entries),
+                            black_box(files),
+                            [0u8; 32],
+                            false,
+                            OwnerMerge::Winner,
+                            PatternAnchor::FileDir,
+                            base_path,
+                        )
+                        .unwrap()
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// 2,000 entries of one CODEOWNERS file cycling through 20 patterns
+fn create_duplicated_entries(base_path: &Path) -> Vec<CodeownersEntry> {
+    let source_file = base_path.join("CODEOWNERS");
+    (0..2000)
+        .map(|i| CodeownersEntry {
+            source_file: source_file.clone(),
+            line_number: i,
+            pattern: format!("/module_{}/**/*.rs", i % 20),
+            owners: vec![create_test_owner(
+                &format!("@org/team-{}", i % 50),
+                OwnerType::Team,
+            )],
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        })
+        .collect()
+}
+
+fn bench_compile_duplicated_patterns(c: &mut Criterion) {
+    let repo = TempDir::new().unwrap();
+    let base_path = repo.path();
+    let entries = create_duplicated_entries(base_path);
+    let mut group = c.benchmark_group("compile_duplicated_patterns");
+
+    group.bench_function("per_entry", |b| {
+        b.iter(|| {
+            black_box(&entries)
+                .iter()
+                .map(|entry| codeowners_entry_to_matcher(entry, false))
+                .collect::<Vec<CodeownersEntryMatcher>>()
+        })
+    });
+    group.bench_function("memoized", |b| {
+        b.iter(|| {
+            codeowners_entry_matchers(
+                black_box(&entries),
+                false,
+                PatternAnchor::FileDir,
+                base_path,
+            )
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_build_owners_map_mega_large,
+    bench_build_tags_map_mega_large,
+    bench_build_cache_end_to_end,
+    bench_compile_duplicated_patterns
+);
+criterion_main!(benches);
@@ -16,6 +16,7 @@ fn create_test_file_entry(path: &str, owners: Vec<Owner>) -> FileEntry {
         path: PathBuf::from(path),
         owners,
         tags: vec![],
+        inline_override: false,
     }
 }
 
@@ -28,6 +29,8 @@ fn create_test_codeowners_entry(
         pattern: pattern.to_string(),
         owners,
         tags: vec![],
+        comment: None,
+        raw: String::new(),
     }
 }
 
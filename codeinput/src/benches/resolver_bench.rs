@@ -1,6 +1,7 @@
 use codeinput::core::resolver::find_owners_and_tags_for_file;
 use codeinput::core::types::{
-    codeowners_entry_to_matcher, CodeownersEntry, CodeownersEntryMatcher, Owner, OwnerType, Tag,
+    codeowners_entry_to_matcher, CodeownersEntry, CodeownersEntryMatcher, Owner, OwnerMerge,
+    OwnerType, Tag,
 };
 use criterion::{criterion_group, criterion_main, Criterion};
 use std::hint::black_box;
@@ -26,8 +27,10 @@ fn create_test_codeowners_entry_matcher(
         pattern: pattern.to_string(),
         owners,
         tags,
+        comment: None,
+        raw: String::new(),
     };
-    codeowners_entry_to_matcher(&entry)
+    codeowners_entry_to_matcher(&entry, false)
 }
 
 fn bench_find_owners_and_tags_simple_pattern(c: &mut Criterion) {
@@ -51,7 +54,14 @@ fn bench_find_owners_and_tags_simple_pattern(c: &mut Criterion) {
     let file_path = Path::new("/project/src/main.rs");
 
     c.bench_function("find_owners_and_tags_simple", |b| {
-        b.iter(|| find_owners_and_tags_for_file(black_box(file_path), black_box(&entries)).unwrap())
+        b.iter(|| {
+            find_owners_and_tags_for_file(
+                black_box(file_path),
+                black_box(&entries),
+                OwnerMerge::Winner,
+            )
+            .unwrap()
+        })
     });
 }
 
@@ -83,7 +93,14 @@ fn bench_find_owners_and_tags_complex_patterns(c: &mut Criterion) {
     let file_path = Path::new("/project/src/frontend/main.rs");
 
     c.bench_function("find_owners_and_tags_complex", |b| {
-        b.iter(|| find_owners_and_tags_for_file(black_box(file_path), black_box(&entries)).unwrap())
+        b.iter(|| {
+            find_owners_and_tags_for_file(
+                black_box(file_path),
+                black_box(&entries),
+                OwnerMerge::Winner,
+            )
+            .unwrap()
+        })
     });
 }
 
@@ -104,7 +121,14 @@ fn bench_find_owners_and_tags_many_entries(c: &mut Criterion) {
     let file_path = Path::new("/project/src/module_50/file.rs");
 
     c.bench_function("find_owners_and_tags_many_entries", |b| {
-        b.iter(|| find_owners_and_tags_for_file(black_box(file_path), black_box(&entries)).unwrap())
+        b.iter(|| {
+            find_owners_and_tags_for_file(
+                black_box(file_path),
+                black_box(&entries),
+                OwnerMerge::Winner,
+            )
+            .unwrap()
+        })
     });
 }
 
@@ -139,7 +163,14 @@ fn bench_find_owners_and_tags_nested_codeowners(c: &mut Criterion) {
     let file_path = Path::new("/project/src/frontend/component.tsx");
 
     c.bench_function("find_owners_and_tags_nested", |b| {
-        b.iter(|| find_owners_and_tags_for_file(black_box(file_path), black_box(&entries)).unwrap())
+        b.iter(|| {
+            find_owners_and_tags_for_file(
+                black_box(file_path),
+                black_box(&entries),
+                OwnerMerge::Winner,
+            )
+            .unwrap()
+        })
     });
 }
 
@@ -155,7 +186,14 @@ fn bench_find_owners_and_tags_no_matches(c: &mut Criterion) {
     let file_path = Path::new("/project/src/main.rs");
 
     c.bench_function("find_owners_and_tags_no_matches", |b| {
-        b.iter(|| find_owners_and_tags_for_file(black_box(file_path), black_box(&entries)).unwrap())
+        b.iter(|| {
+            find_owners_and_tags_for_file(
+                black_box(file_path),
+                black_box(&entries),
+                OwnerMerge::Winner,
+            )
+            .unwrap()
+        })
     });
 }
 
@@ -183,7 +221,14 @@ fn bench_find_owners_and_tags_multiple_matches(c: &mut Criterion) {
     let file_path = Path::new("/project/src/main.rs");
 
     c.bench_function("find_owners_and_tags_multiple_matches", |b| {
-        b.iter(|| find_owners_and_tags_for_file(black_box(file_path), black_box(&entries)).unwrap())
+        b.iter(|| {
+            find_owners_and_tags_for_file(
+                black_box(file_path),
+                black_box(&entries),
+                OwnerMerge::Winner,
+            )
+            .unwrap()
+        })
     });
 }
 
@@ -0,0 +1,90 @@
+use codeinput::core::resolver::resolve_batch;
+use codeinput::core::types::{
+    codeowners_entry_to_matcher, CodeownersEntry, CodeownersEntryMatcher, Owner, OwnerMerge,
+    OwnerType, Tag,
+};
+use codeinput::utils::app_config::AppConfig;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+use std::path::PathBuf;
+
+fn create_test_matchers() -> Vec<CodeownersEntryMatcher> {
+    (0..20)
+        .map(|i| {
+            let entry = CodeownersEntry {
+                source_file: PathBuf::from("/project/CODEOWNERS"),
+                line_number: i,
+                pattern: format!("src/module_{}/**/*.rs", i),
+                owners: vec![Owner {
+                    identifier: format!("@team-{}", i),
+                    owner_type: OwnerType::Team,
+                }],
+                tags: vec![Tag(format!("module-{}", i))],
+                comment: None,
+                raw: String::new(),
+            };
+            codeowners_entry_to_matcher(&entry, false)
+        })
+        .collect()
+}
+
+fn create_test_paths(count: usize) -> Vec<PathBuf> {
+    (0..count)
+        .map(|i| PathBuf::from(format!("/project/src/module_{}/file_{}.rs", i % 20, i)))
+        .collect()
+}
+
+// Resolve the same batch sequentially and in parallel at several sizes to find
+// where parallelism starts to pay off; this justifies DEFAULT_PARALLEL_THRESHOLD
+fn bench_resolve_batch_crossover(c: &mut Criterion) {
+    AppConfig::init(Some(include_str!("../resources/default_config.toml"))).unwrap();
+    let matchers = create_test_matchers();
+
+    let mut group = c.benchmark_group("resolve_batch_crossover");
+    for size in [8, 32, 64, 128, 512, 2048] {
+        let paths = create_test_paths(size);
+
+        // Threshold above the input size forces sequential iteration, 0 parallel
+        // iteration (on machines with more than one thread)
+        for (mode, threshold) in [("sequential", usize::MAX), ("parallel", 0)] {
+            AppConfig::set("parallel_threshold", &threshold.to_string()).unwrap();
+            group.bench_with_input(BenchmarkId::new(mode, size), &paths, |b, paths| {
+                b.iter(|| resolve_batch(black_box(paths), black_box(&matchers), OwnerMerge::Winner))
+            });
+        }
+    }
+    group.finish();
+}
+
+// Resolve a large batch in parallel with several fixed chunk sizes and the
+// adaptive default (0) to check the default against hand-picked values
+fn bench_resolve_batch_chunk_size(c: &mut Criterion) {
+    AppConfig::init(Some(include_str!("../resources/default_config.toml"))).unwrap();
+    AppConfig::set("parallel_threshold", "0").unwrap();
+    let matchers = create_test_matchers();
+
+    let mut group = c.benchmark_group("resolve_batch_chunk_size");
+    for size in [2048, 16384] {
+        let paths = create_test_paths(size);
+        for chunk_size in [0, 1, 16, 100, 512, 2048] {
+            AppConfig::set("chunk_size", &chunk_size.to_string()).unwrap();
+            group.bench_with_input(
+                BenchmarkId::new(format!("chunk_{}", chunk_size), size),
+                &paths,
+                |b, paths| {
+                    b.iter(|| {
+                        resolve_batch(black_box(paths), black_box(&matchers), OwnerMerge::Winner)
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_resolve_batch_crossover,
+    bench_resolve_batch_chunk_size
+);
+criterion_main!(benches);
@@ -20,6 +20,7 @@ fn create_test_file_entry(path: &str, tags: Vec<Tag>) -> FileEntry {
         path: PathBuf::from(path),
         owners: vec![],
         tags,
+        inline_override: false,
     }
 }
 
@@ -32,6 +33,8 @@ fn create_test_codeowners_entry(
         pattern: pattern.to_string(),
         owners: vec![],
         tags,
+        comment: None,
+        raw: String::new(),
     }
 }
 
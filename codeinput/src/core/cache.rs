@@ -1,98 +1,119 @@
 use crate::{
     core::{
-        common::{collect_owners, collect_tags, get_repo_hash},
-        parse::parse_repo,
-        resolver::find_owners_and_tags_for_file,
+        common::{
+            collect_owners, collect_tags, dedupe_paths, get_repo_hash_with_mode, head_commit,
+            normalize_separators,
+        },
+        identity::IdentityMap,
+        owner_resolver::build_owners_map,
+        parse::{parse_repo, parse_repo_with, BuildSettings},
+        parser::apply_owner_aliases_to_inline,
+        resolver::{check_patterns, resolve_batch_with},
+        tag_resolver::build_tags_map,
         types::{
-            codeowners_entry_to_matcher, CacheEncoding, CodeownersCache, CodeownersEntry,
-            CodeownersEntryMatcher, FileEntry,
+            codeowners_entry_matchers, CacheEncoding, CodeownersCache, CodeownersEntry,
+            CodeownersEntryMatcher, FileEntry, OwnerMerge, PatternAnchor, CACHE_FORMAT_VERSION,
+            CACHE_MAGIC,
         },
     },
-    utils::error::{Error, Result},
+    utils::{
+        app_config::AppConfig,
+        error::{Error, ErrorKind, Result},
+        types::HashMode,
+    },
 };
-use rayon::{iter::ParallelIterator, slice::ParallelSlice};
+use fs2::FileExt;
+use serde::Serialize;
 use std::{
+    collections::HashMap,
     io::{Read, Write},
     path::{Path, PathBuf},
+    time::Duration,
 };
 
+/// Attempts at taking a cache lock before giving up, about five seconds of waiting
+const LOCK_ATTEMPTS: u32 = 10;
+/// Wait after the first failed attempt, doubled after each further one
+const LOCK_BACKOFF: Duration = Duration::from_millis(10);
+
+/// Progress of [`build_cache`], reported as one JSON line each with `--progress-json`
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ProgressEvent {
+    /// `done` of `total` files have been resolved
+    Progress { done: usize, total: usize },
+    /// All files have been resolved
+    Done,
+}
+
+impl ProgressEvent {
+    fn to_json_line(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
 /// Create a cache from parsed CODEOWNERS entries and files
+///
+/// File paths are stored with `/` separators on every platform, and a path
+/// listed twice gets a single entry. With [`PatternAnchor::RepoRoot`], patterns
+/// are anchored at `repo` instead of their CODEOWNERS file's directory.
+///
+/// Progress goes to stderr, as a status line or as JSON lines with `progress_json`.
+/// Fails with a validation error listing any pattern that is not a valid glob.
 pub fn build_cache(
-    entries: Vec<CodeownersEntry>, files: Vec<PathBuf>, hash: [u8; 32],
+    entries: Vec<CodeownersEntry>, files: Vec<PathBuf>, hash: [u8; 32], case_insensitive: bool,
+    owner_merge: OwnerMerge, anchor: PatternAnchor, repo: &Path,
 ) -> Result<CodeownersCache> {
-    let mut owners_map = std::collections::HashMap::new();
-    let mut tags_map = std::collections::HashMap::new();
-
-    let matched_entries: Vec<CodeownersEntryMatcher> = entries
-        .iter()
-        .map(|entry| codeowners_entry_to_matcher(entry))
-        .collect();
+    // The matchers can't be compiled from invalid patterns
+    check_patterns(&entries)?;
+    let matched_entries: Vec<CodeownersEntryMatcher> =
+        codeowners_entry_matchers(&entries, case_insensitive, anchor, repo);
+    let files = dedupe_paths(files.into_iter().map(normalize_separators).collect(), false);
 
     // Process each file to find owners and tags
     let total_files = files.len();
     let processed_count = std::sync::atomic::AtomicUsize::new(0);
+    let progress_json = AppConfig::fetch()
+        .map(|config| config.progress_json)
+        .unwrap_or(false);
 
-    let file_entries: Vec<FileEntry> = files
-        .par_chunks(100)
-        .flat_map(|chunk| {
-            chunk
-                .iter()
-                .map(|file_path| {
-                    let current =
-                        processed_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
-
-                    // Limit filename display length and clear the line properly
-                    let file_display = file_path.display().to_string();
-                    let truncated_file = if file_display.len() > 60 {
-                        format!("...{}", &file_display[file_display.len() - 57..])
-                    } else {
-                        file_display
-                    };
-
-                    print!(
-                        "\r\x1b[K📁 Processing [{}/{}] {}",
-                        current, total_files, truncated_file
-                    );
-                    std::io::stdout().flush().unwrap();
-
-                    let (owners, tags) =
-                        find_owners_and_tags_for_file(file_path, &matched_entries).unwrap();
-
-                    // Build file entry
-                    FileEntry {
-                        path: file_path.clone(),
-                        owners: owners.clone(),
-                        tags: tags.clone(),
-                    }
-                })
-                .collect::<Vec<FileEntry>>()
-        })
-        .collect();
+    let file_entries: Vec<FileEntry> =
+        resolve_batch_with(&files, &matched_entries, owner_merge, |file_path| {
+            let current = processed_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+            if progress_json {
+                let event = ProgressEvent::Progress {
+                    done: current,
+                    total: total_files,
+                };
+                eprintln!("{}", event.to_json_line());
+                return;
+            }
 
-    // Print newline after processing is complete
-    println!("\r\x1b[K✅ Processed {} files successfully", total_files);
+            // Limit filename display length and clear the line properly
+            let file_display = file_path.display().to_string();
+            let truncated_file = if file_display.len() > 60 {
+                format!("...{}", &file_display[file_display.len() - 57..])
+            } else {
+                file_display
+            };
 
-    // Process each owner
-    let owners = collect_owners(&entries);
-    owners.iter().for_each(|owner| {
-        let paths = owners_map.entry(owner.clone()).or_insert_with(Vec::new);
-        for file_entry in &file_entries {
-            if file_entry.owners.contains(owner) {
-                paths.push(file_entry.path.clone());
-            }
-        }
-    });
-
-    // Process each tag
-    let tags = collect_tags(&entries);
-    tags.iter().for_each(|tag| {
-        let paths = tags_map.entry(tag.clone()).or_insert_with(Vec::new);
-        for file_entry in &file_entries {
-            if file_entry.tags.contains(tag) {
-                paths.push(file_entry.path.clone());
-            }
-        }
-    });
+            eprint!(
+                "\r\x1b[K📁 Processing [{}/{}] {}",
+                current, total_files, truncated_file
+            );
+            std::io::stderr().flush().unwrap();
+        });
+
+    if progress_json {
+        eprintln!("{}", ProgressEvent::Done.to_json_line());
+    } else {
+        // Print newline after processing is complete
+        eprintln!("\r\x1b[K✅ Processed {} files successfully", total_files);
+    }
+
+    // Map each owner and tag to the files they apply to
+    let owners_map = build_owners_map(&file_entries, collect_owners(&entries));
+    let tags_map = build_tags_map(&file_entries, collect_tags(&entries));
 
     Ok(CodeownersCache {
         hash,
@@ -100,55 +121,262 @@ pub fn build_cache(
         files: file_entries,
         owners_map,
         tags_map,
+        case_insensitive,
+        owner_merge,
+        anchor,
+        // Set by callers walking the repository themselves
+        follow_links: false,
+        dedupe_paths: false,
+        owner_aliases: HashMap::new(),
+        head_commit: head_commit(repo),
+        identity_map: HashMap::new(),
     })
 }
 
+/// Record the owner aliases a cache is built with, applying them to inline declarations
+///
+/// The entries must already have been aliased with [`apply_owner_aliases`]
+/// before [`build_cache`]; the owner map is rebuilt to include the inline
+/// owners under their new identifiers.
+///
+/// [`apply_owner_aliases`]: crate::core::parser::apply_owner_aliases
+pub(crate) fn set_owner_aliases(cache: &mut CodeownersCache, aliases: HashMap<String, String>) {
+    if !aliases.is_empty() {
+        apply_owner_aliases_to_inline(&mut cache.files, &aliases);
+        cache.owners_map = build_owners_map(&cache.files, collect_owners(&cache.entries));
+    }
+    cache.owner_aliases = aliases;
+}
+
+/// Record the identity map a cache is built with, merging the owners it maps
+///
+/// Call it once the owner map is complete, e.g. after [`set_owner_aliases`].
+pub(crate) fn set_identity_map(cache: &mut CodeownersCache, identities: &IdentityMap) {
+    cache.owners_map =
+        identities.merge_owners_map(std::mem::take(&mut cache.owners_map), &cache.files);
+    cache.identity_map = identities.handles().clone();
+}
+
+/// Re-resolve ownership for `paths` against the rules already in the cache
+///
+/// Entries for paths that no longer exist are dropped, new paths are added and
+/// the owner and tag maps are rebuilt. Rules are not re-read, so the cache must
+/// be rebuilt with [`build_cache`] when a CODEOWNERS file changes. Inline
+/// owners go through the cache's owner aliases. `repo` is the repository the
+/// cache was built for.
+pub fn update_cache_files(cache: &mut CodeownersCache, repo: &Path, paths: &[PathBuf]) {
+    let matchers = cache.entry_matchers(repo);
+
+    let paths: Vec<PathBuf> = paths.iter().cloned().map(normalize_separators).collect();
+    let existing: Vec<PathBuf> = paths
+        .iter()
+        .filter(|path| path.is_file())
+        .cloned()
+        .collect();
+    let mut resolved = resolve_batch_with(&existing, &matchers, cache.owner_merge, |_| {});
+    apply_owner_aliases_to_inline(&mut resolved, &cache.owner_aliases);
+    let mut resolved: HashMap<PathBuf, FileEntry> = resolved
+        .into_iter()
+        .map(|file| (file.path.clone(), file))
+        .collect();
+
+    // Update files in place so unchanged files keep their order
+    let changed: std::collections::HashSet<&PathBuf> = paths.iter().collect();
+    let mut files = Vec::with_capacity(cache.files.len() + resolved.len());
+    for file in cache.files.drain(..) {
+        if !changed.contains(&file.path) {
+            files.push(file);
+        } else if let Some(updated) = resolved.remove(&file.path) {
+            files.push(updated);
+        }
+    }
+    // Whatever is left was not in the cache yet
+    files.extend(existing.iter().filter_map(|path| resolved.remove(path)));
+    cache.files = files;
+
+    cache.owners_map = build_owners_map(&cache.files, collect_owners(&cache.entries));
+    cache.tags_map = build_tags_map(&cache.files, collect_tags(&cache.entries));
+}
+
 /// Store Cache
+///
+/// The cache is written to a temporary file next to `path` and renamed into
+/// place, so readers never see a partially written cache. Concurrent stores,
+/// e.g. from parallel CI jobs, take turns through an advisory lock.
 pub fn store_cache(cache: &CodeownersCache, path: &Path, encoding: CacheEncoding) -> Result<()> {
-    let parent = path
-        .parent()
-        .ok_or_else(|| Error::new("Invalid cache path"))?;
-    std::fs::create_dir_all(parent)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let _lock = CacheLock::acquire(path, true)?;
+    write_atomically(path, |writer| write_cache(cache, writer, encoding))
+}
+
+/// Advisory lock on a cache file, released when dropped
+///
+/// The lock is held on a `<name>.lock` file next to the cache rather than on
+/// the cache itself, which every store replaces. Stores take it exclusively
+/// and loads shared, so a load waits for a store in progress.
+struct CacheLock {
+    file: std::fs::File,
+}
+
+impl CacheLock {
+    /// Take the lock for the cache at `path`, retrying with exponential backoff
+    ///
+    /// Only stores create the lock file. A shared lock is skipped, returning
+    /// `None`, when there is no lock file yet: the cache was then never stored
+    /// by a run that locks it, e.g. it was committed or copied.
+    fn acquire(path: &Path, exclusive: bool) -> Result<Option<Self>> {
+        let lock_path = lock_path(path).ok_or_else(|| Error::new("Invalid cache path"))?;
+
+        let file = match std::fs::OpenOptions::new()
+            .create(exclusive)
+            .truncate(false)
+            .write(exclusive)
+            .read(true)
+            .open(&lock_path)
+        {
+            Ok(file) => file,
+            Err(_) if !exclusive => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut backoff = LOCK_BACKOFF;
+        for attempt in 1..=LOCK_ATTEMPTS {
+            // Through `FileExt`, as `File` has inherent locking methods in newer std
+            let locked = if exclusive {
+                FileExt::try_lock_exclusive(&file)
+            } else {
+                FileExt::try_lock_shared(&file)
+            };
+            match locked {
+                Ok(()) => return Ok(Some(CacheLock { file })),
+                Err(e) if e.kind() != fs2::lock_contended_error().kind() => return Err(e.into()),
+                Err(_) if attempt < LOCK_ATTEMPTS => {
+                    std::thread::sleep(backoff);
+                    backoff *= 2;
+                }
+                Err(_) => {}
+            }
+        }
+
+        Err(Error::with_kind(
+            ErrorKind::Io,
+            &format!(
+                "Cache file {} is locked by another process; try again once it finishes",
+                path.display()
+            ),
+        ))
+    }
+}
+
+impl Drop for CacheLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
 
-    let file = std::fs::File::create(path)?;
-    let mut writer = std::io::BufWriter::new(file);
+/// The `<name>.lock` file guarding the cache at `path`, or `None` without a file name
+pub(crate) fn lock_path(path: &Path) -> Option<PathBuf> {
+    let mut lock_name = path.file_name()?.to_os_string();
+    lock_name.push(".lock");
+    Some(path.with_file_name(lock_name))
+}
 
+/// Serialize `cache` to `writer` in the same form as a cache file
+///
+/// Bincode caches start with [`CACHE_MAGIC`] and [`CACHE_FORMAT_VERSION`].
+pub fn write_cache(
+    cache: &CodeownersCache, writer: &mut impl std::io::Write, encoding: CacheEncoding,
+) -> Result<()> {
     match encoding {
         CacheEncoding::Bincode => {
-            bincode::serde::encode_into_std_write(cache, &mut writer, bincode::config::standard())
+            writer.write_all(&CACHE_MAGIC)?;
+            writer.write_all(&[CACHE_FORMAT_VERSION])?;
+            bincode::serde::encode_into_std_write(cache, writer, bincode::config::standard())
                 .map_err(|e| Error::new(&format!("Failed to serialize cache: {}", e)))?;
         }
         CacheEncoding::Json => {
-            serde_json::to_writer_pretty(&mut writer, cache)
+            serde_json::to_writer_pretty(writer, cache)
                 .map_err(|e| Error::new(&format!("Failed to serialize cache to JSON: {}", e)))?;
         }
     }
+    Ok(())
+}
 
-    writer.flush()?;
+/// Write `path` through a temporary file in the same directory
+///
+/// `path` is only replaced once `write` succeeds and the data is synced to
+/// disk; on failure the temporary file is removed and `path` is left as is.
+fn write_atomically(
+    path: &Path, write: impl FnOnce(&mut std::io::BufWriter<std::fs::File>) -> Result<()>,
+) -> Result<()> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| Error::new("Invalid cache path"))?;
+    std::fs::create_dir_all(parent)?;
 
-    Ok(())
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| Error::new("Invalid cache path"))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = (|| {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(&temp_path)?);
+        write(&mut writer)?;
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, path)?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
 }
 
-/// Load Cache from file, automatically detecting whether it's JSON or Bincode format
-pub fn load_cache(path: &Path) -> Result<CodeownersCache> {
-    // Read the first byte to make an educated guess about the format
-    let mut file = std::fs::File::open(path)
-        .map_err(|e| Error::new(&format!("Failed to open cache file: {}", e)))?;
+/// Guess the encoding of a cache file from its first byte
+///
+/// JSON caches start with `{`; anything else, including an empty file, is
+/// taken to be bincode.
+pub fn detect_encoding(path: &Path) -> Result<CacheEncoding> {
+    let mut file = std::fs::File::open(path).map_err(|e| {
+        Error::with_kind(
+            ErrorKind::CacheMissing,
+            &format!("Failed to open cache file: {}", e),
+        )
+    })?;
 
     let mut first_byte = [0u8; 1];
-    let read_result = file.read_exact(&mut first_byte);
+    if file.read_exact(&mut first_byte).is_ok() && first_byte[0] == b'{' {
+        Ok(CacheEncoding::Json)
+    } else {
+        Ok(CacheEncoding::Bincode)
+    }
+}
 
-    // Close the file handle and reopen for full reading
-    drop(file);
+/// Load Cache from file, automatically detecting whether it's JSON or Bincode format
+///
+/// A cache that can't be decoded, e.g. a bincode cache written with another
+/// [`CACHE_FORMAT_VERSION`], fails with [`ErrorKind::CacheMissing`] so it is
+/// rebuilt like a missing one.
+pub fn load_cache(path: &Path) -> Result<CodeownersCache> {
+    let _lock = CacheLock::acquire(path, false)?;
+    let unreadable = |msg: String| Error::with_kind(ErrorKind::CacheMissing, &msg);
 
-    if read_result.is_ok() && first_byte[0] == b'{' {
+    // Read the first byte to make an educated guess about the format
+    if detect_encoding(path)? == CacheEncoding::Json {
         // First byte is '{', likely JSON
         let file = std::fs::File::open(path)
             .map_err(|e| Error::new(&format!("Failed to open cache file: {}", e)))?;
         let reader = std::io::BufReader::new(file);
 
         return serde_json::from_reader(reader)
-            .map_err(|e| Error::new(&format!("Failed to deserialize JSON cache: {}", e)));
+            .map_err(|e| unreadable(format!("Failed to deserialize JSON cache: {}", e)));
     }
 
     // Try bincode first since it's not JSON
@@ -156,59 +384,612 @@ pub fn load_cache(path: &Path) -> Result<CodeownersCache> {
         .map_err(|e| Error::new(&format!("Failed to open cache file: {}", e)))?;
     let mut reader = std::io::BufReader::new(file);
 
-    match bincode::serde::decode_from_std_read(&mut reader, bincode::config::standard()) {
-        Ok(cache) => Ok(cache),
-        Err(_) => {
-            // If bincode fails and it's not obviously JSON, still try JSON as a fallback
-            let file = std::fs::File::open(path)
-                .map_err(|e| Error::new(&format!("Failed to open cache file: {}", e)))?;
-            let reader = std::io::BufReader::new(file);
-
-            serde_json::from_reader(reader).map_err(|e| {
-                Error::new(&format!(
-                    "Failed to deserialize cache in any supported format: {}",
-                    e
-                ))
-            })
+    let mut header = [0u8; CACHE_MAGIC.len() + 1];
+    if reader.read_exact(&mut header).is_ok() && header[..CACHE_MAGIC.len()] == CACHE_MAGIC {
+        let version = header[CACHE_MAGIC.len()];
+        if version != CACHE_FORMAT_VERSION {
+            return Err(unreadable(format!(
+                "Cache format version {} is not supported (expected {}); rebuild it with `parse`",
+                version, CACHE_FORMAT_VERSION
+            )));
         }
+        return bincode::serde::decode_from_std_read(&mut reader, bincode::config::standard())
+            .map_err(|e| unreadable(format!("Failed to deserialize cache: {}", e)));
     }
+
+    // Without a header it's not a bincode cache of this version, but may still be JSON
+    let file = std::fs::File::open(path)
+        .map_err(|e| Error::new(&format!("Failed to open cache file: {}", e)))?;
+    let reader = std::io::BufReader::new(file);
+
+    serde_json::from_reader(reader).map_err(|e| {
+        unreadable(format!(
+            "Failed to deserialize cache in any supported format: {}; it may have been \
+             written by an older version, rebuild it with `parse`",
+            e
+        ))
+    })
 }
 
+/// Open a cache file for querying, without a repository or git
+///
+/// The cache is used as is, even if it no longer matches any checkout, which
+/// suits tools answering ownership questions from a committed cache.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+///
+/// let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/codeowners.cache.json");
+/// let cache = codeinput::open_cache(Path::new(path))?;
+///
+/// let owners = cache.owners_of(Path::new("src/main.rs")).unwrap();
+/// assert_eq!(owners[0].identifier, "@org/rust");
+/// assert_eq!(cache.files_owned_by("@org/docs").unwrap().len(), 1);
+/// # Ok::<(), codeinput::utils::error::Error>(())
+/// ```
+pub fn open_cache(path: &Path) -> Result<CodeownersCache> {
+    load_cache(path)
+}
+
+/// Load the cache for a repository, rebuilding it when it is missing or stale
+///
+/// The cache is stale when the repository hash changed, or when it is older
+/// than the configured `cache_ttl_secs` (0 disables the age check). With
+/// `hash_mode = "none"` the hash is not checked, so the cache is rebuilt every
+/// time unless a TTL is set.
 pub fn sync_cache(
     repo: &std::path::Path, cache_file: Option<&std::path::Path>,
 ) -> Result<CodeownersCache> {
-    let config_cache_file = crate::utils::app_config::AppConfig::fetch()?
-        .cache_file
-        .clone();
+    let config = crate::utils::app_config::AppConfig::fetch()?;
 
     let cache_file: &std::path::Path = match cache_file {
-        Some(file) => file.into(),
-        None => std::path::Path::new(&config_cache_file),
+        Some(file) => file,
+        None => std::path::Path::new(&config.cache_file),
     };
 
+    sync_cache_with_ttl(repo, cache_file, config.cache_ttl_secs, config.hash_mode)
+}
+
+fn sync_cache_with_ttl(
+    repo: &std::path::Path, cache_file: &std::path::Path, ttl_secs: u64, hash_mode: HashMode,
+) -> Result<CodeownersCache> {
     // Verify that the cache file exists
     if !repo.join(cache_file).exists() {
         // parse the codeowners files and build the cache
-        return parse_repo(&repo, &cache_file);
+        return parse_repo(
+            repo,
+            cache_file,
+            false,
+            OwnerMerge::Winner,
+            PatternAnchor::FileDir,
+        );
     }
 
     // Load the cache from the specified file
-    let cache = load_cache(&repo.join(cache_file)).map_err(|e| {
-        crate::utils::error::Error::new(&format!(
-            "Failed to load cache from {}: {}",
-            cache_file.display(),
-            e
-        ))
-    })?;
+    let cache = match load_cache(&repo.join(cache_file)) {
+        Ok(cache) => cache,
+        // Caches written by an older version can't be decoded, so build a new one
+        Err(e) if e.kind == ErrorKind::CacheMissing => {
+            eprintln!("Rebuilding cache {}: {}", cache_file.display(), e);
+            return parse_repo(
+                repo,
+                cache_file,
+                false,
+                OwnerMerge::Winner,
+                PatternAnchor::FileDir,
+            );
+        }
+        Err(e) => {
+            return Err(Error::with_kind(
+                e.kind,
+                &format!("Failed to load cache from {}: {}", cache_file.display(), e),
+            ))
+        }
+    };
 
     // verify the hash of the cache matches the current repo hash
-    let current_hash = get_repo_hash(repo)?;
-    let cache_hash = cache.hash;
+    let stale = match hash_mode {
+        HashMode::None => ttl_secs == 0,
+        _ => cache.hash != get_repo_hash_with_mode(repo, hash_mode)?,
+    };
 
-    if cache_hash != current_hash {
-        // parse the codeowners files and build the cache
-        return parse_repo(&repo, &cache_file);
+    if stale || is_expired(&repo.join(cache_file), ttl_secs) {
+        // parse the codeowners files and build the cache, keeping the settings it was built with
+        parse_repo_with(repo, cache_file, &BuildSettings::of(&cache))
     } else {
-        return Ok(cache);
+        Ok(cache)
+    }
+}
+
+/// Whether the file was last modified more than `ttl_secs` ago; never for a TTL of 0
+fn is_expired(path: &Path, ttl_secs: u64) -> bool {
+    if ttl_secs == 0 {
+        return false;
+    }
+
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age > std::time::Duration::from_secs(ttl_secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_progress_events_are_json_lines() {
+        let total = 3;
+        let mut lines: Vec<String> = (1..=total)
+            .map(|done| ProgressEvent::Progress { done, total }.to_json_line())
+            .collect();
+        lines.push(ProgressEvent::Done.to_json_line());
+
+        let events: Vec<serde_json::Value> = lines
+            .iter()
+            .map(|line| {
+                assert!(!line.contains('\n'));
+                serde_json::from_str(line).unwrap()
+            })
+            .collect();
+        assert_eq!(
+            events[0],
+            serde_json::json!({"type": "progress", "done": 1, "total": 3})
+        );
+        assert_eq!(events[2]["done"], 3);
+        assert_eq!(events[3], serde_json::json!({"type": "done"}));
+    }
+
+    #[test]
+    fn test_failed_write_keeps_existing_cache() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cache_path = temp_dir.path().join(".codeowners.cache");
+        let cache = CodeownersCache {
+            hash: [7; 32],
+            entries: vec![],
+            files: vec![],
+            owners_map: HashMap::new(),
+            tags_map: HashMap::new(),
+            case_insensitive: false,
+            owner_merge: OwnerMerge::Winner,
+            anchor: PatternAnchor::FileDir,
+            follow_links: false,
+            dedupe_paths: false,
+            owner_aliases: HashMap::new(),
+            head_commit: None,
+            identity_map: HashMap::new(),
+        };
+        store_cache(&cache, &cache_path, CacheEncoding::Bincode)?;
+
+        // Fail halfway through writing a replacement
+        let result = write_atomically(&cache_path, |writer| {
+            writer.write_all(b"{\"hash\": [")?;
+            Err(Error::new("interrupted"))
+        });
+        assert!(result.is_err());
+
+        let loaded = load_cache(&cache_path)?;
+        assert_eq!(loaded.hash, [7; 32]);
+        // No temporary files are left behind, only the cache and its lock file
+        assert_eq!(std::fs::read_dir(temp_dir.path())?.count(), 2);
+        assert!(temp_dir.path().join(".codeowners.cache.lock").is_file());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_store_and_load_serialize() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cache_path = temp_dir.path().join(".codeowners.cache");
+        let cache = |byte: u8| CodeownersCache {
+            hash: [byte; 32],
+            entries: vec![],
+            files: vec![],
+            owners_map: HashMap::new(),
+            tags_map: HashMap::new(),
+            case_insensitive: false,
+            owner_merge: OwnerMerge::Winner,
+            anchor: PatternAnchor::FileDir,
+            follow_links: false,
+            dedupe_paths: false,
+            owner_aliases: HashMap::new(),
+            head_commit: None,
+            identity_map: HashMap::new(),
+        };
+        store_cache(&cache(0), &cache_path, CacheEncoding::Bincode)?;
+
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = [(1, CacheEncoding::Bincode), (2, CacheEncoding::Json)]
+                .into_iter()
+                .map(|(byte, encoding)| {
+                    let cache_path = &cache_path;
+                    let cache = &cache;
+                    scope.spawn(move || -> Result<()> {
+                        for _ in 0..20 {
+                            store_cache(&cache(byte), cache_path, encoding)?;
+                            // Always a whole cache, written by either thread
+                            let loaded = load_cache(cache_path)?;
+                            assert!([[1; 32], [2; 32]].contains(&loaded.hash));
+                        }
+                        Ok(())
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .try_for_each(|worker| worker.join().unwrap())
+        })?;
+
+        // Only the cache and its lock file remain
+        assert_eq!(std::fs::read_dir(temp_dir.path())?.count(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_cache_marks_inline_overrides() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        let main_rs = base_path.join("main.rs");
+        let special_rs = base_path.join("special.rs");
+        std::fs::write(&main_rs, "fn main() {}")?;
+        std::fs::write(
+            &special_rs,
+            "// !!!CODEOWNERS @special-team\nfn special() {}",
+        )?;
+
+        let entries = vec![CodeownersEntry {
+            source_file: base_path.join("CODEOWNERS"),
+            line_number: 0,
+            pattern: "*.rs".to_string(),
+            owners: vec!["@rust-team".parse().unwrap()],
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        }];
+        let cache = build_cache(
+            entries,
+            vec![main_rs, special_rs],
+            [0; 32],
+            false,
+            OwnerMerge::Winner,
+            PatternAnchor::FileDir,
+            base_path,
+        )?;
+
+        let flags: Vec<(&str, bool)> = cache
+            .files
+            .iter()
+            .map(|file| (file.owners[0].identifier.as_str(), file.inline_override))
+            .collect();
+        assert_eq!(flags, vec![("@rust-team", false), ("@special-team", true)]);
+
+        // The flag is part of the per-file JSON output
+        let json = serde_json::to_value(&cache.files).unwrap();
+        assert_eq!(json[0]["inline_override"], false);
+        assert_eq!(json[1]["inline_override"], true);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_cache_stores_forward_slashes() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        std::fs::create_dir_all(base_path.join("src").join("api"))?;
+        std::fs::write(base_path.join("src").join("api").join("main.rs"), "")?;
+
+        // A path as walked on Windows
+        let walked = PathBuf::from(format!("{}\\src\\api\\main.rs", base_path.display()));
+        let entries = vec![CodeownersEntry {
+            source_file: base_path.join("CODEOWNERS"),
+            line_number: 0,
+            pattern: "src/api/".to_string(),
+            owners: vec!["@api-team".parse().unwrap()],
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        }];
+        let cache = build_cache(
+            entries,
+            vec![walked],
+            [0; 32],
+            false,
+            OwnerMerge::Winner,
+            PatternAnchor::FileDir,
+            base_path,
+        )?;
+
+        let expected = base_path.join("src/api/main.rs");
+        assert_eq!(cache.files[0].path, expected);
+        assert_eq!(cache.files[0].owners[0].identifier, "@api-team");
+        let cached_paths = cache
+            .files
+            .iter()
+            .map(|file| &file.path)
+            .chain(cache.owners_map.values().flatten());
+        for path in cached_paths {
+            assert!(!path.to_string_lossy().contains('\\'), "{}", path.display());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_cache_keeps_one_entry_per_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        std::fs::create_dir_all(base_path.join("src"))?;
+        std::fs::write(base_path.join("src").join("main.rs"), "")?;
+
+        let main = base_path.join("src").join("main.rs");
+        let entries = vec![CodeownersEntry {
+            source_file: base_path.join("CODEOWNERS"),
+            line_number: 0,
+            pattern: "*.rs".to_string(),
+            owners: vec!["@rust-team".parse().unwrap()],
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        }];
+        let cache = build_cache(
+            entries,
+            vec![main.clone(), main.clone()],
+            [0; 32],
+            false,
+            OwnerMerge::Winner,
+            PatternAnchor::FileDir,
+            base_path,
+        )?;
+        assert_eq!(cache.files.len(), 1);
+        assert_eq!(cache.owners_map.values().flatten().count(), 1);
+
+        // Spellings of the same file only collapse when canonicalized
+        let walked = vec![main.clone(), base_path.join("src/../src/main.rs")];
+        assert_eq!(dedupe_paths(walked.clone(), false).len(), 2);
+        assert_eq!(dedupe_paths(walked, true), vec![main]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stored_cache_leaves_out_raw_lines() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        let entries = || {
+            vec![CodeownersEntry {
+                source_file: base_path.join("CODEOWNERS"),
+                line_number: 0,
+                pattern: "*.rs".to_string(),
+                owners: vec!["@rust-team".parse().unwrap()],
+                tags: vec![],
+                comment: Some("backend".to_string()),
+                raw: "*.rs @rust-team # backend".to_string(),
+            }]
+        };
+
+        for encoding in [CacheEncoding::Bincode, CacheEncoding::Json] {
+            let cache = build_cache(
+                entries(),
+                vec![],
+                [0; 32],
+                false,
+                OwnerMerge::Winner,
+                PatternAnchor::FileDir,
+                base_path,
+            )?;
+            let cache_path = base_path.join(format!("{:?}.cache", encoding));
+            store_cache(&cache, &cache_path, encoding)?;
+
+            let loaded = load_cache(&cache_path)?;
+            assert_eq!(loaded.entries[0].pattern, "*.rs");
+            assert_eq!(loaded.entries[0].comment.as_deref(), Some("backend"));
+            assert!(loaded.entries[0].raw.is_empty());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_cache_rebuilds_cache_in_old_layout() -> Result<()> {
+        use crate::core::types::{Owner, Tag};
+
+        // Cache types as written before entries and files gained their later fields
+        #[derive(Serialize)]
+        struct OldEntry {
+            source_file: PathBuf,
+            line_number: usize,
+            pattern: String,
+            owners: Vec<Owner>,
+            tags: Vec<Tag>,
+        }
+        #[derive(Serialize)]
+        struct OldFile {
+            path: PathBuf,
+            owners: Vec<Owner>,
+            tags: Vec<Tag>,
+        }
+        #[derive(Serialize)]
+        struct OldCache {
+            hash: [u8; 32],
+            entries: Vec<OldEntry>,
+            files: Vec<OldFile>,
+            owners_map: Vec<(Owner, Vec<PathBuf>)>,
+            tags_map: Vec<(Tag, Vec<PathBuf>)>,
+        }
+
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+        std::fs::write(base_path.join("CODEOWNERS"), "*.rs @rust-team\n")?;
+        std::fs::write(base_path.join("main.rs"), "fn main() {}")?;
+
+        let owner: Owner = "@rust-team".parse().unwrap();
+        let old = OldCache {
+            hash: get_repo_hash_with_mode(base_path, HashMode::Full)?,
+            entries: vec![OldEntry {
+                source_file: base_path.join("CODEOWNERS"),
+                line_number: 0,
+                pattern: "*.rs".to_string(),
+                owners: vec![owner.clone()],
+                tags: vec![],
+            }],
+            files: vec![OldFile {
+                path: base_path.join("main.rs"),
+                owners: vec![owner.clone()],
+                tags: vec![],
+            }],
+            owners_map: vec![(owner, vec![base_path.join("main.rs")])],
+            tags_map: vec![],
+        };
+        let cache_file = Path::new(".codeowners.cache");
+        let cache_path = base_path.join(cache_file);
+        std::fs::write(
+            &cache_path,
+            bincode::serde::encode_to_vec(&old, bincode::config::standard()).unwrap(),
+        )?;
+
+        let error = load_cache(&cache_path).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::CacheMissing);
+
+        // The old cache is replaced by a rebuilt one instead of failing
+        let rebuilt = sync_cache_with_ttl(base_path, cache_file, 0, HashMode::Full)?;
+        assert_eq!(rebuilt.files.len(), 1);
+        assert_eq!(rebuilt.files[0].owners[0].identifier, "@rust-team");
+        assert_eq!(load_cache(&cache_path)?.files, rebuilt.files);
+
+        // A header with another version is refused the same way
+        let mut bytes = std::fs::read(&cache_path)?;
+        bytes[CACHE_MAGIC.len()] = CACHE_FORMAT_VERSION + 1;
+        std::fs::write(&cache_path, bytes)?;
+        assert_eq!(
+            load_cache(&cache_path).unwrap_err().kind,
+            ErrorKind::CacheMissing
+        );
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sync_cache_rebuild_keeps_following_links() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let real_dir = temp_dir.path().join("real");
+        std::fs::create_dir_all(&real_dir)?;
+        std::fs::write(real_dir.join("lib.rs"), "")?;
+
+        let base_path = temp_dir.path().join("repo");
+        std::fs::create_dir_all(&base_path)?;
+        git2::Repository::init(&base_path)?;
+        std::fs::write(base_path.join("CODEOWNERS"), "*.rs @rust-team\n")?;
+        std::fs::write(base_path.join("main.rs"), "fn main() {}")?;
+        std::os::unix::fs::symlink(&real_dir, base_path.join("linked"))?;
+
+        let cache_file = Path::new(".codeowners.cache");
+        let settings = BuildSettings {
+            follow_links: true,
+            ..Default::default()
+        };
+        let cache = parse_repo_with(&base_path, cache_file, &settings)?;
+        assert!(cache.follow_links);
+        assert_eq!(cache.files.len(), 2);
+
+        // Rebuild the cache once it has expired
+        std::fs::File::options()
+            .write(true)
+            .open(base_path.join(cache_file))?
+            .set_modified(SystemTime::now() - Duration::from_secs(7200))?;
+        let rebuilt = sync_cache_with_ttl(&base_path, cache_file, 3600, HashMode::Full)?;
+        assert!(rebuilt.follow_links);
+        let linked = base_path.join("linked").join("lib.rs");
+        assert!(rebuilt.files.iter().any(|file| file.path == linked));
+        assert_eq!(rebuilt.files.len(), 2);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sync_cache_rebuild_keeps_deduping_paths() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+        std::fs::create_dir_all(base_path.join("src"))?;
+        std::fs::write(base_path.join("CODEOWNERS"), "*.rs @rust-team\n")?;
+        std::fs::write(base_path.join("src").join("lib.rs"), "")?;
+        // The walk reaches src/lib.rs a second time through the link
+        std::os::unix::fs::symlink(base_path.join("src"), base_path.join("linked"))?;
+
+        let cache_file = Path::new(".codeowners.cache");
+        let settings = BuildSettings {
+            follow_links: true,
+            dedupe_paths: true,
+            ..Default::default()
+        };
+        let cache = parse_repo_with(base_path, cache_file, &settings)?;
+        assert!(cache.dedupe_paths);
+        assert_eq!(cache.files.len(), 1);
+
+        std::fs::File::options()
+            .write(true)
+            .open(base_path.join(cache_file))?
+            .set_modified(SystemTime::now() - Duration::from_secs(7200))?;
+        let rebuilt = sync_cache_with_ttl(base_path, cache_file, 3600, HashMode::Full)?;
+        assert!(rebuilt.dedupe_paths);
+        assert_eq!(rebuilt.files.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_cache_rebuilds_cache_older_than_ttl() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+        std::fs::write(base_path.join("CODEOWNERS"), "*.rs @rust-team\n")?;
+        std::fs::write(base_path.join("main.rs"), "fn main() {}")?;
+
+        let cache_file = Path::new(".codeowners.cache");
+        let cache_path = base_path.join(cache_file);
+        let mut cache = parse_repo(
+            base_path,
+            cache_file,
+            false,
+            OwnerMerge::Winner,
+            PatternAnchor::FileDir,
+        )?;
+
+        // Store a cache that still matches the repository but has been tampered with
+        cache.files.clear();
+        cache.hash = get_repo_hash_with_mode(base_path, HashMode::Full)?;
+        store_cache(&cache, &cache_path, CacheEncoding::Bincode)?;
+        let age_cache = |age: Duration| -> Result<()> {
+            std::fs::File::options()
+                .write(true)
+                .open(&cache_path)?
+                .set_modified(SystemTime::now() - age)?;
+            Ok(())
+        };
+        age_cache(Duration::from_secs(7200))?;
+
+        // Without a TTL the old cache is used as-is
+        let cache = sync_cache_with_ttl(base_path, cache_file, 0, HashMode::Full)?;
+        assert!(cache.files.is_empty());
+        // A fresh cache is used as-is too
+        age_cache(Duration::ZERO)?;
+        let cache = sync_cache_with_ttl(base_path, cache_file, 3600, HashMode::Full)?;
+        assert!(cache.files.is_empty());
+
+        // An expired cache is rebuilt
+        age_cache(Duration::from_secs(7200))?;
+        let rebuilt = sync_cache_with_ttl(base_path, cache_file, 3600, HashMode::Full)?;
+        assert!(!rebuilt.files.is_empty());
+
+        Ok(())
     }
 }
@@ -0,0 +1,560 @@
+use crate::{
+    core::{
+        cache::sync_cache,
+        common::collect_tags,
+        display::stdout_writer,
+        junit::{self, TestCase},
+        parse::parse_repo_entries,
+        resolver::{
+            check_patterns, find_matching_entries_for_file, find_matching_indices_for_file,
+            invalid_patterns,
+        },
+        types::{CodeownersCache, CodeownersEntry, CodeownersEntryMatcher, OwnerMerge, Tag},
+    },
+    utils::error::{Error, ErrorKind, Result},
+};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Tag naming convention enforced by `--strict-tags` without a pattern
+pub const DEFAULT_TAG_PATTERN: &str = "^[a-z0-9][a-z0-9/_-]*$";
+
+/// Output formats for `audit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditFormat {
+    Text,
+    /// JUnit XML with one test case per rule
+    Junit,
+}
+
+/// Problems found with each rule, keyed by its CODEOWNERS file and line number
+type RuleFailures = HashMap<(PathBuf, usize), Vec<String>>;
+
+/// A rule that matches files but never decides their ownership
+#[derive(Debug)]
+struct ShadowedRule<'a> {
+    rule: &'a CodeownersEntryMatcher,
+    /// The rule that wins for the first file the shadowed rule matches
+    shadowed_by: &'a CodeownersEntryMatcher,
+    /// Number of files the shadowed rule matches
+    matched_files: usize,
+}
+
+/// Audit CODEOWNERS rules for problems
+///
+/// Reports rules that match files but never win for any of them, because a
+/// later rule or a rule in a deeper CODEOWNERS file always takes precedence.
+/// Inline `!!!CODEOWNERS` declarations are not taken into account. Fails
+/// before loading the cache if any rule's pattern is not a valid glob.
+///
+/// With `strict_tags`, also reports every tag that doesn't match the pattern
+/// and fails if there are any.
+///
+/// With `warn_ownerless`, warns on stderr about each pattern-only rule and
+/// the number of files it leaves unowned, without failing.
+///
+/// With [`AuditFormat::Junit`], every rule is a test case that fails for each
+/// of these problems, and invalid patterns are reported as failures too
+/// before the command fails.
+pub fn run(
+    repo: Option<&Path>, strict_tags: Option<&Regex>, warn_ownerless: bool, format: AuditFormat,
+    cache_file: Option<&Path>,
+) -> Result<()> {
+    // Repository path
+    let repo = repo.unwrap_or_else(|| Path::new("."));
+
+    let entries = parse_repo_entries(repo)?;
+    if format == AuditFormat::Junit {
+        // The cache can't be built from invalid patterns
+        let patterns = check_patterns(&entries);
+        let cache = match patterns {
+            Ok(()) => Some(sync_cache(repo, cache_file)?),
+            Err(_) => None,
+        };
+        let failures = rule_failures(repo, &entries, cache.as_ref(), strict_tags)?;
+        let mut stdout = stdout_writer();
+        write!(
+            stdout,
+            "{}",
+            junit::render_suite("codeowners audit", &junit_cases(repo, &entries, &failures))
+        )?;
+        stdout.finish()?;
+        if let (true, Some(cache)) = (warn_ownerless, &cache) {
+            warn_ownerless_rules(repo, &ownerless_rules(cache, &cache.entry_matchers(repo))?);
+        }
+        patterns?;
+        if let Some(pattern) = strict_tags {
+            let invalid = find_invalid_tags(&entries, pattern);
+            if !invalid.is_empty() {
+                return Err(invalid_tags_error(invalid.len(), pattern));
+            }
+        }
+        return Ok(());
+    }
+
+    // Unlike the resolvers, don't skip rules with broken patterns
+    check_patterns(&entries)?;
+
+    // Load the cache
+    let cache = sync_cache(repo, cache_file)?;
+
+    let matchers = cache.entry_matchers(repo);
+    let shadowed = find_shadowed_rules(&cache, &matchers)?;
+
+    let mut stdout = stdout_writer();
+    if cache.owner_merge == OwnerMerge::Union {
+        writeln!(
+            stdout,
+            "Owners of all matching rules are merged, so no rule is shadowed"
+        )?;
+    } else if shadowed.is_empty() {
+        writeln!(stdout, "No shadowed rules found")?;
+    } else {
+        writeln!(stdout, "Shadowed rules ({}):", shadowed.len())?;
+        for entry in &shadowed {
+            writeln!(
+                stdout,
+                "  {} (matches {} files)",
+                describe_rule(repo, entry.rule, 0),
+                entry.matched_files
+            )?;
+            writeln!(
+                stdout,
+                "    shadowed by {}",
+                describe_rule(repo, entry.shadowed_by, 0)
+            )?;
+        }
+    }
+    if warn_ownerless {
+        warn_ownerless_rules(repo, &ownerless_rules(&cache, &matchers)?);
+    }
+
+    let Some(pattern) = strict_tags else {
+        return stdout.finish();
+    };
+    // The parsed entries, as cached ones don't keep the raw lines to echo
+    let invalid = find_invalid_tags(&entries, pattern);
+    if invalid.is_empty() {
+        writeln!(stdout, "All tags match {}", pattern)?;
+        return stdout.finish();
+    }
+
+    writeln!(stdout, "Tags not matching {} ({}):", pattern, invalid.len())?;
+    for (entry, tag) in &invalid {
+        let source = entry
+            .source_file
+            .strip_prefix(repo)
+            .unwrap_or(&entry.source_file);
+        writeln!(
+            stdout,
+            "  {}:{} #{}",
+            source.display(),
+            entry.line_number,
+            tag.0
+        )?;
+        if !entry.raw.is_empty() {
+            writeln!(stdout, "    {}", entry.raw)?;
+        }
+    }
+
+    stdout.finish()?;
+    Err(invalid_tags_error(invalid.len(), pattern))
+}
+
+fn invalid_tags_error(count: usize, pattern: &Regex) -> Error {
+    Error::with_kind(
+        ErrorKind::Validation,
+        &format!("{} tags do not match {}", count, pattern),
+    )
+}
+
+/// Every problem found with each rule of `entries`
+///
+/// Shadowed rules are only looked for with a `cache` built from `entries`.
+fn rule_failures(
+    repo: &Path, entries: &[CodeownersEntry], cache: Option<&CodeownersCache>,
+    strict_tags: Option<&Regex>,
+) -> Result<RuleFailures> {
+    let mut failures = RuleFailures::new();
+    let mut fail = |source_file: &Path, line_number: usize, message: String| {
+        failures
+            .entry((source_file.to_path_buf(), line_number))
+            .or_default()
+            .push(message);
+    };
+
+    for (entry, error) in invalid_patterns(entries) {
+        fail(
+            &entry.source_file,
+            entry.line_number,
+            format!("Invalid pattern: {}", error),
+        );
+    }
+
+    if let Some(cache) = cache {
+        let matchers = cache.entry_matchers(repo);
+        for shadowed in find_shadowed_rules(cache, &matchers)? {
+            fail(
+                &shadowed.rule.source_file,
+                shadowed.rule.line_number,
+                format!(
+                    "Matches {} files but is shadowed by {}",
+                    shadowed.matched_files,
+                    // Reported in JUnit, where lines count from 1
+                    describe_rule(repo, shadowed.shadowed_by, 1)
+                ),
+            );
+        }
+    }
+
+    if let Some(pattern) = strict_tags {
+        for (entry, tag) in find_invalid_tags(entries, pattern) {
+            fail(
+                &entry.source_file,
+                entry.line_number,
+                format!("Tag #{} does not match {}", tag.0, pattern),
+            );
+        }
+    }
+
+    Ok(failures)
+}
+
+/// One JUnit test case per rule, grouped by CODEOWNERS file
+fn junit_cases(repo: &Path, entries: &[CodeownersEntry], failures: &RuleFailures) -> Vec<TestCase> {
+    entries
+        .iter()
+        .map(|entry| {
+            let source = entry
+                .source_file
+                .strip_prefix(repo)
+                .unwrap_or(&entry.source_file)
+                .display()
+                .to_string();
+            // CI annotations count lines from 1, unlike `line_number`
+            let line = entry.line_number + 1;
+            TestCase {
+                name: format!("{} (line {})", entry.pattern, line),
+                classname: source.clone(),
+                file: source,
+                line,
+                failure: failures
+                    .get(&(entry.source_file.clone(), entry.line_number))
+                    .map(|messages| messages.join("\n")),
+            }
+        })
+        .collect()
+}
+
+/// Tags that don't match `pattern`, with each rule declaring them, in rule order
+fn find_invalid_tags<'a>(
+    entries: &'a [CodeownersEntry], pattern: &Regex,
+) -> Vec<(&'a CodeownersEntry, &'a Tag)> {
+    let invalid: HashSet<Tag> = collect_tags(entries)
+        .into_iter()
+        .filter(|tag| !pattern.is_match(&tag.0))
+        .collect();
+
+    entries
+        .iter()
+        .flat_map(|entry| entry.tags.iter().map(move |tag| (entry, tag)))
+        .filter(|(_, tag)| invalid.contains(tag))
+        .collect()
+}
+
+/// Find rules that match at least one file but never win for any of them
+///
+/// Results follow the order of `matchers`. A cache built with
+/// [`OwnerMerge::Union`] has none, as every matching rule contributes owners.
+fn find_shadowed_rules<'a>(
+    cache: &CodeownersCache, matchers: &'a [CodeownersEntryMatcher],
+) -> Result<Vec<ShadowedRule<'a>>> {
+    if cache.owner_merge == OwnerMerge::Union {
+        return Ok(Vec::new());
+    }
+
+    let mut winners = HashSet::new();
+    // Rule index -> (number of matched files, index of the rule that first beat it)
+    let mut losers: HashMap<usize, (usize, usize)> = HashMap::new();
+
+    for file in &cache.files {
+        let matching = find_matching_indices_for_file(&file.path, matchers)?;
+        let Some(&winner) = matching.first() else {
+            continue;
+        };
+        winners.insert(winner);
+
+        for &loser in matching.iter().skip(1) {
+            losers.entry(loser).or_insert((0, winner)).0 += 1;
+        }
+    }
+
+    let mut shadowed: Vec<_> = losers
+        .into_iter()
+        .filter(|(index, _)| !winners.contains(index))
+        .collect();
+    shadowed.sort_unstable_by_key(|(index, _)| *index);
+
+    Ok(shadowed
+        .into_iter()
+        .map(|(index, (matched_files, winner))| ShadowedRule {
+            rule: &matchers[index],
+            shadowed_by: &matchers[winner],
+            matched_files,
+        })
+        .collect())
+}
+
+/// Pattern-only rules, with the number of files each one wins for
+///
+/// `matchers` are built from the entries of `cache`, in the same order.
+fn ownerless_rules<'a>(
+    cache: &CodeownersCache, matchers: &'a [CodeownersEntryMatcher],
+) -> Result<Vec<(&'a CodeownersEntryMatcher, usize)>> {
+    let mut cleared: HashMap<(&Path, usize), usize> = HashMap::new();
+    for file in &cache.files {
+        let matching = find_matching_entries_for_file(&file.path, matchers)?;
+        if let Some(winner) = matching.first() {
+            *cleared
+                .entry((winner.source_file.as_path(), winner.line_number))
+                .or_default() += 1;
+        }
+    }
+
+    Ok(cache
+        .entries
+        .iter()
+        .zip(matchers)
+        .filter(|(entry, _)| entry.clears_ownership())
+        .map(|(_, rule)| {
+            let files = cleared
+                .get(&(rule.source_file.as_path(), rule.line_number))
+                .copied()
+                .unwrap_or(0);
+            (rule, files)
+        })
+        .collect())
+}
+
+fn warn_ownerless_rules(repo: &Path, rules: &[(&CodeownersEntryMatcher, usize)]) {
+    for (rule, files) in rules {
+        let source = rule
+            .source_file
+            .strip_prefix(repo)
+            .unwrap_or(&rule.source_file);
+        eprintln!(
+            "warning: {}:{} {} has no owners and leaves {} files unowned",
+            source.display(),
+            rule.line_number,
+            rule.pattern,
+            files
+        );
+    }
+}
+
+/// One-line description of a rule with its location relative to the repository
+///
+/// Lines are counted from `first_line`: 0 like `line_number`, or 1 for CI annotations.
+fn describe_rule(repo: &Path, entry: &CodeownersEntryMatcher, first_line: usize) -> String {
+    let source = entry
+        .source_file
+        .strip_prefix(repo)
+        .unwrap_or(&entry.source_file);
+    let owners: Vec<String> = entry.owners.iter().map(|o| o.to_string()).collect();
+    format!(
+        "{}:{} {} {}",
+        source.display(),
+        entry.line_number + first_line,
+        entry.pattern,
+        owners.join(" ")
+    )
+    .trim_end()
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{parse::analyze_repo, types::PatternAnchor};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_root_rule_shadowed_by_nested_codeowners() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+
+        fs::write(
+            base_path.join("CODEOWNERS"),
+            "*.md @docs-team\n/src/main.rs @root-team\n",
+        )?;
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::write(base_path.join("src").join("CODEOWNERS"), "*.rs @src-team\n")?;
+        fs::write(base_path.join("src").join("main.rs"), "fn main() {}")?;
+        fs::write(base_path.join("README.md"), "# Readme")?;
+
+        let cache = analyze_repo(base_path, false, OwnerMerge::Winner, PatternAnchor::FileDir)?;
+        let matchers = cache.entry_matchers(base_path);
+
+        let shadowed = find_shadowed_rules(&cache, &matchers)?;
+        assert_eq!(shadowed.len(), 1);
+        assert_eq!(
+            describe_rule(base_path, shadowed[0].rule, 0),
+            "CODEOWNERS:1 /src/main.rs @root-team"
+        );
+        assert_eq!(
+            describe_rule(base_path, shadowed[0].shadowed_by, 0),
+            "src/CODEOWNERS:0 *.rs @src-team"
+        );
+        assert_eq!(shadowed[0].matched_files, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_rules_shadowed_when_owners_are_merged() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+
+        fs::write(
+            base_path.join("CODEOWNERS"),
+            "*.rs @rust-team\n/src/main.rs @root-team\n",
+        )?;
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::write(base_path.join("src").join("CODEOWNERS"), "*.rs @src-team\n")?;
+        fs::write(base_path.join("src").join("main.rs"), "fn main() {}")?;
+
+        let cache = analyze_repo(base_path, false, OwnerMerge::Union, PatternAnchor::FileDir)?;
+        let matchers = cache.entry_matchers(base_path);
+        assert!(find_shadowed_rules(&cache, &matchers)?.is_empty());
+
+        // Every matching rule owns the file, so none of them is flagged
+        let owners: Vec<String> = cache.files[0]
+            .owners
+            .iter()
+            .map(|owner| owner.identifier.clone())
+            .collect();
+        assert_eq!(owners.len(), 3);
+        assert!(rule_failures(base_path, &cache.entries, Some(&cache), None)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ownerless_rules_count_files_left_unowned() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+
+        fs::write(
+            base_path.join("CODEOWNERS"),
+            "*.rs @rust-team\n*.generated\n/vendor/\n",
+        )?;
+        fs::write(base_path.join("main.rs"), "fn main() {}")?;
+        fs::write(base_path.join("schema.generated"), "")?;
+        fs::write(base_path.join("notes.txt"), "")?;
+
+        let cache = analyze_repo(base_path, false, OwnerMerge::Winner, PatternAnchor::FileDir)?;
+        let matchers = cache.entry_matchers(base_path);
+        let ownerless = ownerless_rules(&cache, &matchers)?;
+
+        // Both pattern-only rules are reported, even one matching no file
+        let summary: Vec<(&str, usize)> = ownerless
+            .iter()
+            .map(|(rule, files)| (rule.pattern.as_str(), *files))
+            .collect();
+        assert_eq!(summary, vec![("*.generated", 1), ("/vendor/", 0)]);
+
+        // Unowned either way, but only one file was cleared on purpose
+        let unowned = |name: &str| {
+            cache
+                .files
+                .iter()
+                .find(|file| file.path.ends_with(name))
+                .unwrap()
+                .owners
+                .is_empty()
+        };
+        assert!(unowned("schema.generated") && unowned("notes.txt"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_strict_tags_flags_nonconforming_tags() {
+        let entry = |line_number: usize, tags: &[&str]| CodeownersEntry {
+            source_file: Path::new("CODEOWNERS").to_path_buf(),
+            line_number,
+            pattern: "*".to_string(),
+            owners: vec![],
+            tags: tags.iter().map(|tag| Tag(tag.to_string())).collect(),
+            comment: None,
+            raw: String::new(),
+        };
+        let entries = vec![
+            entry(0, &["backend", "area/api_v2"]),
+            entry(1, &["Backend", "team x"]),
+            entry(2, &["backend", "Backend"]),
+        ];
+
+        let pattern = Regex::new(DEFAULT_TAG_PATTERN).unwrap();
+        let invalid: Vec<(usize, &str)> = find_invalid_tags(&entries, &pattern)
+            .iter()
+            .map(|(entry, tag)| (entry.line_number, tag.0.as_str()))
+            .collect();
+        assert_eq!(invalid, vec![(1, "Backend"), (1, "team x"), (2, "Backend")]);
+    }
+
+    #[test]
+    fn test_junit_report_fails_shadowed_rules_and_bad_tags() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+
+        fs::write(
+            base_path.join("CODEOWNERS"),
+            "*.md @docs-team #Docs\n/src/main.rs @root-team\n",
+        )?;
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::write(base_path.join("src").join("CODEOWNERS"), "*.rs @src-team\n")?;
+        fs::write(base_path.join("src").join("main.rs"), "fn main() {}")?;
+        fs::write(base_path.join("README.md"), "# Readme")?;
+
+        let cache = analyze_repo(base_path, false, OwnerMerge::Winner, PatternAnchor::FileDir)?;
+        let pattern = Regex::new(DEFAULT_TAG_PATTERN).unwrap();
+        let failures = rule_failures(base_path, &cache.entries, Some(&cache), Some(&pattern))?;
+        let xml = junit::render_suite(
+            "codeowners audit",
+            &junit_cases(base_path, &cache.entries, &failures),
+        );
+
+        assert!(xml.contains("<testsuite name=\"codeowners audit\" tests=\"3\" failures=\"2\""));
+        assert!(xml.contains("<failure message=\"Tag #Docs does not match"));
+        assert!(xml.contains(
+            "<failure message=\"Matches 1 files but is shadowed by src/CODEOWNERS:1 *.rs @src-team\">CODEOWNERS:2: "
+        ));
+        assert!(xml.contains(
+            "<testcase name=\"*.rs (line 1)\" classname=\"src/CODEOWNERS\" file=\"src/CODEOWNERS\" line=\"1\"/>"
+        ));
+
+        // Invalid patterns fail their rule even without a cache
+        let entries = vec![CodeownersEntry {
+            source_file: base_path.join("CODEOWNERS"),
+            line_number: 0,
+            pattern: "[invalid".to_string(),
+            owners: vec![],
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        }];
+        let failures = rule_failures(base_path, &entries, None, None)?;
+        assert_eq!(failures.len(), 1);
+        assert!(failures[&(base_path.join("CODEOWNERS"), 0)][0].starts_with("Invalid pattern: "));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{
+    core::{
+        cache::{detect_encoding, load_cache},
+        display::stdout_writer,
+        types::CacheEncoding,
+    },
+    utils::error::Result,
+};
+use std::io::Write;
+use std::path::Path;
+
+/// Metadata of a cache file, without its contents
+#[derive(Debug, PartialEq, Eq)]
+struct CacheInfo {
+    encoding: CacheEncoding,
+    /// Repository hash the cache was built for, as lowercase hex
+    hash: String,
+    entries: usize,
+    files: usize,
+    owners: usize,
+    tags: usize,
+}
+
+/// Print the encoding, hash and sizes of the cache file at `path`
+pub fn run(path: &Path) -> Result<()> {
+    let info = cache_info(path)?;
+
+    let encoding = match info.encoding {
+        CacheEncoding::Bincode => "bincode",
+        CacheEncoding::Json => "json",
+    };
+    let mut stdout = stdout_writer();
+    writeln!(stdout, "Cache:    {}", path.display())?;
+    writeln!(stdout, "Encoding: {}", encoding)?;
+    writeln!(stdout, "Hash:     {}", info.hash)?;
+    writeln!(stdout, "Entries:  {}", info.entries)?;
+    writeln!(stdout, "Files:    {}", info.files)?;
+    writeln!(stdout, "Owners:   {}", info.owners)?;
+    writeln!(stdout, "Tags:     {}", info.tags)?;
+
+    stdout.finish()
+}
+
+fn cache_info(path: &Path) -> Result<CacheInfo> {
+    let encoding = detect_encoding(path)?;
+    let cache = load_cache(path)?;
+
+    Ok(CacheInfo {
+        encoding,
+        hash: cache
+            .hash
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+        entries: cache.entries.len(),
+        files: cache.files.len(),
+        owners: cache.owners_map.len(),
+        tags: cache.tags_map.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        cache::store_cache,
+        parser::parse_owner,
+        types::{CodeownersCache, CodeownersEntry, FileEntry, OwnerMerge, PatternAnchor, Tag},
+    };
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cache_info_reports_counts_and_encoding() -> Result<()> {
+        let owner = parse_owner("@rust-team")?;
+        let tag = Tag("core".to_string());
+        let cache = CodeownersCache {
+            hash: [0xab; 32],
+            entries: vec![CodeownersEntry {
+                source_file: PathBuf::from("CODEOWNERS"),
+                line_number: 0,
+                pattern: "*.rs".to_string(),
+                owners: vec![owner.clone()],
+                tags: vec![tag.clone()],
+                comment: None,
+                raw: String::new(),
+            }],
+            files: ["main.rs", "lib.rs"]
+                .iter()
+                .map(|path| FileEntry {
+                    path: PathBuf::from(path),
+                    owners: vec![owner.clone()],
+                    tags: vec![tag.clone()],
+                    inline_override: false,
+                })
+                .collect(),
+            owners_map: HashMap::from([(
+                owner,
+                vec![PathBuf::from("main.rs"), PathBuf::from("lib.rs")],
+            )]),
+            tags_map: HashMap::from([(
+                tag,
+                vec![PathBuf::from("main.rs"), PathBuf::from("lib.rs")],
+            )]),
+            case_insensitive: false,
+            owner_merge: OwnerMerge::Winner,
+            anchor: PatternAnchor::FileDir,
+            follow_links: false,
+            dedupe_paths: false,
+            owner_aliases: HashMap::new(),
+            head_commit: None,
+            identity_map: HashMap::new(),
+        };
+
+        let temp_dir = TempDir::new()?;
+        for encoding in [CacheEncoding::Bincode, CacheEncoding::Json] {
+            let path = temp_dir.path().join(format!("{:?}.cache", encoding));
+            store_cache(&cache, &path, encoding)?;
+
+            assert_eq!(
+                cache_info(&path)?,
+                CacheInfo {
+                    encoding,
+                    hash: "ab".repeat(32),
+                    entries: 1,
+                    files: 2,
+                    owners: 1,
+                    tags: 1,
+                }
+            );
+        }
+
+        Ok(())
+    }
+}
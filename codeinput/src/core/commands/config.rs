@@ -1,4 +1,8 @@
-use crate::utils::{app_config::AppConfig, error::Result};
+use crate::{
+    core::display::stdout_writer,
+    utils::{app_config::AppConfig, error::Result},
+};
+use std::io::Write;
 use tabled::{Table, Tabled};
 
 #[derive(Tabled)]
@@ -7,31 +11,74 @@ struct ConfigDisplay {
     key: String,
     #[tabled(rename = "Value")]
     value: String,
+    #[tabled(rename = "Source")]
+    source: String,
 }
 
 /// Show the configuration file
-pub fn run() -> Result<()> {
+///
+/// With `show_sources`, each setting is labeled with the layer its value comes
+/// from: the defaults, the config file, the environment or the command line.
+pub fn run(show_sources: bool) -> Result<()> {
     let config = AppConfig::fetch()?;
 
-    let table_data = vec![
-        ConfigDisplay {
-            key: "Debug Mode".to_string(),
-            value: config.debug.to_string(),
-        },
-        ConfigDisplay {
-            key: "Log Level".to_string(),
-            value: config.log_level.to_string(),
-        },
-        ConfigDisplay {
-            key: "Cache File".to_string(),
-            value: config.cache_file,
-        },
+    let settings = [
+        ("Debug Mode", "debug", config.debug.to_string()),
+        ("Log Level", "log_level", config.log_level.to_string()),
+        ("Cache File", "cache_file", config.cache_file),
+        (
+            "Skipped Directories",
+            "skip_dirs",
+            config.skip_dirs.join(", "),
+        ),
+        (
+            "Cache TTL (seconds)",
+            "cache_ttl_secs",
+            config.cache_ttl_secs.to_string(),
+        ),
+        ("Hash Mode", "hash_mode", config.hash_mode.to_string()),
+        (
+            "Parallel Threshold",
+            "parallel_threshold",
+            config.parallel_threshold.to_string(),
+        ),
+        ("Chunk Size", "chunk_size", config.chunk_size.to_string()),
+        (
+            "Max Output Bytes",
+            "max_output_bytes",
+            config.max_output_bytes.to_string(),
+        ),
+        (
+            "JSON Progress Events",
+            "progress_json",
+            config.progress_json.to_string(),
+        ),
+        (
+            "Max Inline Scan Bytes",
+            "max_inline_scan_bytes",
+            config.max_inline_scan_bytes.to_string(),
+        ),
     ];
 
+    let table_data: Vec<ConfigDisplay> = settings
+        .into_iter()
+        .map(|(label, key, value)| ConfigDisplay {
+            key: label.to_string(),
+            value,
+            source: AppConfig::source_of(key).to_string(),
+        })
+        .collect();
+
     let mut table = Table::new(table_data);
+    if !show_sources {
+        table.with(tabled::settings::Remove::column(
+            tabled::settings::location::Locator::column("Source"),
+        ));
+    }
     table.with(tabled::settings::Style::modern());
 
-    println!("{}", table);
+    let mut stdout = stdout_writer();
+    writeln!(stdout, "{}", table)?;
 
-    Ok(())
+    stdout.finish()
 }
@@ -0,0 +1,157 @@
+use crate::{
+    core::{
+        cache::sync_cache,
+        display::{stdout_writer, write_json_pretty},
+        types::{Owner, OwnerType},
+    },
+    utils::error::Result,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Output formats for `contacts`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactFormat {
+    Json,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+/// An email owner and the number of files it owns
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct Contact {
+    email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    local_part: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    domain: Option<String>,
+    files: usize,
+}
+
+/// Export the email owners in the cache as a contact list
+pub fn run(
+    repo: Option<&std::path::Path>, format: ContactFormat, split_address: bool,
+    cache_file: Option<&std::path::Path>,
+) -> Result<()> {
+    // Repository path
+    let repo = repo.unwrap_or_else(|| std::path::Path::new("."));
+
+    // Load the cache
+    let cache = sync_cache(repo, cache_file)?;
+
+    let contacts = collect_contacts(&cache.owners_map, split_address);
+
+    let mut stdout = stdout_writer();
+    match format {
+        ContactFormat::Json => write_json_pretty(&mut stdout, &contacts)?,
+        ContactFormat::Csv => write!(stdout, "{}", render_csv(&contacts, split_address))?,
+    }
+
+    stdout.finish()
+}
+
+/// Distinct email owners sorted by file count (descending), then by address
+fn collect_contacts(
+    owners_map: &HashMap<Owner, Vec<PathBuf>>, split_address: bool,
+) -> Vec<Contact> {
+    let mut contacts: Vec<Contact> = owners_map
+        .iter()
+        .filter(|(owner, _)| owner.owner_type == OwnerType::Email)
+        .map(|(owner, paths)| {
+            let parts = owner.identifier.rsplit_once('@').filter(|_| split_address);
+            Contact {
+                email: owner.identifier.clone(),
+                local_part: parts.map(|(local, _)| local.to_string()),
+                domain: parts.map(|(_, domain)| domain.to_string()),
+                files: paths.len(),
+            }
+        })
+        .collect();
+    contacts.sort_by(|a, b| b.files.cmp(&a.files).then_with(|| a.email.cmp(&b.email)));
+    contacts
+}
+
+fn render_csv(contacts: &[Contact], split_address: bool) -> String {
+    let mut csv = String::from(if split_address {
+        "email,local_part,domain,files\n"
+    } else {
+        "email,files\n"
+    });
+    for contact in contacts {
+        let mut fields = vec![csv_field(&contact.email)];
+        if split_address {
+            fields.push(csv_field(contact.local_part.as_deref().unwrap_or("")));
+            fields.push(csv_field(contact.domain.as_deref().unwrap_or("")));
+        }
+        fields.push(contact.files.to_string());
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owner(identifier: &str, owner_type: OwnerType) -> Owner {
+        Owner {
+            identifier: identifier.to_string(),
+            owner_type,
+        }
+    }
+
+    #[test]
+    fn test_contacts_from_email_owners() {
+        let paths = |n: usize| (0..n).map(|i| PathBuf::from(format!("f{}", i))).collect();
+        let owners_map: HashMap<Owner, Vec<PathBuf>> = HashMap::from([
+            (owner("bob@example.com", OwnerType::Email), paths(1)),
+            (owner("alice@example.com", OwnerType::Email), paths(3)),
+            (owner("carol@corp.io", OwnerType::Email), paths(1)),
+            (owner("@org/core", OwnerType::Team), paths(5)),
+        ]);
+
+        let contacts = collect_contacts(&owners_map, false);
+        let emails: Vec<&str> = contacts.iter().map(|c| c.email.as_str()).collect();
+        assert_eq!(
+            emails,
+            vec!["alice@example.com", "bob@example.com", "carol@corp.io"]
+        );
+        assert_eq!(
+            render_csv(&contacts, false),
+            "email,files\nalice@example.com,3\nbob@example.com,1\ncarol@corp.io,1\n"
+        );
+
+        let contacts = collect_contacts(&owners_map, true);
+        assert_eq!(
+            contacts[2],
+            Contact {
+                email: "carol@corp.io".to_string(),
+                local_part: Some("carol".to_string()),
+                domain: Some("corp.io".to_string()),
+                files: 1,
+            }
+        );
+        assert_eq!(
+            render_csv(&contacts[..1], true),
+            "email,local_part,domain,files\nalice@example.com,alice,example.com,3\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("a@b.com"), "a@b.com");
+        assert_eq!(csv_field("\"a,b\"@c.com"), "\"\"\"a,b\"\"@c.com\"");
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{
+    core::{
+        cache::{sync_cache, write_cache},
+        display::stdout_writer,
+        types::CacheEncoding,
+    },
+    utils::error::Result,
+};
+use std::path::Path;
+
+/// Write the whole cache to stdout, for piping into other tools
+///
+/// The output is byte-for-byte what a cache file with the same `encoding`
+/// would contain, so it can be read back like one.
+pub fn run(repo: Option<&Path>, encoding: CacheEncoding, cache_file: Option<&Path>) -> Result<()> {
+    // Repository path
+    let repo = repo.unwrap_or_else(|| Path::new("."));
+
+    // Load the cache
+    let cache = sync_cache(repo, cache_file)?;
+
+    let mut stdout = stdout_writer();
+    write_cache(&cache, &mut stdout, encoding)?;
+
+    stdout.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        cache::load_cache,
+        parse::analyze_repo,
+        types::{OwnerMerge, PatternAnchor},
+    };
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_dumped_cache_round_trips() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+
+        fs::write(base_path.join("CODEOWNERS"), "*.rs @rust-team #rust\n")?;
+        fs::write(base_path.join("main.rs"), "fn main() {}")?;
+        let mut cache = analyze_repo(base_path, false, OwnerMerge::Winner, PatternAnchor::FileDir)?;
+        // Raw lines are left out of caches
+        for entry in &mut cache.entries {
+            entry.raw.clear();
+        }
+
+        for encoding in [CacheEncoding::Bincode, CacheEncoding::Json] {
+            let mut dumped = Vec::new();
+            write_cache(&cache, &mut dumped, encoding)?;
+            let dump_file = base_path.join("dump");
+            fs::write(&dump_file, &dumped)?;
+
+            let loaded = load_cache(&dump_file)?;
+            assert_eq!(loaded.hash, cache.hash);
+            assert_eq!(loaded.entries, cache.entries);
+            assert_eq!(loaded.files, cache.files);
+            assert_eq!(loaded.owners_map, cache.owners_map);
+            assert_eq!(loaded.tags_map, cache.tags_map);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{
+    core::{cache::sync_cache, display::stdout_writer, types::FileEntry},
+    utils::error::Result,
+};
+use std::io::Write;
+
+/// Output formats for `export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One `path<TAB>owner1,owner2` line per file
+    Flat,
+}
+
+/// Export the resolved ownership of every file in the cache
+pub fn run(
+    repo: Option<&std::path::Path>, format: ExportFormat, only_owned: bool,
+    cache_file: Option<&std::path::Path>,
+) -> Result<()> {
+    // Repository path
+    let repo = repo.unwrap_or_else(|| std::path::Path::new("."));
+
+    // Load the cache
+    let cache = sync_cache(repo, cache_file)?;
+
+    let mut stdout = stdout_writer();
+    match format {
+        ExportFormat::Flat => write!(stdout, "{}", render_flat(&cache.files, only_owned))?,
+    }
+
+    stdout.finish()
+}
+
+/// Render files as tab-delimited `path<TAB>owners` lines
+///
+/// Owners are comma-separated; unowned files have an empty owners column.
+fn render_flat(files: &[FileEntry], only_owned: bool) -> String {
+    files
+        .iter()
+        .filter(|file| !only_owned || !file.is_effectively_unowned())
+        .map(|file| {
+            let owners = file
+                .owners
+                .iter()
+                .map(|owner| owner.identifier.as_str())
+                .collect::<Vec<_>>()
+                .join(",");
+            format!("{}\t{}\n", file.path.display(), owners)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{Owner, OwnerType};
+    use std::path::PathBuf;
+
+    fn create_test_file_entry(path: &str, owners: &[(&str, OwnerType)]) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from(path),
+            owners: owners
+                .iter()
+                .map(|(identifier, owner_type)| Owner {
+                    identifier: identifier.to_string(),
+                    owner_type: owner_type.clone(),
+                })
+                .collect(),
+            tags: vec![],
+            inline_override: false,
+        }
+    }
+
+    #[test]
+    fn test_render_flat_is_tab_delimited() {
+        let files = vec![
+            create_test_file_entry(
+                "src/main.rs",
+                &[("@org/core", OwnerType::Team), ("@alice", OwnerType::User)],
+            ),
+            create_test_file_entry("docs/guide.md", &[("@org/docs", OwnerType::Team)]),
+            create_test_file_entry("scratch.txt", &[]),
+            create_test_file_entry("vendor/lib.c", &[("NOOWNER", OwnerType::Unowned)]),
+        ];
+
+        assert_eq!(
+            render_flat(&files, false),
+            "src/main.rs\t@org/core,@alice\n\
+             docs/guide.md\t@org/docs\n\
+             scratch.txt\t\n\
+             vendor/lib.c\tNOOWNER\n"
+        );
+
+        assert_eq!(
+            render_flat(&files, true),
+            "src/main.rs\t@org/core,@alice\n\
+             docs/guide.md\t@org/docs\n"
+        );
+    }
+}
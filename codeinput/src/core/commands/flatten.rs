@@ -0,0 +1,149 @@
+use crate::{
+    core::{cache::sync_cache, display::stdout_writer, types::CodeownersEntry},
+    utils::error::Result,
+};
+use std::io::Write;
+use std::path::{Component, Path};
+
+/// Print a single CODEOWNERS file equivalent to all CODEOWNERS files in the repository
+///
+/// Rules from nested CODEOWNERS files are rewritten relative to the repository
+/// root and placed after the rules they take precedence over, so the last
+/// matching rule still wins.
+pub fn run(repo: Option<&Path>, cache_file: Option<&Path>) -> Result<()> {
+    // Repository path
+    let repo = repo.unwrap_or_else(|| Path::new("."));
+
+    // Load the cache
+    let cache = sync_cache(repo, cache_file)?;
+
+    let mut stdout = stdout_writer();
+    write!(stdout, "{}", flatten(&cache.entries, repo))?;
+    stdout.finish()
+}
+
+/// Render `entries` as one CODEOWNERS document rooted at `repo`
+///
+/// Entries are ordered by the depth of their CODEOWNERS file, then by file and
+/// line, with a comment naming each source file.
+fn flatten(entries: &[CodeownersEntry], repo: &Path) -> String {
+    let mut sorted: Vec<(String, &CodeownersEntry)> = entries
+        .iter()
+        .map(|entry| (codeowners_dir(entry, repo), entry))
+        .collect();
+    sorted.sort_by(|(a_dir, a), (b_dir, b)| {
+        let depth = |dir: &str| dir.split('/').filter(|part| !part.is_empty()).count();
+        depth(a_dir)
+            .cmp(&depth(b_dir))
+            .then_with(|| a_dir.cmp(b_dir))
+            .then_with(|| a.line_number.cmp(&b.line_number))
+    });
+
+    let mut output = String::new();
+    let mut current_source = None;
+    for (dir, entry) in sorted {
+        if current_source != Some(&entry.source_file) {
+            if current_source.is_some() {
+                output.push('\n');
+            }
+            let source = entry
+                .source_file
+                .strip_prefix(repo)
+                .unwrap_or(&entry.source_file);
+            output.push_str(&format!("# {}\n", source.display()));
+            current_source = Some(&entry.source_file);
+        }
+
+        let mut line = prefix_pattern(&dir, &entry.pattern);
+        if line.contains(char::is_whitespace) {
+            line = format!("\"{}\"", line);
+        }
+        for owner in &entry.owners {
+            line.push_str(&format!(" {}", owner));
+        }
+        for tag in &entry.tags {
+            line.push_str(&format!(" #{}", tag));
+        }
+        output.push_str(&line);
+        output.push('\n');
+    }
+    output
+}
+
+/// Directory of the entry's CODEOWNERS file relative to `repo`, `/`-separated
+fn codeowners_dir(entry: &CodeownersEntry, repo: &Path) -> String {
+    let dir = entry.source_file.parent().unwrap_or(Path::new(""));
+    let dir = dir.strip_prefix(repo).unwrap_or(dir);
+    dir.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Rewrite a pattern from a CODEOWNERS file in `dir` to be relative to the root
+///
+/// Patterns with a slash other than a trailing one are anchored to `dir`;
+/// patterns without one match at any depth below it.
+fn prefix_pattern(dir: &str, pattern: &str) -> String {
+    if dir.is_empty() {
+        return pattern.to_string();
+    }
+
+    let anchored = pattern.trim_end_matches('/').contains('/');
+    if anchored {
+        format!("/{}/{}", dir, pattern.trim_start_matches('/'))
+    } else {
+        format!("/{}/**/{}", dir, pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{Owner, Tag};
+    use std::path::PathBuf;
+
+    fn entry(source_file: &str, line_number: usize, pattern: &str, owner: &str) -> CodeownersEntry {
+        CodeownersEntry {
+            source_file: PathBuf::from(source_file),
+            line_number,
+            pattern: pattern.to_string(),
+            owners: vec![owner.parse::<Owner>().unwrap()],
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_flatten_prefixes_nested_patterns() {
+        let mut tagged = entry("/repo/src/api/CODEOWNERS", 1, "/v1/", "@org/api-v1");
+        tagged.tags = vec![Tag("legacy".to_string())];
+        let entries = vec![
+            entry("/repo/src/api/CODEOWNERS", 0, "*.rs", "@org/api"),
+            tagged,
+            entry("/repo/src/CODEOWNERS", 0, "*", "@org/src"),
+            entry("/repo/src/CODEOWNERS", 1, "lib/*.rs", "@org/lib"),
+            entry("/repo/CODEOWNERS", 0, "*", "@org/all"),
+            entry("/repo/CODEOWNERS", 1, " notes.txt", "@org/docs"),
+        ];
+
+        assert_eq!(
+            flatten(&entries, Path::new("/repo")),
+            "# CODEOWNERS\n\
+             * @org/all\n\
+             \" notes.txt\" @org/docs\n\
+             \n\
+             # src/CODEOWNERS\n\
+             /src/**/* @org/src\n\
+             /src/lib/*.rs @org/lib\n\
+             \n\
+             # src/api/CODEOWNERS\n\
+             /src/api/**/*.rs @org/api\n\
+             /src/api/v1/ @org/api-v1 #legacy\n"
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use crate::core::{
     cache::load_cache,
-    common::find_files,
+    common::{find_files, format_walk_errors},
+    display::stdout_writer,
     resolver::find_owners_and_tags_for_file,
-    types::{CodeownersCache, Owner, OwnerType, codeowners_entry_to_matcher},
+    types::{CodeownersCache, Owner, OwnerType},
 };
 use crate::utils::error::{Error, Result};
 use git2::{Blame, BlameOptions, Repository, Time};
@@ -88,10 +89,13 @@ pub fn run(
         .map_err(|e| Error::with_source("Failed to open git repository", Box::new(e)))?;
 
     // Find files to analyze
-    let files = find_files(base_path)?;
+    let (files, walk_errors) = find_files(base_path, false)?;
+    if let Some(warning) = format_walk_errors(&walk_errors, false) {
+        eprintln!("{}", warning);
+    }
     let files_to_analyze = match scope {
         InferScope::All => files,
-        InferScope::Unowned => filter_unowned_files(files, &cache)?,
+        InferScope::Unowned => filter_unowned_files(files, base_path, &cache)?,
     };
 
     log::info!("Analyzing {} files for ownership inference", files_to_analyze.len());
@@ -117,7 +121,7 @@ pub fn run(
     if output_file.is_some() {
         output_codeowners(&inferences, output_file)?;
     } else {
-        output_text(&inferences);
+        output_text(&inferences)?;
     }
 
     Ok(())
@@ -125,6 +129,7 @@ pub fn run(
 
 fn filter_unowned_files(
     files: Vec<PathBuf>,
+    base_path: &Path,
     cache: &Option<CodeownersCache>,
 ) -> Result<Vec<PathBuf>> {
     let Some(cache) = cache else {
@@ -132,9 +137,9 @@ fn filter_unowned_files(
     };
 
     let mut unowned_files = Vec::new();
-    let matchers: Vec<_> = cache.entries.iter().map(codeowners_entry_to_matcher).collect();
+    let matchers = cache.entry_matchers(base_path);
     for file in files {
-        let (owners, _tags) = find_owners_and_tags_for_file(&file, &matchers)?;
+        let (owners, _tags) = find_owners_and_tags_for_file(&file, &matchers, cache.owner_merge)?;
         if owners.is_empty() || owners.iter().all(|o| o.owner_type == OwnerType::Unowned) {
             unowned_files.push(file);
         }
@@ -156,8 +161,10 @@ fn analyze_file_ownership(
     // Get existing owners from cache
     let existing_owners = match cache {
         Some(cache) => {
-            let matchers: Vec<_> = cache.entries.iter().map(codeowners_entry_to_matcher).collect();
-            let (owners, _tags) = find_owners_and_tags_for_file(file_path, &matchers).unwrap_or_default();
+            let matchers = cache.entry_matchers(base_path);
+            let (owners, _tags) =
+                find_owners_and_tags_for_file(file_path, &matchers, cache.owner_merge)
+                    .unwrap_or_default();
             owners
         },
         None => Vec::new(),
@@ -369,10 +376,11 @@ fn commit_touches_file(repo: &Repository, commit: &git2::Commit, file_path: &Pat
 }
 
 
-fn output_text(inferences: &[FileOwnershipInference]) {
+fn output_text(inferences: &[FileOwnershipInference]) -> Result<()> {
+    let mut stdout = stdout_writer();
     if inferences.is_empty() {
-        println!("No ownership inferences found.");
-        return;
+        writeln!(stdout, "No ownership inferences found.")?;
+        return stdout.finish();
     }
 
     let mut rows = Vec::new();
@@ -408,14 +416,16 @@ fn output_text(inferences: &[FileOwnershipInference]) {
     }
 
     let table = Table::new(rows);
-    println!("{}", table);
+    writeln!(stdout, "{}", table)?;
     
-    println!("\nSummary:");
-    println!("  Total files analyzed: {}", inferences.len());
-    println!("  Files with inferred owners: {}", 
-        inferences.iter().filter(|i| !i.inferred_owners.is_empty()).count());
-    println!("  Average confidence: {:.1}%", 
-        inferences.iter().map(|i| i.confidence).sum::<f64>() / inferences.len() as f64 * 100.0);
+    writeln!(stdout, "\nSummary:")?;
+    writeln!(stdout, "  Total files analyzed: {}", inferences.len())?;
+    writeln!(stdout, "  Files with inferred owners: {}", 
+        inferences.iter().filter(|i| !i.inferred_owners.is_empty()).count())?;
+    writeln!(stdout, "  Average confidence: {:.1}%", 
+        inferences.iter().map(|i| i.confidence).sum::<f64>() / inferences.len() as f64 * 100.0)?;
+
+    stdout.finish()
 }
 
 
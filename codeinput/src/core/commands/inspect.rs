@@ -1,13 +1,19 @@
 use crate::{
     core::{
         cache::sync_cache,
-        types::{CodeownersEntry, OutputFormat},
+        display::stdout_writer,
+        resolver::resolve_directory,
+        types::{CodeownersCache, CodeownersEntry, FileEntry, OutputFormat},
     },
-    utils::error::{Error, Result},
+    utils::error::{Error, ErrorKind, Result},
 };
-use std::io::{self, Write};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
 
 /// Inspect ownership and tags for a specific file
+///
+/// When `file_path` is a directory, the owners and tags of the files below it
+/// are shown instead.
 pub fn run(
     file_path: &std::path::Path, repo: Option<&std::path::Path>, format: &OutputFormat,
     cache_file: Option<&std::path::Path>,
@@ -19,32 +25,26 @@ pub fn run(
     let cache = sync_cache(repo, cache_file)?;
 
     // Normalize the file path to be relative to the repo
-    let normalized_file_path = if file_path.is_absolute() {
-        file_path
-            .strip_prefix(repo)
-            .map_err(|_| {
-                Error::new(&format!(
-                    "File {} is not within repository {}",
-                    file_path.display(),
-                    repo.display()
-                ))
-            })?
-            .to_path_buf()
-    } else {
-        file_path.to_path_buf()
-    };
+    let relative_path = relative_to_repo(file_path, repo)?;
+
+    let full_path = repo.join(&relative_path);
+    if full_path.is_dir() {
+        return inspect_directory(&cache, repo, &full_path, &relative_path, format);
+    }
 
     // Find the file in the cache
-    let file_entry = cache
-        .files
-        .iter()
-        .find(|file| file.path == normalized_file_path)
-        .ok_or_else(|| {
-            Error::new(&format!(
-                "File {} not found in cache",
-                normalized_file_path.display()
-            ))
-        })?;
+    let file_entry = find_file(&cache, repo, &relative_path).ok_or_else(|| {
+        Error::with_kind(
+            ErrorKind::Validation,
+            &format!("File {} not found in cache", relative_path.display()),
+        )
+    })?;
+    let normalized_file_path = without_cur_dir(
+        file_entry
+            .path
+            .strip_prefix(repo)
+            .unwrap_or(&file_entry.path),
+    );
 
     // Find the CODEOWNERS entries that match this file
     let matching_entries: Vec<&CodeownersEntry> = cache
@@ -86,59 +86,65 @@ pub fn run(
                 "line_number": entry.line_number,
                 "pattern": entry.pattern,
                 "owners": entry.owners,
-                "tags": entry.tags.iter().map(|t| &t.0).collect::<Vec<_>>()
+                "tags": entry.tags.iter().map(|t| &t.0).collect::<Vec<_>>(),
+                "comment": entry.comment
             })
         }).collect::<Vec<_>>()
     });
 
     // Output the inspection result in the requested format
+    let mut stdout = stdout_writer();
     match format {
         OutputFormat::Text => {
-            println!(
+            writeln!(
+                stdout,
                 "==============================================================================="
-            );
-            println!(" File: {}", normalized_file_path.display());
-            println!(
+            )?;
+            writeln!(stdout, " File: {}", normalized_file_path.display())?;
+            writeln!(
+                stdout,
                 "==============================================================================="
-            );
-            println!("\nOwners:");
+            )?;
+            writeln!(stdout, "\nOwners:")?;
             if file_entry.owners.is_empty() {
-                println!("  (no owners)");
+                writeln!(stdout, "  (no owners)")?;
             } else {
                 for owner in &file_entry.owners {
-                    println!("  - {}", owner.identifier);
+                    writeln!(stdout, "  - {}", owner.identifier)?;
                 }
             }
 
-            println!("\nTags:");
+            writeln!(stdout, "\nTags:")?;
             if file_entry.tags.is_empty() {
-                println!("  (no tags)");
+                writeln!(stdout, "  (no tags)")?;
             } else {
                 for tag in &file_entry.tags {
-                    println!("  - {}", tag.0);
+                    writeln!(stdout, "  - {}", tag.0)?;
                 }
             }
 
-            println!("\nMatching CODEOWNERS Rules:");
+            writeln!(stdout, "\nMatching CODEOWNERS Rules:")?;
             if matching_entries.is_empty() {
-                println!("  (no explicit rules)");
+                writeln!(stdout, "  (no explicit rules)")?;
             } else {
                 for entry in matching_entries {
-                    println!(
+                    writeln!(
+                        stdout,
                         "\n  From {}:{}",
                         entry.source_file.display(),
                         entry.line_number
-                    );
-                    println!("    Pattern: {}", entry.pattern);
+                    )?;
+                    writeln!(stdout, "    Pattern: {}", entry.pattern)?;
                     let owners_str = entry
                         .owners
                         .iter()
                         .map(|o| o.identifier.as_str())
                         .collect::<Vec<_>>()
                         .join(", ");
-                    println!("    Owners:  {}", owners_str);
+                    writeln!(stdout, "    Owners:  {}", owners_str)?;
                     if !entry.tags.is_empty() {
-                        println!(
+                        writeln!(
+                            stdout,
                             "    Tags:    {}",
                             entry
                                 .tags
@@ -146,18 +152,193 @@ pub fn run(
                                 .map(|t| t.0.as_str())
                                 .collect::<Vec<_>>()
                                 .join(", ")
-                        );
+                        )?;
+                    }
+                    if let Some(comment) = &entry.comment {
+                        writeln!(stdout, "    Comment: {}", comment)?;
                     }
                 }
             }
-            println!();
+            writeln!(stdout)?;
         }
         OutputFormat::Json => {
-            println!(
+            writeln!(
+                stdout,
                 "{}",
                 serde_json::to_string_pretty(&inspection_result)
                     .map_err(|e| Error::new(&format!("JSON serialization error: {}", e)))?
-            );
+            )?;
+        }
+        OutputFormat::Dot => {
+            return Err(Error::with_kind(
+                ErrorKind::Validation,
+                "Inspection result is not available as dot",
+            ));
+        }
+        OutputFormat::Bincode => {
+            let encoded =
+                bincode::serde::encode_to_vec(&inspection_result, bincode::config::standard())
+                    .map_err(|e| Error::new(&format!("Serialization error: {}", e)))?;
+
+            // Write raw binary bytes to stdout
+            stdout
+                .write_all(&encoded)
+                .map_err(|e| Error::new(&format!("IO error: {}", e)))?;
+        }
+        OutputFormat::Html => {
+            return Err(Error::with_kind(
+                ErrorKind::Validation,
+                "HTML output is not supported by inspect; use text, json or bincode",
+            ));
+        }
+    }
+
+    stdout.finish()
+}
+
+/// Normalize a path given on the command line to be relative to `repo`
+///
+/// Backslashes are treated as separators and `.` components are dropped. An
+/// absolute path must be within `repo`, compared as given and canonicalized.
+fn relative_to_repo(file_path: &Path, repo: &Path) -> Result<PathBuf> {
+    let file_path = PathBuf::from(file_path.to_string_lossy().replace('\\', "/"));
+    if !file_path.is_absolute() {
+        return Ok(without_cur_dir(&file_path));
+    }
+
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let canonical_repo = canonical(repo);
+    file_path
+        .strip_prefix(repo)
+        .or_else(|_| file_path.strip_prefix(&canonical_repo))
+        .map(without_cur_dir)
+        .or_else(|_| {
+            canonical(&file_path)
+                .strip_prefix(&canonical_repo)
+                .map(without_cur_dir)
+        })
+        .map_err(|_| {
+            Error::with_kind(
+                ErrorKind::Validation,
+                &format!(
+                    "File {} is not within repository {}",
+                    file_path.display(),
+                    repo.display()
+                ),
+            )
+        })
+}
+
+/// `path` without its `.` components
+fn without_cur_dir(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
+
+/// Cached entry for `relative_path` in `repo`
+///
+/// Without an exact match, falls back to the cached files whose path ends with
+/// `relative_path`, warning and taking the first when there are several.
+fn find_file<'a>(
+    cache: &'a CodeownersCache, repo: &Path, relative_path: &Path,
+) -> Option<&'a FileEntry> {
+    if let Some(file) = cache.file(&repo.join(relative_path)) {
+        return Some(file);
+    }
+    if relative_path.as_os_str().is_empty() {
+        return None;
+    }
+
+    let candidates: Vec<&FileEntry> = cache
+        .files
+        .iter()
+        .filter(|file| file.path.ends_with(relative_path))
+        .collect();
+    if candidates.len() > 1 {
+        let paths: Vec<String> = candidates
+            .iter()
+            .map(|file| file.path.display().to_string())
+            .collect();
+        eprintln!(
+            "Warning: {} matches {} files, showing the first: {}",
+            relative_path.display(),
+            candidates.len(),
+            paths.join(", ")
+        );
+    }
+    candidates.first().copied()
+}
+
+/// Inspect the aggregated ownership of the directory `full_path`, shown as `display_path`
+fn inspect_directory(
+    cache: &CodeownersCache, repo: &Path, full_path: &Path, display_path: &Path,
+    format: &OutputFormat,
+) -> Result<()> {
+    let matchers = cache.entry_matchers(repo);
+    let (owners, tags) = resolve_directory(full_path, &cache.files, &matchers)?;
+    let file_count = cache
+        .files
+        .iter()
+        .filter(|file| file.path.starts_with(full_path))
+        .count();
+
+    let inspection_result = serde_json::json!({
+        "directory": display_path.to_string_lossy(),
+        "file_count": file_count,
+        "owners": owners,
+        "tags": tags.iter().map(|t| &t.0).collect::<Vec<_>>(),
+    });
+
+    let mut stdout = stdout_writer();
+    match format {
+        OutputFormat::Text => {
+            writeln!(
+                stdout,
+                "==============================================================================="
+            )?;
+            writeln!(
+                stdout,
+                " Directory: {} ({} files)",
+                display_path.display(),
+                file_count
+            )?;
+            writeln!(
+                stdout,
+                "==============================================================================="
+            )?;
+            writeln!(stdout, "\nOwners (most files first):")?;
+            if owners.is_empty() {
+                writeln!(stdout, "  (no owners)")?;
+            } else {
+                for owner in &owners {
+                    writeln!(stdout, "  - {}", owner.identifier)?;
+                }
+            }
+
+            writeln!(stdout, "\nTags:")?;
+            if tags.is_empty() {
+                writeln!(stdout, "  (no tags)")?;
+            } else {
+                for tag in &tags {
+                    writeln!(stdout, "  - {}", tag.0)?;
+                }
+            }
+            writeln!(stdout)?;
+        }
+        OutputFormat::Json => {
+            writeln!(
+                stdout,
+                "{}",
+                serde_json::to_string_pretty(&inspection_result)
+                    .map_err(|e| Error::new(&format!("JSON serialization error: {}", e)))?
+            )?;
+        }
+        OutputFormat::Dot => {
+            return Err(Error::with_kind(
+                ErrorKind::Validation,
+                "Inspection result is not available as dot",
+            ));
         }
         OutputFormat::Bincode => {
             let encoded =
@@ -165,11 +346,104 @@ pub fn run(
                     .map_err(|e| Error::new(&format!("Serialization error: {}", e)))?;
 
             // Write raw binary bytes to stdout
-            io::stdout()
+            stdout
                 .write_all(&encoded)
                 .map_err(|e| Error::new(&format!("IO error: {}", e)))?;
         }
+        OutputFormat::Html => {
+            return Err(Error::with_kind(
+                ErrorKind::Validation,
+                "HTML output is not supported by inspect; use text, json or bincode",
+            ));
+        }
+    }
+
+    stdout.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{OwnerMerge, PatternAnchor};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn cache_with_files(paths: &[PathBuf]) -> CodeownersCache {
+        CodeownersCache {
+            hash: [0; 32],
+            entries: vec![],
+            files: paths
+                .iter()
+                .map(|path| FileEntry {
+                    path: path.clone(),
+                    owners: vec![],
+                    tags: vec![],
+                    inline_override: false,
+                })
+                .collect(),
+            owners_map: HashMap::new(),
+            tags_map: HashMap::new(),
+            case_insensitive: false,
+            owner_merge: OwnerMerge::Winner,
+            anchor: PatternAnchor::FileDir,
+            follow_links: false,
+            dedupe_paths: false,
+            owner_aliases: HashMap::new(),
+            head_commit: None,
+            identity_map: HashMap::new(),
+        }
     }
 
-    Ok(())
+    #[test]
+    fn test_find_file_normalizes_path_forms() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        let cached = repo.join("src").join("x.rs");
+        let cache = cache_with_files(&[cached.clone(), repo.join("tests").join("y.rs")]);
+
+        for input in [
+            PathBuf::from("./src/x.rs"),
+            PathBuf::from("src/x.rs"),
+            PathBuf::from("src\\x.rs"),
+            repo.join("src/x.rs"),
+        ] {
+            let relative = relative_to_repo(&input, repo)?;
+            assert_eq!(
+                relative,
+                Path::new("src/x.rs"),
+                "input: {}",
+                input.display()
+            );
+            let found = find_file(&cache, repo, &relative).map(|file| &file.path);
+            assert_eq!(found, Some(&cached), "input: {}", input.display());
+        }
+
+        // Cached as `./src/x.rs` when the repository is the current directory
+        let cache = cache_with_files(&[PathBuf::from("./src/x.rs")]);
+        let relative = relative_to_repo(Path::new("src/x.rs"), Path::new("."))?;
+        assert!(find_file(&cache, Path::new("."), &relative).is_some());
+
+        assert!(relative_to_repo(Path::new("/elsewhere/x.rs"), repo).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_file_falls_back_to_suffix_match() {
+        let cache = cache_with_files(&[
+            PathBuf::from("/repo/api/src/lib.rs"),
+            PathBuf::from("/repo/web/src/main.ts"),
+            PathBuf::from("/repo/web/src/lib.rs"),
+        ]);
+        let repo = Path::new("/repo");
+
+        let found = find_file(&cache, repo, Path::new("main.ts")).map(|file| &file.path);
+        assert_eq!(found, Some(&PathBuf::from("/repo/web/src/main.ts")));
+
+        // Ambiguous: the first cached match wins
+        let found = find_file(&cache, repo, Path::new("src/lib.rs")).map(|file| &file.path);
+        assert_eq!(found, Some(&PathBuf::from("/repo/api/src/lib.rs")));
+
+        assert!(find_file(&cache, repo, Path::new("missing.rs")).is_none());
+    }
 }
@@ -1,13 +1,27 @@
 use crate::{
     core::{
         cache::sync_cache,
-        display::{truncate_path, truncate_string},
-        types::OutputFormat,
+        common::last_commit_authors,
+        display::{
+            encode_bincode_output, file_url, hyperlink, output_writer, remove_header,
+            truncate_path, truncate_string, write_json_pretty, JsonEnvelope,
+        },
+        html,
+        inline_parser::detect_inline_codeowners,
+        resolver::find_matching_entries_for_file,
+        types::{
+            BincodePayload, CodeownersEntry, CodeownersEntryMatcher, FileEntry, OutputFormat,
+            Owner, OwnerType,
+        },
     },
-    utils::error::{Error, Result},
+    utils::error::{Error, ErrorKind, Result},
 };
-use std::io::{self, Write};
-use tabled::{Table, Tabled};
+use ignore::overrides::{Override, OverrideBuilder};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use tabled::{grid::records::vec_records::Cell, Table, Tabled};
 
 #[derive(Tabled)]
 struct FileDisplay {
@@ -19,11 +33,347 @@ struct FileDisplay {
     tags: String,
 }
 
+/// How multiple `--owners` values are combined
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OwnerMatch {
+    /// File has at least one of the owners
+    #[default]
+    Any,
+    /// File has every one of the owners
+    All,
+}
+
+/// How owners are rendered in the text table
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OwnerDisplay {
+    /// Raw identifiers, e.g. `@org/team`
+    #[default]
+    Identifier,
+    /// Identifiers grouped by owner type, e.g. `Team: @org/team; User: @alice`
+    Type,
+    /// Identifiers followed by their type, e.g. `@org/team (Team)`
+    Both,
+}
+
+/// How files are laid out in JSON output
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum JsonShape {
+    /// An array of files, each with its `path`
+    #[default]
+    Array,
+    /// An object keyed by path, each value holding the file's other fields
+    Map,
+}
+
+/// Output options for `list-files`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ListOptions<'a> {
+    /// Include the rule that decided each file's owners (JSON only)
+    pub with_rule: bool,
+    /// How owners are rendered in the text table
+    pub owner_display: OwnerDisplay,
+    /// Print only the number of matching files
+    pub count_only: bool,
+    /// Print `path=...;owners=...;tags=...` lines instead of the text table
+    pub parseable: bool,
+    /// Link paths in the text table even if the terminal isn't known to support it
+    pub hyperlinks: bool,
+    /// Wrap JSON output in `{ "query", "total", "results" }`
+    pub json_envelope: bool,
+    /// Leave out the header row and the total line of the text table
+    pub no_header: bool,
+    /// Lay out JSON output as an array of files or an object keyed by path
+    pub json_shape: JsonShape,
+    /// Write the output to this file instead of stdout
+    pub output: Option<&'a Path>,
+    /// Write the byte offsets of the JSON records of each owner and tag to this file
+    pub index_out: Option<&'a Path>,
+}
+
+/// Filter criteria for `list-files`
+///
+/// List values are comma-separated and match by substring. A tag value with a
+/// trailing `/` (e.g. `area/`) matches every tag in that namespace instead.
+#[derive(Debug, Default, Clone)]
+pub struct FileFilter<'a> {
+    /// Only include files carrying any of these tags
+    pub tags: Option<&'a str>,
+    /// Drop files carrying any of these tags
+    pub exclude_tags: Option<&'a str>,
+    /// Only include files owned by these owners
+    pub owners: Option<&'a str>,
+    /// Whether a file needs any or all of `owners`
+    pub owner_match: OwnerMatch,
+    /// Only include files with an owner whose identifier matches
+    pub owner_regex: Option<&'a Regex>,
+    /// Only include files with a tag that matches
+    pub tag_regex: Option<&'a Regex>,
+    /// Only include unowned files
+    pub unowned: bool,
+    /// Include unowned/untagged files
+    pub show_all: bool,
+    /// Only include files outside the directory of every CODEOWNERS file
+    ///
+    /// Applied by [`run`] against the cached rules, not by [`FileFilter::matches`].
+    pub no_governing_codeowners: bool,
+    /// Only include files whose only owner is an individual user, with no team as backup
+    ///
+    /// Applied by [`run`] against the cached owners, not by [`FileFilter::matches`].
+    pub spof: bool,
+    /// With `spof`, only include files whose user owns at least this many files
+    pub min_impact: Option<usize>,
+    /// Only include paths matching an `--only` glob and no `--exclude` glob
+    pub paths: Option<&'a PathGlobs>,
+    /// Only include files whose last commit author contains this, by name or email
+    ///
+    /// Applied by [`run`] against the git history, not by [`FileFilter::matches`].
+    pub modified_by: Option<&'a str>,
+}
+
+/// Compiled `--only`/`--exclude` path globs for `list-files`
+///
+/// Globs use gitignore syntax relative to the repository, so `web/` covers
+/// every file below `web`. A path must match at least one `only` glob, if there
+/// are any, and an `exclude` glob wins over it.
+#[derive(Debug, Clone)]
+pub struct PathGlobs {
+    only: Vec<String>,
+    exclude: Vec<String>,
+    root: PathBuf,
+    only_matcher: Option<Override>,
+    exclude_matcher: Option<Override>,
+}
+
+impl PathGlobs {
+    /// Compile the globs against `repo`, or `None` if there are none
+    pub fn new(repo: &Path, only: &[String], exclude: &[String]) -> Result<Option<Self>> {
+        if only.is_empty() && exclude.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(PathGlobs {
+            only: only.to_vec(),
+            exclude: exclude.to_vec(),
+            root: repo.to_path_buf(),
+            only_matcher: build_override(repo, only, "")?,
+            // Overrides whitelist by default, so excludes become ignore globs
+            exclude_matcher: build_override(repo, exclude, "!")?,
+        }))
+    }
+
+    /// Whether `path` passes the globs
+    pub fn matches(&self, path: &Path) -> bool {
+        // Check the file itself, then each directory containing it
+        let mut candidates = std::iter::once((path, false)).chain(
+            path.ancestors()
+                .skip(1)
+                .take_while(|dir| *dir != self.root && dir.starts_with(&self.root))
+                .map(|dir| (dir, true)),
+        );
+        let included = self.only_matcher.as_ref().is_none_or(|matcher| {
+            candidates
+                .clone()
+                .any(|(path, is_dir)| matcher.matched(path, is_dir).is_whitelist())
+        });
+        let excluded = self.exclude_matcher.as_ref().is_some_and(|matcher| {
+            candidates.any(|(path, is_dir)| matcher.matched(path, is_dir).is_ignore())
+        });
+        included && !excluded
+    }
+}
+
+/// Build an override from `globs`, each prefixed with `prefix`, or `None` if empty
+fn build_override(root: &Path, globs: &[String], prefix: &str) -> Result<Option<Override>> {
+    if globs.is_empty() {
+        return Ok(None);
+    }
+
+    let invalid = |glob: &str, e: ignore::Error| {
+        Error::with_kind(
+            ErrorKind::Validation,
+            &format!("Invalid glob '{}': {}", glob, e),
+        )
+    };
+    let mut builder = OverrideBuilder::new(root);
+    for glob in globs {
+        builder
+            .add(&format!("{}{}", prefix, glob))
+            .map_err(|e| invalid(glob, e))?;
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| Error::with_kind(ErrorKind::Validation, &format!("Invalid path globs: {}", e)))
+}
+
+impl FileFilter<'_> {
+    /// The active filters as a JSON object, for `--json-envelope` output
+    pub fn query_json(&self) -> serde_json::Value {
+        let mut query = serde_json::Map::new();
+        let lists = [
+            ("tags", self.tags),
+            ("exclude_tags", self.exclude_tags),
+            ("owners", self.owners),
+            ("modified_by", self.modified_by),
+        ];
+        for (key, value) in lists {
+            if let Some(value) = value {
+                query.insert(key.to_string(), value.into());
+            }
+        }
+        if self.owners.is_some() {
+            let owner_match = match self.owner_match {
+                OwnerMatch::Any => "any",
+                OwnerMatch::All => "all",
+            };
+            query.insert("owner_match".to_string(), owner_match.into());
+        }
+        let regexes = [
+            ("owner_regex", self.owner_regex),
+            ("tag_regex", self.tag_regex),
+        ];
+        for (key, regex) in regexes {
+            if let Some(regex) = regex {
+                query.insert(key.to_string(), regex.as_str().into());
+            }
+        }
+        let flags = [
+            ("unowned", self.unowned),
+            ("show_all", self.show_all),
+            ("no_governing_codeowners", self.no_governing_codeowners),
+            ("spof", self.spof),
+        ];
+        for (key, value) in flags {
+            if value {
+                query.insert(key.to_string(), true.into());
+            }
+        }
+        if let Some(min_impact) = self.min_impact {
+            query.insert("min_impact".to_string(), min_impact.into());
+        }
+        if let Some(paths) = self.paths {
+            for (key, globs) in [("only", &paths.only), ("exclude", &paths.exclude)] {
+                if !globs.is_empty() {
+                    query.insert(key.to_string(), globs.as_slice().into());
+                }
+            }
+        }
+        serde_json::Value::Object(query)
+    }
+
+    /// Returns true if the file passes all filter criteria
+    pub fn matches(&self, file: &FileEntry) -> bool {
+        let has_any_tag = |tag_filter: &str| {
+            let tag_patterns: Vec<&str> = tag_filter.split(',').collect();
+            file.tags.iter().any(|tag| {
+                tag_patterns.iter().any(|pattern| {
+                    if pattern.ends_with('/') {
+                        tag.matches_prefix(pattern)
+                    } else {
+                        tag.0.contains(pattern)
+                    }
+                })
+            })
+        };
+
+        let passes_owner_filter = match self.owners {
+            Some(owner_filter) => {
+                let has_owner = |pattern: &&str| {
+                    file.owners
+                        .iter()
+                        .any(|owner| owner.identifier.contains(pattern))
+                };
+                let mut owner_patterns = owner_filter.split(',');
+                match self.owner_match {
+                    OwnerMatch::Any => owner_patterns.any(|pattern| has_owner(&pattern)),
+                    OwnerMatch::All => owner_patterns.all(|pattern| has_owner(&pattern)),
+                }
+            }
+            None => true,
+        };
+
+        let passes_owner_regex = self.owner_regex.is_none_or(|re| {
+            file.owners
+                .iter()
+                .any(|owner| re.is_match(&owner.identifier))
+        });
+
+        let passes_tag_filter = self.tags.map(has_any_tag).unwrap_or(true);
+
+        let passes_tag_regex = self
+            .tag_regex
+            .is_none_or(|re| file.tags.iter().any(|tag| re.is_match(&tag.0)));
+
+        let passes_exclude_tag_filter = !self.exclude_tags.map(has_any_tag).unwrap_or(false);
+
+        let passes_path_globs = self.paths.is_none_or(|paths| paths.matches(&file.path));
+
+        let passes_unowned_filter = if self.unowned {
+            file.is_effectively_unowned()
+        } else {
+            true
+        };
+
+        //  exclude unowned/untagged files unless show_all or unowned is specified
+        let passes_ownership_requirement =
+            if self.show_all || self.unowned || self.no_governing_codeowners {
+                true
+            } else {
+                !file.owners.is_empty() || !file.tags.is_empty()
+            };
+
+        passes_owner_filter
+            && passes_owner_regex
+            && passes_tag_filter
+            && passes_tag_regex
+            && passes_exclude_tag_filter
+            && passes_path_globs
+            && passes_unowned_filter
+            && passes_ownership_requirement
+    }
+}
+
 /// Find and list files with their owners based on filter criteria
+///
+/// With `with_rule`, JSON output includes the rule that decided each file's
+/// owners. `owner_display` only affects text output. With `count_only`, only
+/// the number of matching files is printed. With `no_header`, the text table
+/// has no header row and no total line.
 pub fn run(
-    repo: Option<&std::path::Path>, tags: Option<&str>, owners: Option<&str>, unowned: bool,
-    show_all: bool, format: &OutputFormat, cache_file: Option<&std::path::Path>,
+    repo: Option<&std::path::Path>, filter: &FileFilter, format: &OutputFormat,
+    cache_file: Option<&std::path::Path>, options: &ListOptions,
 ) -> Result<()> {
+    let ListOptions {
+        with_rule,
+        owner_display,
+        count_only,
+        parseable,
+        hyperlinks,
+        json_envelope,
+        no_header,
+        json_shape,
+        output,
+        index_out,
+    } = *options;
+
+    if parseable && *format != OutputFormat::Text {
+        return Err(Error::with_kind(
+            ErrorKind::Validation,
+            "--parseable is only available with text output",
+        ));
+    }
+    let plain_json_array = *format == OutputFormat::Json
+        && !with_rule
+        && !json_envelope
+        && !count_only
+        && json_shape == JsonShape::Array;
+    if index_out.is_some() && !plain_json_array {
+        return Err(Error::with_kind(
+            ErrorKind::Validation,
+            "--index-out is only available with --format json as a plain array of files",
+        ));
+    }
+
     // Repository path
     let repo = repo.unwrap_or_else(|| std::path::Path::new("."));
 
@@ -31,123 +381,1184 @@ pub fn run(
     let cache = sync_cache(repo, cache_file)?;
 
     // Filter files based on criteria
+    let governing_dirs = codeowners_dirs(&cache.entries);
     let filtered_files = cache
         .files
         .iter()
+        .filter(|file| filter.matches(file))
+        .filter(|file| !filter.no_governing_codeowners || !is_governed(&file.path, &governing_dirs))
         .filter(|file| {
-            // Check if we should include this file based on filters
-            let passes_owner_filter = match owners {
-                Some(owner_filter) => {
-                    let owner_patterns: Vec<&str> = owner_filter.split(',').collect();
-                    file.owners.iter().any(|owner| {
-                        owner_patterns
-                            .iter()
-                            .any(|pattern| owner.identifier.contains(pattern))
+            !filter.spof
+                || is_single_point_of_failure(
+                    file,
+                    &cache.owners_map,
+                    filter.min_impact.unwrap_or(0),
+                )
+        })
+        .collect::<Vec<_>>();
+    let filtered_files = match filter.modified_by {
+        Some(author) => {
+            log::warn!("--modified-by walks the git history, which is slow in large repositories");
+            files_modified_by(repo, filtered_files, author)?
+        }
+        None => filtered_files,
+    };
+
+    // Output the filtered files in the requested format
+    let mut stdout = output_writer(output)?;
+    if count_only {
+        writeln!(stdout, "{}", render_count(filtered_files.len(), format))?;
+        return stdout.finish();
+    }
+    match format {
+        OutputFormat::Text if parseable => {
+            for file in &filtered_files {
+                writeln!(stdout, "{}", parseable_line(file))?;
+            }
+        }
+        OutputFormat::Text => {
+            // Get terminal width, fallback to 80 if unavailable
+            let terminal_width =
+                if let Some((terminal_size::Width(w), _)) = terminal_size::terminal_size() {
+                    w as usize
+                } else {
+                    80
+                };
+
+            let mut table = build_table(&filtered_files, terminal_width, owner_display);
+            if no_header {
+                remove_header(&mut table);
+            }
+            let table = if hyperlinks_enabled(
+                hyperlinks,
+                output.is_none() && io::stdout().is_terminal(),
+                std::env::var_os("NO_COLOR").is_some(),
+                std::env::var("TERM_PROGRAM").ok().as_deref(),
+            ) {
+                link_table_paths(&table, &filtered_files)
+            } else {
+                table.to_string()
+            };
+
+            writeln!(stdout, "{}", table)?;
+            if !no_header {
+                writeln!(stdout, "Total: {} files", filtered_files.len())?;
+            }
+        }
+        OutputFormat::Json if with_rule || json_shape == JsonShape::Map => {
+            let files = if with_rule {
+                let matchers = cache.entry_matchers(repo);
+                files_with_rules(&filtered_files, &matchers)?
+            } else {
+                filtered_files
+                    .iter()
+                    .map(|file| {
+                        serde_json::to_value(file)
+                            .map_err(|e| Error::new(&format!("Serialization error: {}", e)))
                     })
-                }
-                None => true,
+                    .collect::<Result<_>>()?
+            };
+            let files = match json_shape {
+                JsonShape::Array => serde_json::Value::Array(files),
+                JsonShape::Map => serde_json::Value::Object(keyed_by_path(files)),
+            };
+            if json_envelope {
+                let envelope = JsonEnvelope {
+                    query: filter.query_json(),
+                    total: filtered_files.len(),
+                    results: &files,
+                };
+                write_json_pretty(&mut stdout, &envelope)?;
+            } else {
+                write_json_pretty(&mut stdout, &files)?;
+            }
+        }
+        OutputFormat::Json if json_envelope => {
+            let envelope = JsonEnvelope {
+                query: filter.query_json(),
+                total: filtered_files.len(),
+                results: &filtered_files,
             };
+            write_json_pretty(&mut stdout, &envelope)?;
+        }
+        OutputFormat::Json => match index_out {
+            Some(index_out) => {
+                let index = write_indexed_json(&mut stdout, &filtered_files)?;
+                write_json_pretty(
+                    io::BufWriter::new(std::fs::File::create(index_out)?),
+                    &index,
+                )?;
+            }
+            // Stream straight to stdout instead of building one large string
+            None => write_json_pretty(&mut stdout, &filtered_files)?,
+        },
+        OutputFormat::Dot => {
+            return Err(Error::with_kind(
+                ErrorKind::Validation,
+                "File list is not available as dot",
+            ));
+        }
+        OutputFormat::Bincode => {
+            let encoded = encode_bincode_output(BincodePayload::Files, &filtered_files)?;
 
-            let passes_tag_filter = match tags {
-                Some(tag_filter) => {
-                    let tag_patterns: Vec<&str> = tag_filter.split(',').collect();
-                    file.tags
-                        .iter()
-                        .any(|tag| tag_patterns.iter().any(|pattern| tag.0.contains(pattern)))
+            // Write framed binary bytes to stdout
+            stdout
+                .write_all(&encoded)
+                .map_err(|e| Error::new(&format!("IO error: {}", e)))?;
+        }
+        OutputFormat::Html => {
+            let rows: Vec<Vec<String>> = filtered_files
+                .iter()
+                .map(|file| {
+                    vec![
+                        file.path.to_string_lossy().to_string(),
+                        format_owners(&file.owners, owner_display),
+                        file.tags
+                            .iter()
+                            .map(|t| t.0.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    ]
+                })
+                .collect();
+            write!(
+                stdout,
+                "{}",
+                html::render_table("Files", &["File Path", "Owners", "Tags"], &rows)
+            )?;
+        }
+    }
+
+    stdout.finish()
+}
+
+/// Write `files` as a pretty-printed JSON array and index its records
+///
+/// The bytes match [`write_json_pretty`]. The index maps `owner:<identifier>`
+/// and `tag:<name>` to the byte offset of each record with that owner or tag,
+/// pointing at the record's opening `{`, so a record can be read without
+/// parsing the whole array.
+fn write_indexed_json<W: Write>(
+    mut writer: W, files: &[&FileEntry],
+) -> Result<BTreeMap<String, Vec<u64>>> {
+    let mut index: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+    if files.is_empty() {
+        writer.write_all(b"[]\n")?;
+        return Ok(index);
+    }
+
+    writer.write_all(b"[\n")?;
+    let mut offset = 2;
+    for (position, file) in files.iter().enumerate() {
+        let record = serde_json::to_string_pretty(file)
+            .map_err(|e| Error::new(&format!("Serialization error: {}", e)))?
+            // Nested one level deeper inside the array
+            .replace('\n', "\n  ");
+        let separator = if position + 1 < files.len() {
+            ",\n"
+        } else {
+            "\n"
+        };
+
+        let start = offset + 2;
+        for owner in &file.owners {
+            index
+                .entry(format!("owner:{}", owner.identifier))
+                .or_default()
+                .push(start);
+        }
+        for tag in &file.tags {
+            index
+                .entry(format!("tag:{}", tag.0))
+                .or_default()
+                .push(start);
+        }
+
+        write!(writer, "  {}{}", record, separator)?;
+        offset = start + (record.len() + separator.len()) as u64;
+    }
+    writer.write_all(b"]\n")?;
+    writer.flush()?;
+
+    Ok(index)
+}
+
+/// Files serialized to JSON, as an object keyed by their `path` field
+///
+/// Each value keeps the file's other fields. Cached paths are unique, so no
+/// file replaces another.
+fn keyed_by_path(files: Vec<serde_json::Value>) -> serde_json::Map<String, serde_json::Value> {
+    files
+        .into_iter()
+        .filter_map(|mut file| {
+            let path = file.as_object_mut()?.remove("path")?;
+            Some((path.as_str()?.to_string(), file))
+        })
+        .collect()
+}
+
+/// Directories of the CODEOWNERS files that declare at least one rule
+fn codeowners_dirs(entries: &[CodeownersEntry]) -> HashSet<&Path> {
+    entries
+        .iter()
+        .filter_map(|entry| entry.source_file.parent())
+        .collect()
+}
+
+/// Whether any CODEOWNERS file in `codeowners_dirs` can apply to `path`
+///
+/// A file that is not governed has no rules covering its area at all, as
+/// opposed to an unowned file that is covered by rules none of which match.
+fn is_governed(path: &Path, codeowners_dirs: &HashSet<&Path>) -> bool {
+    path.ancestors()
+        .skip(1)
+        .any(|dir| codeowners_dirs.contains(dir))
+}
+
+/// Keep the files whose last commit author contains `author`
+///
+/// Each file's author is looked up once, in a single walk of the history.
+fn files_modified_by<'a>(
+    repo: &Path, files: Vec<&'a FileEntry>, author: &str,
+) -> Result<Vec<&'a FileEntry>> {
+    let paths: Vec<&Path> = files.iter().map(|file| file.path.as_path()).collect();
+    let authors = last_commit_authors(repo, &paths)?;
+    Ok(files
+        .into_iter()
+        .filter(|file| {
+            authors
+                .get(&file.path)
+                .is_some_and(|last_author| last_author.contains(author))
+        })
+        .collect())
+}
+
+/// Whether a single user, owning at least `min_impact` files, is the file's only owner
+fn is_single_point_of_failure(
+    file: &FileEntry, owners_map: &HashMap<Owner, Vec<PathBuf>>, min_impact: usize,
+) -> bool {
+    match file.owners.as_slice() {
+        [owner] if owner.owner_type == OwnerType::User => {
+            owners_map.get(owner).map_or(0, Vec::len) >= min_impact
+        }
+        _ => false,
+    }
+}
+
+/// Render the number of matching files
+///
+/// JSON output is `{"count": N}`; every other format prints the bare integer.
+fn render_count(count: usize, format: &OutputFormat) -> String {
+    match format {
+        OutputFormat::Json => serde_json::json!({ "count": count }).to_string(),
+        _ => count.to_string(),
+    }
+}
+
+/// Compile a `--owner-regex`/`--tag-regex` value, reporting invalid ones as validation errors
+pub fn compile_regex(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).map_err(|e| {
+        Error::with_kind(
+            ErrorKind::Validation,
+            &format!("Invalid regular expression '{}': {}", pattern, e),
+        )
+    })
+}
+
+/// Render a file as a `path=...;owners=...;tags=...` line
+///
+/// Owners and tags are comma-separated and every value is quoted for the
+/// shell, so the line can be passed to `eval`.
+fn parseable_line(file: &FileEntry) -> String {
+    let owners: Vec<&str> = file.owners.iter().map(|o| o.identifier.as_str()).collect();
+    let tags: Vec<&str> = file.tags.iter().map(|t| t.0.as_str()).collect();
+    format!(
+        "path={};owners={};tags={}",
+        shell_quote(&file.path.to_string_lossy()),
+        shell_quote(&owners.join(",")),
+        shell_quote(&tags.join(","))
+    )
+}
+
+/// Single-quote `value` unless it only contains characters the shell leaves alone
+fn shell_quote(value: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "@%+=:,./_-".contains(c);
+    if value.chars().all(is_safe) {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// Serialize files to JSON, adding the rule that decided their owners
+///
+/// `rule` is `null` for files no rule matches. For inline `!!!CODEOWNERS`
+/// declarations it points at the file itself with a `null` pattern. A file
+/// that can't be read or matched gets a `null` rule, as when the cache is
+/// built, instead of failing the whole listing.
+fn files_with_rules(
+    files: &[&FileEntry], matchers: &[CodeownersEntryMatcher],
+) -> Result<Vec<serde_json::Value>> {
+    files
+        .iter()
+        .map(|file| {
+            let inline = detect_inline_codeowners(&file.path).unwrap_or(None);
+            let rule = if let Some(inline) = inline {
+                serde_json::json!({
+                    "source_file": inline.file_path.to_string_lossy(),
+                    "line_number": inline.line_number,
+                    "pattern": null
+                })
+            } else {
+                let matching = find_matching_entries_for_file(&file.path, matchers);
+                match matching.unwrap_or_default().first() {
+                    Some(entry) => serde_json::json!({
+                        "source_file": entry.source_file.to_string_lossy(),
+                        "line_number": entry.line_number,
+                        "pattern": entry.pattern
+                    }),
+                    None => serde_json::Value::Null,
                 }
-                None => true,
             };
 
-            let passes_unowned_filter = if unowned {
-                file.owners.is_empty()
+            let mut value = serde_json::to_value(file)
+                .map_err(|e| Error::new(&format!("Serialization error: {}", e)))?;
+            value["rule"] = rule;
+            Ok(value)
+        })
+        .collect()
+}
+
+/// Build the text table for a list of files
+///
+/// Columns are truncated by display width, so wide Unicode owners, tags and
+/// paths stay aligned with the table borders.
+fn build_table(files: &[&FileEntry], terminal_width: usize, owner_display: OwnerDisplay) -> Table {
+    let table_data: Vec<FileDisplay> = files
+        .iter()
+        .map(|file| {
+            let path_str = file.path.to_string_lossy().to_string();
+
+            let owners_str = if file.owners.is_empty() {
+                "None".to_string()
             } else {
-                true
+                format_owners(&file.owners, owner_display)
             };
 
-            //  exclude unowned/untagged files unless show_all or unowned is specified
-            let passes_ownership_requirement = if show_all || unowned {
-                true
+            let tags_str = if file.tags.is_empty() {
+                "None".to_string()
             } else {
-                !file.owners.is_empty() || !file.tags.is_empty()
+                file.tags
+                    .iter()
+                    .map(|t| t.0.clone())
+                    .collect::<Vec<_>>()
+                    .join(", ")
             };
 
-            passes_owner_filter
-                && passes_tag_filter
-                && passes_unowned_filter
-                && passes_ownership_requirement
+            FileDisplay {
+                path: truncate_path(&path_str, 60),
+                owners: truncate_string(&owners_str, 40),
+                tags: truncate_string(&tags_str, 30),
+            }
         })
-        .collect::<Vec<_>>();
+        .collect();
 
-    // Output the filtered files in the requested format
-    match format {
-        OutputFormat::Text => {
-            // Create table data
-            let table_data: Vec<FileDisplay> = filtered_files
+    let mut table = Table::new(table_data);
+    table
+        .with(tabled::settings::Style::modern())
+        .with(tabled::settings::Width::wrap(
+            terminal_width.saturating_sub(4),
+        ))
+        .with(tabled::settings::Padding::new(1, 1, 0, 0));
+
+    table
+}
+
+/// Terminals (by `TERM_PROGRAM`) known to support OSC 8 hyperlinks
+const HYPERLINK_TERMINALS: &[&str] = &["iTerm.app", "WezTerm", "vscode", "ghostty", "WarpTerminal"];
+
+/// Whether to link paths in the text table
+///
+/// Links are only written to a terminal and never when `NO_COLOR` is set;
+/// otherwise they are used when requested or when the terminal supports them.
+fn hyperlinks_enabled(
+    requested: bool, is_terminal: bool, no_color: bool, term_program: Option<&str>,
+) -> bool {
+    is_terminal
+        && !no_color
+        && (requested || term_program.is_some_and(|term| HYPERLINK_TERMINALS.contains(&term)))
+}
+
+/// Wrap each file's path cell in the rendered table in an OSC 8 link to the file
+///
+/// Links are added after rendering so the escapes don't count towards column
+/// widths. A path wrapped over several lines is left unlinked. Works on both
+/// the bordered table and one without header and borders.
+fn link_table_paths(table: &Table, files: &[&FileEntry]) -> String {
+    let rendered = table.to_string();
+    // A bordered table has a border or separator line above each row
+    let bordered = rendered.starts_with('┌');
+    let left_border = if bordered { "│" } else { "" };
+
+    // The file whose row starts at each line; files are the last rows, after any header
+    let records = table.get_records();
+    let first_file_row = records.len().saturating_sub(files.len());
+    let mut row_files = vec![None; rendered.lines().count()];
+    let mut line = 0;
+    for (row, cells) in records.iter().enumerate() {
+        if bordered {
+            line += 1;
+        }
+        if let Some(slot) = row_files.get_mut(line) {
+            *slot = row.checked_sub(first_file_row).map(|index| files[index]);
+        }
+        line += cells.iter().map(Cell::count_lines).max().unwrap_or(1);
+    }
+
+    let mut linked = String::with_capacity(rendered.len());
+    for (line, file) in rendered.lines().zip(row_files) {
+        let mut line = line.to_string();
+        if let Some(file) = file {
+            let display = truncate_path(&file.path.to_string_lossy(), 60);
+            let cell = format!("{} {} ", left_border, display);
+            if line.starts_with(&cell) {
+                line = format!(
+                    "{} {}{}",
+                    left_border,
+                    hyperlink(&display, &file_url(&file.path)),
+                    &line[cell.len() - 1..]
+                );
+            }
+        }
+        linked.push_str(&line);
+        linked.push('\n');
+    }
+    linked.pop();
+    linked
+}
+
+/// Render a file's owners for the text table
+fn format_owners(owners: &[Owner], owner_display: OwnerDisplay) -> String {
+    match owner_display {
+        OwnerDisplay::Identifier => owners
+            .iter()
+            .map(|o| o.identifier.clone())
+            .collect::<Vec<_>>()
+            .join(", "),
+        OwnerDisplay::Both => owners
+            .iter()
+            .map(|o| format!("{} ({})", o.identifier, o.owner_type))
+            .collect::<Vec<_>>()
+            .join(", "),
+        OwnerDisplay::Type => {
+            // Groups keep the order in which each type first appears
+            let mut groups: Vec<(String, Vec<&str>)> = Vec::new();
+            for owner in owners {
+                let owner_type = owner.owner_type.to_string();
+                match groups.iter_mut().find(|(t, _)| *t == owner_type) {
+                    Some((_, identifiers)) => identifiers.push(&owner.identifier),
+                    None => groups.push((owner_type, vec![&owner.identifier])),
+                }
+            }
+            groups
                 .iter()
-                .map(|file| {
-                    let path_str = file.path.to_string_lossy().to_string();
+                .map(|(owner_type, identifiers)| {
+                    format!("{}: {}", owner_type, identifiers.join(", "))
+                })
+                .collect::<Vec<_>>()
+                .join("; ")
+        }
+    }
+}
 
-                    let owners_str = if file.owners.is_empty() {
-                        "None".to_string()
-                    } else {
-                        file.owners
-                            .iter()
-                            .map(|o| o.identifier.clone())
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_codeowners;
+    use crate::core::types::{codeowners_entry_to_matcher, Owner, OwnerType, Tag};
+    use std::path::PathBuf;
+    use unicode_width::UnicodeWidthStr;
 
-                    let tags_str = if file.tags.is_empty() {
-                        "None".to_string()
-                    } else {
-                        file.tags
-                            .iter()
-                            .map(|t| t.0.clone())
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    };
+    fn create_tagged_file_entry(path: &str, tags: &[&str]) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from(path),
+            owners: vec![Owner {
+                identifier: "@core-team".to_string(),
+                owner_type: OwnerType::Team,
+            }],
+            tags: tags.iter().map(|t| Tag(t.to_string())).collect(),
+            inline_override: false,
+        }
+    }
 
-                    FileDisplay {
-                        path: truncate_path(&path_str, 60),
-                        owners: truncate_string(&owners_str, 40),
-                        tags: truncate_string(&tags_str, 30),
-                    }
+    #[test]
+    fn test_file_filter_tags_with_exclude_tags() {
+        let files = [
+            create_tagged_file_entry("src/core.rs", &["core"]),
+            create_tagged_file_entry("src/core_generated.rs", &["core", "generated"]),
+            create_tagged_file_entry("src/schema.rs", &["generated"]),
+            create_tagged_file_entry("docs/README.md", &["docs"]),
+        ];
+
+        let filter = FileFilter {
+            tags: Some("core"),
+            exclude_tags: Some("generated"),
+            ..Default::default()
+        };
+        let matched: Vec<_> = files
+            .iter()
+            .filter(|file| filter.matches(file))
+            .map(|file| file.path.clone())
+            .collect();
+        assert_eq!(matched, vec![PathBuf::from("src/core.rs")]);
+
+        // Excluding on its own keeps everything without the tag
+        let filter = FileFilter {
+            exclude_tags: Some("generated,docs"),
+            ..Default::default()
+        };
+        let matched: Vec<_> = files
+            .iter()
+            .filter(|file| filter.matches(file))
+            .map(|file| file.path.clone())
+            .collect();
+        assert_eq!(matched, vec![PathBuf::from("src/core.rs")]);
+    }
+
+    #[test]
+    fn test_file_filter_namespaced_tags() {
+        let files = [
+            create_tagged_file_entry("web/app.tsx", &["area/frontend"]),
+            create_tagged_file_entry("api/server.rs", &["area/backend", "critical"]),
+            create_tagged_file_entry("docs/areas.md", &["areas/docs"]),
+            create_tagged_file_entry("src/lib.rs", &["core"]),
+        ];
+        let matched_paths = |filter: &FileFilter| -> Vec<PathBuf> {
+            files
+                .iter()
+                .filter(|file| filter.matches(file))
+                .map(|file| file.path.clone())
+                .collect()
+        };
+
+        // Namespace prefix matches every tag under `area/`, but not `areas/`
+        let filter = FileFilter {
+            tags: Some("area/"),
+            ..Default::default()
+        };
+        assert_eq!(
+            matched_paths(&filter),
+            vec![PathBuf::from("web/app.tsx"), PathBuf::from("api/server.rs")]
+        );
+
+        // Fully qualified and flat tags keep working
+        let filter = FileFilter {
+            tags: Some("area/backend,core"),
+            ..Default::default()
+        };
+        assert_eq!(
+            matched_paths(&filter),
+            vec![PathBuf::from("api/server.rs"), PathBuf::from("src/lib.rs")]
+        );
+
+        // Namespaces can be excluded too
+        let filter = FileFilter {
+            exclude_tags: Some("area/"),
+            ..Default::default()
+        };
+        assert_eq!(
+            matched_paths(&filter),
+            vec![PathBuf::from("docs/areas.md"), PathBuf::from("src/lib.rs")]
+        );
+    }
+
+    #[test]
+    fn test_json_envelope_reports_query_and_total() {
+        let files = [
+            create_test_file_entry("src/main.rs", "@alice", "core"),
+            create_test_file_entry("src/lib.rs", "@bob", "core"),
+            create_test_file_entry("docs/guide.md", "@alice", "docs"),
+        ];
+        let owner_regex = Regex::new("^@ali").unwrap();
+        let filter = FileFilter {
+            tags: Some("core"),
+            owner_regex: Some(&owner_regex),
+            ..Default::default()
+        };
+        let filtered: Vec<&FileEntry> = files.iter().filter(|f| filter.matches(f)).collect();
+
+        let envelope = serde_json::to_value(JsonEnvelope {
+            query: filter.query_json(),
+            total: filtered.len(),
+            results: &filtered,
+        })
+        .unwrap();
+
+        assert_eq!(
+            envelope["query"],
+            serde_json::json!({ "tags": "core", "owner_regex": "^@ali" })
+        );
+        let results = envelope["results"].as_array().unwrap();
+        assert_eq!(envelope["total"], results.len());
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["path"], "src/main.rs");
+    }
+
+    #[test]
+    fn test_json_map_is_keyed_by_path() {
+        let files = [
+            create_test_file_entry("src/main.rs", "@alice", "core"),
+            create_test_file_entry("docs/guide.md", "@bob", "docs"),
+        ];
+        let values = files
+            .iter()
+            .map(|file| serde_json::to_value(file).unwrap())
+            .collect();
+
+        let map = serde_json::Value::Object(keyed_by_path(values));
+
+        assert_eq!(map.as_object().unwrap().len(), 2);
+        let main = &map["src/main.rs"];
+        assert!(main.get("path").is_none());
+        assert_eq!(main["owners"][0]["identifier"], "@alice");
+        assert_eq!(main["tags"], serde_json::json!(["core"]));
+        assert_eq!(main["inline_override"], false);
+        assert_eq!(map["docs/guide.md"]["owners"][0]["identifier"], "@bob");
+        assert_eq!(map["docs/guide.md"]["tags"], serde_json::json!(["docs"]));
+    }
+
+    #[test]
+    fn test_render_count_matches_filtered_files() {
+        let files = [
+            create_tagged_file_entry("web/app.tsx", &["area/frontend"]),
+            create_tagged_file_entry("api/server.rs", &["area/backend"]),
+            create_tagged_file_entry("src/lib.rs", &["core"]),
+        ];
+        let filter = FileFilter {
+            tags: Some("area/"),
+            ..Default::default()
+        };
+        let count = files.iter().filter(|file| filter.matches(file)).count();
+        assert_eq!(count, 2);
+
+        assert_eq!(render_count(count, &OutputFormat::Text), "2");
+        assert_eq!(render_count(count, &OutputFormat::Json), r#"{"count":2}"#);
+    }
+
+    #[test]
+    fn test_parseable_line_quotes_for_the_shell() {
+        let file = create_tagged_file_entry("src/main.rs", &["core", "rust"]);
+        assert_eq!(
+            parseable_line(&file),
+            "path=src/main.rs;owners=@core-team;tags=core,rust"
+        );
+
+        let file = create_tagged_file_entry("docs/Getting Started's.md", &[]);
+        assert_eq!(
+            parseable_line(&file),
+            r#"path='docs/Getting Started'\''s.md';owners=@core-team;tags="#
+        );
+    }
+
+    #[test]
+    fn test_files_without_governing_codeowners() {
+        let entry = CodeownersEntry {
+            source_file: PathBuf::from("/repo/src/CODEOWNERS"),
+            line_number: 0,
+            pattern: "*.rs".to_string(),
+            owners: vec![],
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        };
+        let entries = vec![entry];
+        let dirs = codeowners_dirs(&entries);
+
+        // Covered by src/CODEOWNERS, even though its rule does not match
+        assert!(is_governed(Path::new("/repo/src/README.md"), &dirs));
+        assert!(is_governed(Path::new("/repo/src/deep/lib.rs"), &dirs));
+        // No CODEOWNERS file in any ancestor
+        assert!(!is_governed(Path::new("/repo/docs/guide.md"), &dirs));
+        assert!(!is_governed(Path::new("/repo/build.rs"), &dirs));
+        // A sibling directory sharing the prefix is not covered
+        assert!(!is_governed(Path::new("/repo/src-gen/lib.rs"), &dirs));
+
+        let unowned = FileEntry {
+            path: PathBuf::from("/repo/docs/guide.md"),
+            owners: vec![],
+            tags: vec![],
+            inline_override: false,
+        };
+        let filter = FileFilter {
+            no_governing_codeowners: true,
+            ..Default::default()
+        };
+        assert!(filter.matches(&unowned));
+    }
+
+    #[test]
+    fn test_file_filter_owner_regex_anchor() {
+        let owned_by = |path: &str, owner: &str| FileEntry {
+            path: PathBuf::from(path),
+            owners: vec![Owner {
+                identifier: owner.to_string(),
+                owner_type: OwnerType::Team,
+            }],
+            tags: vec![],
+            inline_override: false,
+        };
+        let files = [
+            owned_by("ops/pager.yml", "@org/infra-oncall"),
+            owned_by("ops/runbook.md", "@org/oncall-docs"),
+            owned_by("src/lib.rs", "@org/core"),
+        ];
+
+        let owner_regex = Regex::new("-oncall$").unwrap();
+        let filter = FileFilter {
+            owner_regex: Some(&owner_regex),
+            ..Default::default()
+        };
+        let matched: Vec<_> = files
+            .iter()
+            .filter(|file| filter.matches(file))
+            .map(|file| file.path.clone())
+            .collect();
+        assert_eq!(matched, vec![PathBuf::from("ops/pager.yml")]);
+    }
+
+    #[test]
+    fn test_file_filter_owner_match_any_vs_all() {
+        let owned_by = |path: &str, owners: &[&str]| FileEntry {
+            path: PathBuf::from(path),
+            owners: owners
+                .iter()
+                .map(|identifier| Owner {
+                    identifier: identifier.to_string(),
+                    owner_type: OwnerType::Team,
                 })
-                .collect();
+                .collect(),
+            tags: vec![],
+            inline_override: false,
+        };
+        let files = [
+            owned_by("api/server.rs", &["@org/backend"]),
+            owned_by("api/deploy.yml", &["@org/backend", "@org/devops"]),
+            owned_by("ci/build.yml", &["@org/devops"]),
+        ];
+        let matched_paths = |filter: &FileFilter| -> Vec<PathBuf> {
+            files
+                .iter()
+                .filter(|file| filter.matches(file))
+                .map(|file| file.path.clone())
+                .collect()
+        };
 
-            // Get terminal width, fallback to 80 if unavailable
-            let terminal_width =
-                if let Some((terminal_size::Width(w), _)) = terminal_size::terminal_size() {
-                    w as usize
-                } else {
-                    80
-                };
+        let filter = FileFilter {
+            owners: Some("@org/backend,@org/devops"),
+            ..Default::default()
+        };
+        assert_eq!(matched_paths(&filter).len(), 3);
 
-            let mut table = Table::new(table_data);
-            table
-                .with(tabled::settings::Style::modern())
-                .with(tabled::settings::Width::wrap(
-                    terminal_width.saturating_sub(4),
-                ))
-                .with(tabled::settings::Padding::new(1, 1, 0, 0));
+        let filter = FileFilter {
+            owners: Some("@org/backend,@org/devops"),
+            owner_match: OwnerMatch::All,
+            ..Default::default()
+        };
+        assert_eq!(
+            matched_paths(&filter),
+            vec![PathBuf::from("api/deploy.yml")]
+        );
+    }
 
-            println!("{}", table);
-            println!("Total: {} files", filtered_files.len());
+    fn create_test_file_entry(path: &str, owner: &str, tag: &str) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from(path),
+            owners: vec![Owner {
+                identifier: owner.to_string(),
+                owner_type: OwnerType::User,
+            }],
+            tags: vec![Tag(tag.to_string())],
+            inline_override: false,
         }
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&filtered_files).unwrap());
+    }
+
+    #[test]
+    fn test_write_indexed_json_offsets_point_at_records() -> Result<()> {
+        let files = [
+            create_test_file_entry("src/main.rs", "@rust", "core"),
+            create_test_file_entry("docs/guide.md", "@docs", "docs"),
+            create_test_file_entry("src/lib.rs", "@rust", "lib"),
+        ];
+        let files: Vec<&FileEntry> = files.iter().collect();
+
+        let mut output = Vec::new();
+        let index = write_indexed_json(&mut output, &files)?;
+
+        // Same bytes as the unindexed JSON output
+        let mut plain = Vec::new();
+        write_json_pretty(&mut plain, &files)?;
+        assert_eq!(output, plain);
+
+        let record_at = |offset: u64| -> FileEntry {
+            serde_json::Deserializer::from_slice(&output[offset as usize..])
+                .into_iter::<FileEntry>()
+                .next()
+                .unwrap()
+                .unwrap()
+        };
+        let paths = |key: &str| -> Vec<PathBuf> {
+            index[key]
+                .iter()
+                .map(|&offset| record_at(offset).path)
+                .collect()
+        };
+        assert_eq!(
+            paths("owner:@rust"),
+            vec![PathBuf::from("src/main.rs"), PathBuf::from("src/lib.rs")]
+        );
+        assert_eq!(paths("owner:@docs"), vec![PathBuf::from("docs/guide.md")]);
+        assert_eq!(paths("tag:lib"), vec![PathBuf::from("src/lib.rs")]);
+        assert_eq!(index.len(), 5);
+
+        let mut empty = Vec::new();
+        assert!(write_indexed_json(&mut empty, &[])?.is_empty());
+        assert_eq!(empty, b"[]\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_with_rules_points_at_winning_line() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let base_path = temp_dir.path();
+        let codeowners = base_path.join("CODEOWNERS");
+        std::fs::write(
+            &codeowners,
+            "# Owners\n*.rs @rust-team #rust\n/src/ @core-team #core\n",
+        )?;
+        std::fs::create_dir_all(base_path.join("src"))?;
+        std::fs::write(base_path.join("src/lib.rs"), "")?;
+        std::fs::write(base_path.join("build.rs"), "")?;
+        std::fs::write(base_path.join("notes.txt"), "")?;
+
+        let matchers: Vec<CodeownersEntryMatcher> = parse_codeowners(&codeowners)?
+            .iter()
+            .map(|entry| codeowners_entry_to_matcher(entry, false))
+            .collect();
+        let file_entry = |name: &str, owner: &str, tag: &str| {
+            create_test_file_entry(&base_path.join(name).to_string_lossy(), owner, tag)
+        };
+        let files = [
+            file_entry("src/lib.rs", "@core-team", "core"),
+            file_entry("build.rs", "@rust-team", "rust"),
+            file_entry("notes.txt", "@nobody", "none"),
+        ];
+        let files: Vec<&FileEntry> = files.iter().collect();
+
+        let values = files_with_rules(&files, &matchers)?;
+
+        // The later `/src/` rule wins over `*.rs` for src/lib.rs
+        assert_eq!(values[0]["rule"]["pattern"], "/src/");
+        assert_eq!(values[0]["rule"]["line_number"], 2);
+        assert_eq!(
+            values[0]["rule"]["source_file"],
+            codeowners.to_string_lossy().as_ref()
+        );
+        assert_eq!(values[0]["path"], files[0].path.to_string_lossy().as_ref());
+
+        assert_eq!(values[1]["rule"]["pattern"], "*.rs");
+        assert_eq!(values[1]["rule"]["line_number"], 1);
+
+        assert!(values[2]["rule"].is_null());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_paths_link_only_when_enabled() {
+        let files = [
+            create_test_file_entry("src/main.rs", "@alice", "core"),
+            create_test_file_entry("docs/my guide.md", "@bob", "docs"),
+        ];
+        let files: Vec<&FileEntry> = files.iter().collect();
+        let built = build_table(&files, 200, OwnerDisplay::Identifier);
+        let table = built.to_string();
+
+        assert!(!hyperlinks_enabled(true, false, false, Some("iTerm.app")));
+        assert!(!hyperlinks_enabled(true, true, true, Some("iTerm.app")));
+        assert!(!hyperlinks_enabled(false, true, false, Some("tmux")));
+        assert!(hyperlinks_enabled(false, true, false, Some("WezTerm")));
+        assert!(hyperlinks_enabled(true, true, false, None));
+
+        // Disabled output is the plain table
+        assert!(!table.contains("\x1b]8;;"));
+
+        let linked = link_table_paths(&built, &files);
+        assert_eq!(linked.matches("\x1b]8;;file://").count(), 2);
+        let cwd = std::env::current_dir().unwrap();
+        let main_url = format!("file://{}/src/main.rs", cwd.display());
+        assert!(linked.contains(&hyperlink("src/main.rs", &main_url)));
+        assert!(linked.contains("/docs/my%20guide.md\x1b\\docs/my guide.md\x1b]8;;\x1b\\"));
+
+        // Stripping the escapes gives back the plain table
+        let stripped = linked
+            .replace(&hyperlink("src/main.rs", &main_url), "src/main.rs")
+            .replace(
+                &hyperlink("docs/my guide.md", &file_url(Path::new("docs/my guide.md"))),
+                "docs/my guide.md",
+            );
+        assert_eq!(stripped, table);
+    }
+
+    #[test]
+    fn test_table_without_header_has_only_data_rows() {
+        let files = [
+            create_test_file_entry("src/main.rs", "@alice", "core"),
+            create_test_file_entry("docs/guide.md", "@bob", "docs"),
+        ];
+        let files: Vec<&FileEntry> = files.iter().collect();
+        let mut table = build_table(&files, 200, OwnerDisplay::Identifier);
+        remove_header(&mut table);
+
+        assert_eq!(
+            table.to_string(),
+            " src/main.rs     @alice   core \n docs/guide.md   @bob     docs "
+        );
+
+        // Links go on the data rows, with no header row to skip
+        let linked = link_table_paths(&table, &files);
+        let lines: Vec<&str> = linked.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(&format!(
+            " {}",
+            hyperlink("src/main.rs", &file_url(Path::new("src/main.rs")))
+        )));
+        assert!(lines[1].starts_with(&format!(
+            " {}",
+            hyperlink("docs/guide.md", &file_url(Path::new("docs/guide.md")))
+        )));
+    }
+
+    #[test]
+    fn test_build_table_aligns_wide_unicode_owners() {
+        let files = [
+            create_test_file_entry("src/main.rs", "@alice", "core"),
+            create_test_file_entry("src/ui/画面.tsx", "@チーム開発者", "フロント"),
+            create_test_file_entry(
+                "docs/guide.md",
+                "@非常に長い名前のチームの担当者です長い長い長い",
+                "docs",
+            ),
+        ];
+        let files: Vec<&FileEntry> = files.iter().collect();
+
+        let rendered = build_table(&files, 200, OwnerDisplay::Identifier).to_string();
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        // Every row must occupy the same number of terminal columns
+        let expected_width = lines[0].width();
+        for line in &lines {
+            assert_eq!(line.width(), expected_width, "misaligned row: {}", line);
         }
-        OutputFormat::Bincode => {
-            let encoded =
-                bincode::serde::encode_to_vec(&filtered_files, bincode::config::standard())
-                    .map_err(|e| Error::new(&format!("Serialization error: {}", e)))?;
 
-            // Write raw binary bytes to stdout
-            io::stdout()
-                .write_all(&encoded)
-                .map_err(|e| Error::new(&format!("IO error: {}", e)))?;
+        // Column separators line up at the same display column on every row
+        let separator_columns = |line: &str| -> Vec<usize> {
+            let mut col = 0;
+            let mut columns = Vec::new();
+            for c in line.chars() {
+                if c == '│' {
+                    columns.push(col);
+                }
+                col += c.to_string().width();
+            }
+            columns
+        };
+        let header_columns = separator_columns(lines[1]);
+        for line in lines.iter().filter(|l| l.starts_with('│')) {
+            assert_eq!(separator_columns(line), header_columns);
         }
     }
 
-    Ok(())
+    #[test]
+    fn test_format_owners_display_modes() {
+        let owners = vec![
+            Owner {
+                identifier: "@org/core".to_string(),
+                owner_type: OwnerType::Team,
+            },
+            Owner {
+                identifier: "dev@example.com".to_string(),
+                owner_type: OwnerType::Email,
+            },
+            Owner {
+                identifier: "@org/docs".to_string(),
+                owner_type: OwnerType::Team,
+            },
+        ];
+
+        assert_eq!(
+            format_owners(&owners, OwnerDisplay::Identifier),
+            "@org/core, dev@example.com, @org/docs"
+        );
+        assert_eq!(
+            format_owners(&owners, OwnerDisplay::Both),
+            "@org/core (Team), dev@example.com (Email), @org/docs (Team)"
+        );
+        assert_eq!(
+            format_owners(&owners, OwnerDisplay::Type),
+            "Team: @org/core, @org/docs; Email: dev@example.com"
+        );
+    }
+
+    #[test]
+    fn test_single_point_of_failure() {
+        let owner = |identifier: &str, owner_type| Owner {
+            identifier: identifier.to_string(),
+            owner_type,
+        };
+        let alice = owner("@alice", OwnerType::User);
+        let bob = owner("@bob", OwnerType::User);
+        let team = owner("@org/core", OwnerType::Team);
+        let file = |path: &str, owners: Vec<Owner>| FileEntry {
+            path: PathBuf::from(path),
+            owners,
+            tags: vec![],
+            inline_override: false,
+        };
+
+        let alice_only = file("src/billing.rs", vec![alice.clone()]);
+        let bob_only = file("src/auth.rs", vec![bob.clone()]);
+        let team_only = file("src/lib.rs", vec![team.clone()]);
+        let with_backup = file("src/api.rs", vec![alice.clone(), team.clone()]);
+
+        let mut owners_map = HashMap::new();
+        owners_map.insert(
+            alice.clone(),
+            vec![alice_only.path.clone(), with_backup.path.clone()],
+        );
+        owners_map.insert(bob, vec![bob_only.path.clone()]);
+        owners_map.insert(team, vec![team_only.path.clone(), with_backup.path.clone()]);
+
+        assert!(is_single_point_of_failure(&alice_only, &owners_map, 0));
+        assert!(is_single_point_of_failure(&bob_only, &owners_map, 0));
+        assert!(!is_single_point_of_failure(&team_only, &owners_map, 0));
+        assert!(!is_single_point_of_failure(&with_backup, &owners_map, 0));
+
+        // Only users owning at least two files count as high impact
+        assert!(is_single_point_of_failure(&alice_only, &owners_map, 2));
+        assert!(!is_single_point_of_failure(&bob_only, &owners_map, 2));
+    }
+
+    #[test]
+    fn test_path_globs_only_and_exclude() -> Result<()> {
+        let file = |path: &str| FileEntry {
+            path: PathBuf::from(path),
+            owners: vec![Owner {
+                identifier: "@org/web".to_string(),
+                owner_type: OwnerType::Team,
+            }],
+            tags: vec![],
+            inline_override: false,
+        };
+        let files = [
+            file("/repo/web/App.tsx"),
+            file("/repo/web/App.test.tsx"),
+            file("/repo/web/index.ts"),
+            file("/repo/api/server.rs"),
+        ];
+        let listed = |only: &[&str], exclude: &[&str]| -> Result<Vec<String>> {
+            let to_vec = |globs: &[&str]| globs.iter().map(|g| g.to_string()).collect::<Vec<_>>();
+            let paths = PathGlobs::new(Path::new("/repo"), &to_vec(only), &to_vec(exclude))?;
+            let filter = FileFilter {
+                paths: paths.as_ref(),
+                ..Default::default()
+            };
+            Ok(files
+                .iter()
+                .filter(|file| filter.matches(file))
+                .map(|file| file.path.display().to_string())
+                .collect())
+        };
+
+        assert!(PathGlobs::new(Path::new("/repo"), &[], &[])?.is_none());
+        assert_eq!(
+            listed(&["**/*.tsx"], &[])?,
+            vec!["/repo/web/App.tsx", "/repo/web/App.test.tsx"]
+        );
+        // Excludes win over includes
+        assert_eq!(
+            listed(&["**/*.tsx", "*.ts"], &["*.test.tsx"])?,
+            vec!["/repo/web/App.tsx", "/repo/web/index.ts"]
+        );
+        // Directory globs cover the files below them
+        assert_eq!(listed(&[], &["web/"])?, vec!["/repo/api/server.rs"]);
+        assert_eq!(
+            listed(&["web/"], &["*.test.tsx"])?,
+            vec!["/repo/web/App.tsx", "/repo/web/index.ts"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_modified_by_last_commit_author() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let base_path = temp_dir.path();
+        let repo = git2::Repository::init(base_path)?;
+        let commit = |author: &str, email: &str, files: &[(&str, &str)]| -> Result<()> {
+            let mut index = repo.index()?;
+            for (path, content) in files {
+                std::fs::write(base_path.join(path), content)?;
+                index.add_path(Path::new(path))?;
+            }
+            index.write()?;
+            let tree = repo.find_tree(index.write_tree()?)?;
+            let signature = git2::Signature::now(author, email)?;
+            let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "change",
+                &tree,
+                &parent.iter().collect::<Vec<_>>(),
+            )?;
+            Ok(())
+        };
+        commit(
+            "Alice",
+            "alice@example.com",
+            &[("a.rs", "a"), ("b.rs", "b"), ("c.rs", "c")],
+        )?;
+        commit("Bob", "bob@example.com", &[("b.rs", "b2")])?;
+        std::fs::write(base_path.join("untracked.rs"), "")?;
+
+        let files: Vec<FileEntry> = ["a.rs", "b.rs", "c.rs", "untracked.rs"]
+            .iter()
+            .map(|path| FileEntry {
+                path: base_path.join(path),
+                owners: vec![],
+                tags: vec![],
+                inline_override: false,
+            })
+            .collect();
+        let modified_by = |author: &str| -> Result<Vec<PathBuf>> {
+            Ok(
+                files_modified_by(base_path, files.iter().collect(), author)?
+                    .into_iter()
+                    .map(|file| file.path.strip_prefix(base_path).unwrap().to_path_buf())
+                    .collect(),
+            )
+        };
+
+        // Only the last commit to each file counts, by name or email
+        assert_eq!(
+            modified_by("Alice")?,
+            vec![PathBuf::from("a.rs"), PathBuf::from("c.rs")]
+        );
+        assert_eq!(modified_by("bob@example.com")?, vec![PathBuf::from("b.rs")]);
+        assert!(modified_by("carol")?.is_empty());
+
+        Ok(())
+    }
 }
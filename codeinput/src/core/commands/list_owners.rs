@@ -1,8 +1,20 @@
 use crate::{
-    core::{cache::sync_cache, display::truncate_string, types::OutputFormat},
-    utils::error::{Error, Result},
+    core::{
+        cache::sync_cache,
+        common::collect_owners,
+        display::{
+            encode_bincode_output, insert_file_list, remove_header, sample_file_names,
+            stdout_writer, truncate_string, write_json_pretty, JsonEnvelope,
+        },
+        html,
+        identity::IdentityMap,
+        types::{BincodePayload, CodeownersEntry, FileEntry, OutputFormat, Owner, OwnerType, Tag},
+    },
+    utils::error::{Error, ErrorKind, Result},
 };
-use std::io::{self, Write};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Write;
+use std::path::PathBuf;
 use tabled::{Table, Tabled};
 
 #[derive(Tabled)]
@@ -15,60 +27,112 @@ struct OwnerDisplay {
     file_count: usize,
     #[tabled(rename = "Sample Files")]
     sample_files: String,
+    #[tabled(rename = "Tags")]
+    tags: String,
+}
+
+#[derive(Tabled)]
+struct UnknownOwnerDisplay {
+    #[tabled(rename = "Owner")]
+    identifier: String,
+    #[tabled(rename = "Declared At")]
+    declared_at: String,
+    #[tabled(rename = "Did You Mean")]
+    suggestion: String,
+}
+
+#[derive(Tabled)]
+struct OrphanedOwnerDisplay {
+    #[tabled(rename = "Owner")]
+    identifier: String,
+    #[tabled(rename = "Type")]
+    owner_type: String,
+    #[tabled(rename = "Declared At")]
+    declared_at: String,
+}
+
+#[derive(Tabled)]
+struct DomainDisplay {
+    #[tabled(rename = "Email Domain")]
+    domain: String,
+    #[tabled(rename = "Owners")]
+    owner_count: usize,
+}
+
+/// Minimum normalized Levenshtein similarity for an owner to be suggested
+const SUGGESTION_THRESHOLD: f64 = 0.7;
+
+/// Order of the owners listed by `list-owners`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OwnerSort {
+    /// Most files first
+    #[default]
+    Files,
+    /// Teams, then users, then emails, each by number of files
+    Type,
+    /// Alphabetically by identifier
+    Name,
+}
+
+/// Distinct tags of the files each owner owns
+type OwnerTags<'a> = HashMap<&'a Owner, BTreeSet<&'a Tag>>;
+
+/// Output options for `list-owners`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OwnerListOptions {
+    /// Show owners grouped under their type
+    pub group_by_type: bool,
+    /// Cap on sample files in text output and on the file list in JSON output
+    pub max_files: Option<usize>,
+    /// Show the distinct tags of each owner's files (text, JSON and HTML)
+    pub with_tags: bool,
+    /// Wrap JSON output in `{ "query", "total", "results" }`
+    pub json_envelope: bool,
+    /// Leave out the type headings, table header rows and total line of text output
+    pub no_header: bool,
+    /// Order of the listed owners
+    pub sort: OwnerSort,
 }
 
 /// Display aggregated owner statistics and associations
+///
+/// `owner_type` keeps only owners of that type. With `group_by_type`, text
+/// output shows one table per type and JSON output nests owners under their
+/// type; bincode output is never grouped. `max_files` caps the sample files
+/// shown per owner in text output (default 3) and the file list embedded in
+/// JSON output (default unlimited). With `with_tags`, each owner also shows
+/// the distinct tags of its files. With `json_envelope`, JSON output is
+/// wrapped with the query and the number of owners. With `no_header`, text
+/// output is only the table rows. `sort` orders the owners, within each type
+/// when they are grouped.
 pub fn run(
-    repo: Option<&std::path::Path>, format: &OutputFormat, cache_file: Option<&std::path::Path>,
+    repo: Option<&std::path::Path>, owner_type: Option<&OwnerType>, format: &OutputFormat,
+    cache_file: Option<&std::path::Path>, options: &OwnerListOptions,
 ) -> Result<()> {
+    let OwnerListOptions {
+        group_by_type,
+        max_files,
+        with_tags,
+        json_envelope,
+        no_header,
+        sort,
+    } = *options;
+
     // Repository path
     let repo = repo.unwrap_or_else(|| std::path::Path::new("."));
 
     // Load the cache
     let cache = sync_cache(repo, cache_file)?;
 
-    // Sort owners by number of files they own (descending)
-    let mut owners_with_counts: Vec<_> = cache.owners_map.iter().collect();
-    owners_with_counts.sort_by(|a, b| b.1.len().cmp(&a.1.len()));
+    let mut owners_with_counts = owners_by_file_count(&cache.owners_map, owner_type);
+    sort_owners(&mut owners_with_counts, sort);
+    let owner_tags = with_tags.then(|| tags_by_owner(&cache.files));
+    let owner_tags = owner_tags.as_ref();
 
     // Process the owners from the cache
+    let mut stdout = stdout_writer();
     match format {
         OutputFormat::Text => {
-            // Create table data
-            let table_data: Vec<OwnerDisplay> = owners_with_counts
-                .iter()
-                .map(|(owner, paths)| {
-                    // Prepare sample file list
-                    let file_samples = if paths.is_empty() {
-                        "None".to_string()
-                    } else {
-                        let samples: Vec<_> = paths
-                            .iter()
-                            .take(3) // Show max 3 files as samples
-                            .map(|p| {
-                                let file_name = p
-                                    .file_name()
-                                    .map(|f| f.to_string_lossy().to_string())
-                                    .unwrap_or_else(|| p.to_string_lossy().to_string());
-                                file_name
-                            })
-                            .collect();
-                        let mut display = samples.join(", ");
-                        if paths.len() > 3 {
-                            display.push_str(&format!(" (+{})", paths.len() - 3));
-                        }
-                        display
-                    };
-
-                    OwnerDisplay {
-                        identifier: truncate_string(&owner.identifier, 35),
-                        owner_type: format!("{:?}", owner.owner_type),
-                        file_count: paths.len(),
-                        sample_files: truncate_string(&file_samples, 45),
-                    }
-                })
-                .collect();
-
             // Get terminal width, fallback to 80 if unavailable
             let terminal_width =
                 if let Some((terminal_size::Width(w), _)) = terminal_size::terminal_size() {
@@ -76,44 +140,906 @@ pub fn run(
                 } else {
                     80
                 };
+            let sample_count = max_files.unwrap_or(3);
+
+            if group_by_type {
+                for (owner_type, owners) in group_by_owner_type(&owners_with_counts) {
+                    if !no_header {
+                        writeln!(stdout, "{} ({})", owner_type, owners.len())?;
+                    }
+                    writeln!(
+                        stdout,
+                        "{}",
+                        build_table(&owners, terminal_width, sample_count, owner_tags, no_header)
+                    )?;
+                }
+            } else {
+                writeln!(
+                    stdout,
+                    "{}",
+                    build_table(
+                        &owners_with_counts,
+                        terminal_width,
+                        sample_count,
+                        owner_tags,
+                        no_header
+                    )
+                )?;
+            }
+            if !no_header {
+                writeln!(stdout, "Total: {} owners", owners_with_counts.len())?;
+            }
+        }
+        OutputFormat::Json => {
+            // Convert to a more friendly JSON structure
+            let owners_data = if group_by_type {
+                let groups: serde_json::Map<String, serde_json::Value> =
+                    group_by_owner_type(&owners_with_counts)
+                        .into_iter()
+                        .map(|(owner_type, owners)| {
+                            (
+                                owner_type.to_lowercase(),
+                                owners_json(&owners, max_files, owner_tags),
+                            )
+                        })
+                        .collect();
+                serde_json::Value::Object(groups)
+            } else {
+                owners_json(&owners_with_counts, max_files, owner_tags)
+            };
+
+            if json_envelope {
+                // Only the options that were given
+                let mut query = serde_json::Map::new();
+                if let Some(owner_type) = owner_type {
+                    query.insert("type".to_string(), serde_json::json!(owner_type));
+                }
+                if group_by_type {
+                    query.insert("group_by_type".to_string(), true.into());
+                }
+                if let Some(max_files) = max_files {
+                    query.insert("max_files".to_string(), max_files.into());
+                }
+                if with_tags {
+                    query.insert("with_tags".to_string(), true.into());
+                }
+                let envelope = JsonEnvelope {
+                    query: serde_json::Value::Object(query),
+                    total: owners_with_counts.len(),
+                    results: owners_data,
+                };
+                write_json_pretty(&mut stdout, &envelope)?;
+            } else {
+                write_json_pretty(&mut stdout, &owners_data)?;
+            }
+        }
+        OutputFormat::Dot => {
+            return Err(Error::with_kind(
+                ErrorKind::Validation,
+                "Owner list is not available as dot",
+            ));
+        }
+        OutputFormat::Bincode => {
+            let encoded = encode_bincode_output(BincodePayload::Owners, &owners_with_counts)?;
+
+            // Write framed binary bytes to stdout
+            stdout
+                .write_all(&encoded)
+                .map_err(|e| Error::new(&format!("IO error: {}", e)))?;
+        }
+        OutputFormat::Html => {
+            let rows: Vec<Vec<String>> = owners_with_counts
+                .iter()
+                .map(|(owner, paths)| {
+                    let mut row = vec![
+                        owner.identifier.clone(),
+                        owner.owner_type.to_string(),
+                        paths.len().to_string(),
+                    ];
+                    if let Some(owner_tags) = owner_tags {
+                        row.push(tag_list(owner_tags.get(owner)));
+                    }
+                    row
+                })
+                .collect();
+            let headers: &[&str] = if with_tags {
+                &["Owner", "Type", "Files", "Tags"]
+            } else {
+                &["Owner", "Type", "Files"]
+            };
+            write!(stdout, "{}", html::render_table("Owners", headers, &rows))?;
+        }
+    }
+
+    stdout.finish()
+}
+
+/// Display how many email owners belong to each email domain
+///
+/// Useful to spot owners outside the organization's own domains.
+pub fn run_email_domains(
+    repo: Option<&std::path::Path>, format: &OutputFormat, cache_file: Option<&std::path::Path>,
+) -> Result<()> {
+    // Repository path
+    let repo = repo.unwrap_or_else(|| std::path::Path::new("."));
+
+    // Load the cache
+    let cache = sync_cache(repo, cache_file)?;
+
+    // Sort domains by number of owners (descending), then by name
+    let mut domains: Vec<_> = email_domain_breakdown(cache.owners_map.keys())
+        .into_iter()
+        .collect();
+    domains.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let mut stdout = stdout_writer();
+    match format {
+        OutputFormat::Text => {
+            let table_data: Vec<DomainDisplay> = domains
+                .iter()
+                .map(|(domain, owner_count)| DomainDisplay {
+                    domain: truncate_string(domain, 50),
+                    owner_count: *owner_count,
+                })
+                .collect();
 
             let mut table = Table::new(table_data);
             table
                 .with(tabled::settings::Style::modern())
-                .with(tabled::settings::Width::wrap(
-                    terminal_width.saturating_sub(4),
-                ))
                 .with(tabled::settings::Padding::new(1, 1, 0, 0));
 
-            println!("{}", table);
-            println!("Total: {} owners", cache.owners_map.len());
+            writeln!(stdout, "{}", table)?;
+            writeln!(stdout, "Total: {} email domains", domains.len())?;
         }
         OutputFormat::Json => {
-            // Convert to a more friendly JSON structure
-            let owners_data: Vec<_> = owners_with_counts.iter()
-                .map(|(owner, paths)| {
+            let domains_data: serde_json::Map<String, serde_json::Value> = domains
+                .into_iter()
+                .map(|(domain, owner_count)| (domain, owner_count.into()))
+                .collect();
+
+            write_json_pretty(&mut stdout, &domains_data)?;
+        }
+        OutputFormat::Html => {
+            let rows: Vec<Vec<String>> = domains
+                .iter()
+                .map(|(domain, owner_count)| vec![domain.clone(), owner_count.to_string()])
+                .collect();
+            write!(
+                stdout,
+                "{}",
+                html::render_table("Email Domains", &["Email Domain", "Owners"], &rows)
+            )?;
+        }
+        OutputFormat::Bincode | OutputFormat::Dot => {
+            return Err(Error::with_kind(
+                ErrorKind::Validation,
+                &format!("Email domain breakdown is not available as {}", format),
+            ));
+        }
+    }
+
+    stdout.finish()
+}
+
+/// Display owners of `Unknown` type together with where they are declared
+///
+/// Such owners (e.g. `org/team` without the leading `@`) are usually typos.
+/// With `suggest`, each one is shown with the closest known owner, if any.
+pub fn run_unknown(
+    repo: Option<&std::path::Path>, format: &OutputFormat, cache_file: Option<&std::path::Path>,
+    suggest: bool,
+) -> Result<()> {
+    // Repository path
+    let repo = repo.unwrap_or_else(|| std::path::Path::new("."));
+
+    // Load the cache
+    let cache = sync_cache(repo, cache_file)?;
+
+    let unknown = unknown_owner_declarations(&cache.entries, repo);
+
+    let mut known: Vec<&Owner> = cache
+        .owners_map
+        .keys()
+        .filter(|owner| !matches!(owner.owner_type, OwnerType::Unknown | OwnerType::Unowned))
+        .collect();
+    known.sort();
+    let suggestion =
+        |identifier: &str| suggest.then(|| suggest_owner(identifier, &known)).flatten();
+
+    let mut stdout = stdout_writer();
+    match format {
+        OutputFormat::Text => {
+            let table_data: Vec<UnknownOwnerDisplay> = unknown
+                .iter()
+                .map(|(identifier, sites)| UnknownOwnerDisplay {
+                    identifier: truncate_string(identifier, 35),
+                    declared_at: sites.join(", "),
+                    suggestion: suggestion(identifier)
+                        .map(|owner| owner.identifier.clone())
+                        .unwrap_or_default(),
+                })
+                .collect();
+
+            let mut table = Table::new(table_data);
+            if !suggest {
+                table.with(tabled::settings::Remove::column(
+                    tabled::settings::location::Locator::column("Did You Mean"),
+                ));
+            }
+            table
+                .with(tabled::settings::Style::modern())
+                .with(tabled::settings::Padding::new(1, 1, 0, 0));
+
+            writeln!(stdout, "{}", table)?;
+            writeln!(stdout, "Total: {} unknown owners", unknown.len())?;
+        }
+        OutputFormat::Json => {
+            let owners_data: Vec<_> = unknown
+                .iter()
+                .map(|(identifier, sites)| {
+                    let mut owner = serde_json::json!({
+                        "identifier": identifier,
+                        "declared_at": sites,
+                    });
+                    if suggest {
+                        owner["suggestion"] = suggestion(identifier)
+                            .map(|owner| owner.identifier.as_str())
+                            .into();
+                    }
+                    owner
+                })
+                .collect();
+
+            write_json_pretty(&mut stdout, &owners_data)?;
+        }
+        OutputFormat::Html => {
+            let rows: Vec<Vec<String>> = unknown
+                .iter()
+                .map(|(identifier, sites)| {
+                    let mut row = vec![identifier.to_string(), sites.join(", ")];
+                    if suggest {
+                        row.push(
+                            suggestion(identifier)
+                                .map(|owner| owner.identifier.clone())
+                                .unwrap_or_default(),
+                        );
+                    }
+                    row
+                })
+                .collect();
+            let headers: &[&str] = if suggest {
+                &["Owner", "Declared At", "Did You Mean"]
+            } else {
+                &["Owner", "Declared At"]
+            };
+            write!(
+                stdout,
+                "{}",
+                html::render_table("Unknown Owners", headers, &rows)
+            )?;
+        }
+        OutputFormat::Bincode | OutputFormat::Dot => {
+            return Err(Error::with_kind(
+                ErrorKind::Validation,
+                &format!("Unknown owner report is not available as {}", format),
+            ));
+        }
+    }
+
+    stdout.finish()
+}
+
+/// Display owners declared in CODEOWNERS files that own no file
+///
+/// An owner is orphaned when every rule naming it is shadowed or matches
+/// nothing, so it can usually be removed. `NOOWNER` is never listed.
+pub fn run_orphaned(
+    repo: Option<&std::path::Path>, format: &OutputFormat, cache_file: Option<&std::path::Path>,
+) -> Result<()> {
+    // Repository path
+    let repo = repo.unwrap_or_else(|| std::path::Path::new("."));
+
+    // Load the cache
+    let cache = sync_cache(repo, cache_file)?;
+
+    let identities = IdentityMap::new(cache.identity_map.clone())?;
+    let orphaned = orphaned_owners(&cache.entries, &cache.owners_map, &identities);
+    let declared_at = |owner: &Owner| -> Vec<String> {
+        cache
+            .entries
+            .iter()
+            .filter(|entry| entry.owners.contains(owner))
+            .map(|entry| {
+                let source = entry
+                    .source_file
+                    .strip_prefix(repo)
+                    .unwrap_or(&entry.source_file);
+                format!("{}:{}", source.display(), entry.line_number)
+            })
+            .collect()
+    };
+
+    let mut stdout = stdout_writer();
+    match format {
+        OutputFormat::Text => {
+            let table_data: Vec<OrphanedOwnerDisplay> = orphaned
+                .iter()
+                .map(|owner| OrphanedOwnerDisplay {
+                    identifier: truncate_string(&owner.identifier, 35),
+                    owner_type: owner.owner_type.to_string(),
+                    declared_at: declared_at(owner).join(", "),
+                })
+                .collect();
+
+            let mut table = Table::new(table_data);
+            table
+                .with(tabled::settings::Style::modern())
+                .with(tabled::settings::Padding::new(1, 1, 0, 0));
+
+            writeln!(stdout, "{}", table)?;
+            writeln!(stdout, "Total: {} orphaned owners", orphaned.len())?;
+        }
+        OutputFormat::Json => {
+            let owners_data: Vec<_> = orphaned
+                .iter()
+                .map(|owner| {
                     serde_json::json!({
                         "identifier": owner.identifier,
-                        "type": format!("{:?}", owner.owner_type),
-                        "file_count": paths.len(),
-                        "files": paths.iter().map(|p| p.to_string_lossy().to_string()).collect::<Vec<_>>()
+                        "type": owner.owner_type,
+                        "declared_at": declared_at(owner),
                     })
                 })
                 .collect();
 
-            println!("{}", serde_json::to_string_pretty(&owners_data).unwrap());
+            write_json_pretty(&mut stdout, &owners_data)?;
         }
-        OutputFormat::Bincode => {
-            let encoded =
-                bincode::serde::encode_to_vec(&owners_with_counts, bincode::config::standard())
-                    .map_err(|e| Error::new(&format!("Serialization error: {}", e)))?;
+        OutputFormat::Html => {
+            let rows: Vec<Vec<String>> = orphaned
+                .iter()
+                .map(|owner| {
+                    vec![
+                        owner.identifier.clone(),
+                        owner.owner_type.to_string(),
+                        declared_at(owner).join(", "),
+                    ]
+                })
+                .collect();
+            write!(
+                stdout,
+                "{}",
+                html::render_table("Orphaned Owners", &["Owner", "Type", "Declared At"], &rows)
+            )?;
+        }
+        OutputFormat::Bincode | OutputFormat::Dot => {
+            return Err(Error::with_kind(
+                ErrorKind::Validation,
+                &format!("Orphaned owner report is not available as {}", format),
+            ));
+        }
+    }
 
-            // Write raw binary bytes to stdout
-            io::stdout()
-                .write_all(&encoded)
-                .map_err(|e| Error::new(&format!("IO error: {}", e)))?;
+    stdout.finish()
+}
+
+/// The known owner most similar to `identifier`, ignoring case
+///
+/// Returns `None` when no owner reaches [`SUGGESTION_THRESHOLD`]. On ties the
+/// last owner in `known` wins.
+fn suggest_owner<'a>(identifier: &str, known: &[&'a Owner]) -> Option<&'a Owner> {
+    let identifier = identifier.to_lowercase();
+    known
+        .iter()
+        .map(|owner| {
+            let similarity =
+                strsim::normalized_levenshtein(&identifier, &owner.identifier.to_lowercase());
+            (similarity, *owner)
+        })
+        .filter(|(similarity, _)| *similarity >= SUGGESTION_THRESHOLD)
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, owner)| owner)
+}
+
+/// Owners of `Unknown` type mapped to the `source_file:line_number` sites declaring them
+///
+/// Source files are shown relative to `repo`.
+fn unknown_owner_declarations<'a>(
+    entries: &'a [CodeownersEntry], repo: &std::path::Path,
+) -> BTreeMap<&'a str, Vec<String>> {
+    let mut unknown: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for entry in entries {
+        let source = entry
+            .source_file
+            .strip_prefix(repo)
+            .unwrap_or(&entry.source_file);
+        let site = format!("{}:{}", source.display(), entry.line_number);
+        for owner in &entry.owners {
+            if owner.owner_type == OwnerType::Unknown {
+                unknown
+                    .entry(&owner.identifier)
+                    .or_default()
+                    .push(site.clone());
+            }
+        }
+    }
+    unknown
+}
+
+/// Owners declared in `entries` with no files in `owners_map`, sorted, leaving out `NOOWNER`
+///
+/// Emails merged into a handle by `identities` are looked up as that handle.
+fn orphaned_owners(
+    entries: &[CodeownersEntry], owners_map: &HashMap<Owner, Vec<PathBuf>>,
+    identities: &IdentityMap,
+) -> Vec<Owner> {
+    let mut orphaned: Vec<Owner> = collect_owners(entries)
+        .into_iter()
+        .filter(|owner| owner.owner_type != OwnerType::Unowned)
+        .filter(|owner| {
+            owners_map
+                .get(&identities.canonical(owner))
+                .is_none_or(|paths| paths.is_empty())
+        })
+        .collect();
+    orphaned.sort();
+    orphaned
+}
+
+/// Count `Email` owners per domain, the part of the identifier after the last `@`
+///
+/// Domains are lowercased so `Example.com` and `example.com` are counted together.
+pub fn email_domain_breakdown<'a>(
+    owners: impl IntoIterator<Item = &'a Owner>,
+) -> HashMap<String, usize> {
+    let mut domains = HashMap::new();
+    for owner in owners {
+        if owner.owner_type != OwnerType::Email {
+            continue;
+        }
+        if let Some((_, domain)) = owner.identifier.rsplit_once('@') {
+            *domains.entry(domain.to_lowercase()).or_insert(0) += 1;
         }
     }
+    domains
+}
+
+/// Owners sorted by number of files they own (descending), optionally of a single type
+fn owners_by_file_count<'a>(
+    owners_map: &'a HashMap<Owner, Vec<PathBuf>>, owner_type: Option<&OwnerType>,
+) -> Vec<(&'a Owner, &'a Vec<PathBuf>)> {
+    let mut owners_with_counts: Vec<_> = owners_map
+        .iter()
+        .filter(|(owner, _)| owner_type.is_none_or(|t| owner.owner_type == *t))
+        .collect();
+    owners_with_counts.sort_by(|a, b| b.1.len().cmp(&a.1.len()));
+    owners_with_counts
+}
+
+/// Reorder owners listed by file count according to `sort`
+///
+/// The sort is stable, so owners of the same type stay ordered by file count.
+fn sort_owners(owners: &mut [(&Owner, &Vec<PathBuf>)], sort: OwnerSort) {
+    let type_rank = |owner_type: &OwnerType| match owner_type {
+        OwnerType::Team => 0,
+        OwnerType::User => 1,
+        OwnerType::Email => 2,
+        OwnerType::Unowned => 3,
+        OwnerType::Unknown => 4,
+    };
+    match sort {
+        OwnerSort::Files => {}
+        OwnerSort::Type => owners.sort_by_key(|(owner, _)| type_rank(&owner.owner_type)),
+        OwnerSort::Name => owners.sort_by(|a, b| a.0.identifier.cmp(&b.0.identifier)),
+    }
+}
 
-    Ok(())
+/// Partition owners by their type name, keeping their order within each type
+fn group_by_owner_type<'a>(
+    owners: &[(&'a Owner, &'a Vec<PathBuf>)],
+) -> BTreeMap<String, Vec<(&'a Owner, &'a Vec<PathBuf>)>> {
+    let mut groups: BTreeMap<String, Vec<_>> = BTreeMap::new();
+    for &(owner, paths) in owners {
+        groups
+            .entry(owner.owner_type.to_string())
+            .or_default()
+            .push((owner, paths));
+    }
+    groups
+}
+
+/// Distinct tags across the files of each owner
+fn tags_by_owner(files: &[FileEntry]) -> OwnerTags<'_> {
+    let mut owner_tags: OwnerTags = HashMap::new();
+    for file in files {
+        for owner in &file.owners {
+            owner_tags.entry(owner).or_default().extend(&file.tags);
+        }
+    }
+    owner_tags
+}
+
+/// Comma-separated tag names, e.g. `backend, security`
+fn tag_list(tags: Option<&BTreeSet<&Tag>>) -> String {
+    tags.into_iter()
+        .flatten()
+        .map(|tag| tag.0.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Owner table, with a Tags column only when `owner_tags` is given
+fn build_table(
+    owners: &[(&Owner, &Vec<PathBuf>)], terminal_width: usize, sample_count: usize,
+    owner_tags: Option<&OwnerTags>, no_header: bool,
+) -> Table {
+    // Create table data
+    let table_data: Vec<OwnerDisplay> = owners
+        .iter()
+        .map(|(owner, paths)| {
+            let file_samples = sample_file_names(paths, sample_count);
+
+            OwnerDisplay {
+                identifier: truncate_string(&owner.identifier, 35),
+                owner_type: owner.owner_type.to_string(),
+                file_count: paths.len(),
+                sample_files: truncate_string(&file_samples, 45),
+                tags: truncate_string(&tag_list(owner_tags.and_then(|t| t.get(owner))), 40),
+            }
+        })
+        .collect();
+
+    let mut table = Table::new(table_data);
+    if owner_tags.is_none() {
+        table.with(tabled::settings::Remove::column(
+            tabled::settings::location::Locator::column("Tags"),
+        ));
+    }
+    table
+        .with(tabled::settings::Style::modern())
+        .with(tabled::settings::Width::wrap(
+            terminal_width.saturating_sub(4),
+        ))
+        .with(tabled::settings::Padding::new(1, 1, 0, 0));
+    if no_header {
+        remove_header(&mut table);
+    }
+
+    table
+}
+
+fn owners_json(
+    owners: &[(&Owner, &Vec<PathBuf>)], max_files: Option<usize>, owner_tags: Option<&OwnerTags>,
+) -> serde_json::Value {
+    owners
+        .iter()
+        .map(|(owner, paths)| {
+            let mut value = serde_json::json!({
+                "identifier": owner.identifier,
+                "type": owner.owner_type,
+                "file_count": paths.len(),
+            });
+            insert_file_list(&mut value, paths, max_files);
+            if let Some(owner_tags) = owner_tags {
+                let tags: Vec<&str> = owner_tags
+                    .get(owner)
+                    .into_iter()
+                    .flatten()
+                    .map(|tag| tag.0.as_str())
+                    .collect();
+                value["tags"] = tags.into();
+            }
+            value
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::owner_resolver::build_owners_map;
+
+    fn owner(identifier: &str, owner_type: OwnerType) -> Owner {
+        Owner {
+            identifier: identifier.to_string(),
+            owner_type,
+        }
+    }
+
+    #[test]
+    fn test_owners_json_caps_file_list() {
+        let core = owner("@org/core", OwnerType::Team);
+        let docs = owner("@org/docs", OwnerType::Team);
+        let core_files: Vec<PathBuf> = (0..5)
+            .map(|i| PathBuf::from(format!("src/{}.rs", i)))
+            .collect();
+        let docs_files = vec![PathBuf::from("README.md")];
+        let owners = vec![(&core, &core_files), (&docs, &docs_files)];
+
+        let json = owners_json(&owners, Some(2), None);
+        assert_eq!(json[0]["file_count"], 5);
+        assert_eq!(
+            json[0]["files"],
+            serde_json::json!(["src/0.rs", "src/1.rs"])
+        );
+        assert_eq!(json[0]["files_truncated"], true);
+        assert_eq!(json[1]["file_count"], 1);
+        assert_eq!(json[1]["files"], serde_json::json!(["README.md"]));
+        assert!(json[1].get("files_truncated").is_none());
+
+        // Without a cap every file is listed
+        let json = owners_json(&owners, None, None);
+        assert_eq!(json[0]["files"].as_array().map(Vec::len), Some(5));
+        assert!(json[0].get("files_truncated").is_none());
+
+        assert_eq!(sample_file_names(&core_files, 2), "0.rs, 1.rs (+3)");
+    }
+
+    #[test]
+    fn test_owner_tags_cross_reference() {
+        let core = owner("@org/core", OwnerType::Team);
+        let alice = owner("@alice", OwnerType::User);
+        let file = |path: &str, owners: Vec<Owner>, tags: &[&str]| FileEntry {
+            path: PathBuf::from(path),
+            owners,
+            tags: tags.iter().map(|tag| Tag(tag.to_string())).collect(),
+            inline_override: false,
+        };
+        let files = vec![
+            file("src/api.rs", vec![core.clone()], &["backend", "api"]),
+            file("src/db.rs", vec![core.clone(), alice.clone()], &["backend"]),
+            file("README.md", vec![alice.clone()], &[]),
+        ];
+
+        let owner_tags = tags_by_owner(&files);
+        assert_eq!(tag_list(owner_tags.get(&core)), "api, backend");
+        assert_eq!(tag_list(owner_tags.get(&alice)), "backend");
+
+        let core_files = vec![PathBuf::from("src/api.rs"), PathBuf::from("src/db.rs")];
+        let json = owners_json(&[(&core, &core_files)], None, Some(&owner_tags));
+        assert_eq!(json[0]["tags"], serde_json::json!(["api", "backend"]));
+        let json = owners_json(&[(&core, &core_files)], None, None);
+        assert!(json[0].get("tags").is_none());
+    }
+
+    #[test]
+    fn test_owners_json_type_is_lowercase() {
+        let core = owner("@org/core", OwnerType::Team);
+        let files = vec![PathBuf::from("src/lib.rs")];
+
+        let json = owners_json(&[(&core, &files)], None, None);
+        assert_eq!(json[0]["type"], "team");
+        assert!(serde_json::to_string(&json)
+            .unwrap()
+            .contains(r#""type":"team""#));
+    }
+
+    #[test]
+    fn test_owners_filtered_and_grouped_by_type() {
+        let owners_map = HashMap::from([
+            (
+                owner("@org/core", OwnerType::Team),
+                vec![PathBuf::from("a.rs"), PathBuf::from("b.rs")],
+            ),
+            (
+                owner("@alice", OwnerType::User),
+                vec![PathBuf::from("c.rs")],
+            ),
+            (
+                owner("@org/docs", OwnerType::Team),
+                vec![PathBuf::from("d.md")],
+            ),
+        ]);
+
+        let teams = owners_by_file_count(&owners_map, Some(&OwnerType::Team));
+        let identifiers: Vec<&str> = teams.iter().map(|(o, _)| o.identifier.as_str()).collect();
+        assert_eq!(identifiers, vec!["@org/core", "@org/docs"]);
+
+        let all = owners_by_file_count(&owners_map, None);
+        let groups = group_by_owner_type(&all);
+        assert_eq!(groups.keys().collect::<Vec<_>>(), vec!["Team", "User"]);
+        assert_eq!(groups["Team"].len(), 2);
+        assert_eq!(groups["User"][0].0.identifier, "@alice");
+    }
+
+    #[test]
+    fn test_sort_owners_by_type() {
+        let owners_map = HashMap::from([
+            (
+                owner("bob@corp.com", OwnerType::Email),
+                vec![PathBuf::from("a.rs")],
+            ),
+            (
+                owner("@alice", OwnerType::User),
+                vec![PathBuf::from("b.rs"), PathBuf::from("c.rs")],
+            ),
+            (
+                owner("@org/core", OwnerType::Team),
+                vec![PathBuf::from("d.rs")],
+            ),
+            (
+                owner("@org/docs", OwnerType::Team),
+                vec![
+                    PathBuf::from("e.md"),
+                    PathBuf::from("f.md"),
+                    PathBuf::from("g.md"),
+                ],
+            ),
+            (
+                owner("alice@corp.com", OwnerType::Email),
+                vec![PathBuf::from("h.rs"), PathBuf::from("i.rs")],
+            ),
+        ]);
+        let identifiers = |sort: OwnerSort| {
+            let mut owners = owners_by_file_count(&owners_map, None);
+            sort_owners(&mut owners, sort);
+            owners
+                .iter()
+                .map(|(o, _)| o.identifier.clone())
+                .collect::<Vec<_>>()
+        };
+
+        // Teams, users, then emails, each with the most files first
+        assert_eq!(
+            identifiers(OwnerSort::Type),
+            vec![
+                "@org/docs",
+                "@org/core",
+                "@alice",
+                "alice@corp.com",
+                "bob@corp.com"
+            ]
+        );
+        assert_eq!(
+            identifiers(OwnerSort::Name),
+            vec![
+                "@alice",
+                "@org/core",
+                "@org/docs",
+                "alice@corp.com",
+                "bob@corp.com"
+            ]
+        );
+        assert_eq!(identifiers(OwnerSort::Files)[0], "@org/docs");
+    }
+
+    #[test]
+    fn test_email_domain_breakdown() {
+        let owners = [
+            owner("alice@corp.com", OwnerType::Email),
+            owner("bob@Corp.com", OwnerType::Email),
+            owner("contractor@vendor.io", OwnerType::Email),
+            owner("@org/core", OwnerType::Team),
+        ];
+
+        let breakdown = email_domain_breakdown(&owners);
+        assert_eq!(
+            breakdown,
+            HashMap::from([("corp.com".to_string(), 2), ("vendor.io".to_string(), 1)])
+        );
+    }
+
+    #[test]
+    fn test_unknown_owner_declarations() {
+        let entry = |source: &str, line_number: usize, owners: Vec<Owner>| CodeownersEntry {
+            source_file: PathBuf::from(source),
+            line_number,
+            pattern: "*".to_string(),
+            owners,
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        };
+        let entries = vec![
+            entry(
+                "/repo/CODEOWNERS",
+                0,
+                vec![
+                    owner("@org/core", OwnerType::Team),
+                    owner("org/core", OwnerType::Unknown),
+                ],
+            ),
+            entry(
+                "/repo/docs/CODEOWNERS",
+                3,
+                vec![
+                    owner("alice@example.com", OwnerType::Email),
+                    owner("org/core", OwnerType::Unknown),
+                ],
+            ),
+        ];
+
+        let unknown = unknown_owner_declarations(&entries, std::path::Path::new("/repo"));
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(
+            unknown["org/core"],
+            vec!["CODEOWNERS:0", "docs/CODEOWNERS:3"]
+        );
+    }
+
+    #[test]
+    fn test_suggest_owner_for_typo() {
+        let frontend = owner("@frontend", OwnerType::User);
+        let backend = owner("@backend", OwnerType::User);
+        let core = owner("@org/core", OwnerType::Team);
+        let known = vec![&backend, &frontend, &core];
+
+        assert_eq!(suggest_owner("@frontnd", &known), Some(&frontend));
+        assert_eq!(suggest_owner("org/Core", &known), Some(&core));
+        assert_eq!(suggest_owner("@infra", &known), None);
+    }
+
+    #[test]
+    fn test_orphaned_owner_whose_rule_never_wins() {
+        let entry = |line_number: usize, pattern: &str, owners: &[&Owner]| CodeownersEntry {
+            source_file: PathBuf::from("CODEOWNERS"),
+            line_number,
+            pattern: pattern.to_string(),
+            owners: owners.iter().map(|owner| (*owner).clone()).collect(),
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        };
+        let alice = owner("@alice", OwnerType::User);
+        let rust = owner("@org/rust", OwnerType::Team);
+        let docs = owner("@org/docs", OwnerType::Team);
+        let nobody = owner("NOOWNER", OwnerType::Unowned);
+        // The later `*.rs` rule shadows @alice's, and nothing matches `*.md`
+        let entries = vec![
+            entry(0, "*.rs", &[&alice]),
+            entry(1, "*.rs", &[&rust]),
+            entry(2, "*.md", &[&docs]),
+            entry(3, "vendor/", &[&nobody]),
+        ];
+        let files = vec![FileEntry {
+            path: PathBuf::from("src/main.rs"),
+            owners: vec![rust.clone()],
+            tags: vec![],
+            inline_override: false,
+        }];
+        let owners_map = build_owners_map(&files, collect_owners(&entries));
+
+        assert_eq!(
+            orphaned_owners(&entries, &owners_map, &IdentityMap::default()),
+            vec![alice, docs]
+        );
+    }
+
+    #[test]
+    fn test_orphaned_owner_ignores_email_merged_into_handle() -> Result<()> {
+        let entry = |line_number: usize, pattern: &str, owner: &Owner| CodeownersEntry {
+            source_file: PathBuf::from("CODEOWNERS"),
+            line_number,
+            pattern: pattern.to_string(),
+            owners: vec![owner.clone()],
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        };
+        let email = owner("alice@corp.com", OwnerType::Email);
+        let alice = owner("@alice", OwnerType::User);
+        let entries = vec![entry(0, "*.rs", &email), entry(1, "*.md", &alice)];
+        let files = vec![
+            FileEntry {
+                path: PathBuf::from("a.rs"),
+                owners: vec![email.clone()],
+                tags: vec![],
+                inline_override: false,
+            },
+            FileEntry {
+                path: PathBuf::from("b.md"),
+                owners: vec![alice.clone()],
+                tags: vec![],
+                inline_override: false,
+            },
+        ];
+        let identities = IdentityMap::new(HashMap::from([(
+            "alice@corp.com".to_string(),
+            "@alice".to_string(),
+        )]))?;
+        let owners_map =
+            identities.merge_owners_map(build_owners_map(&files, collect_owners(&entries)), &files);
+
+        assert!(orphaned_owners(&entries, &owners_map, &identities).is_empty());
+
+        Ok(())
+    }
 }
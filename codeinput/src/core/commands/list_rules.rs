@@ -1,8 +1,13 @@
 use crate::{
-    core::{cache::sync_cache, display::truncate_string, types::OutputFormat},
-    utils::error::{Error, Result},
+    core::{
+        cache::sync_cache,
+        display::{stdout_writer, truncate_string},
+        html,
+        types::{CodeownersEntry, OutputFormat},
+    },
+    utils::error::{Error, ErrorKind, Result},
 };
-use std::io::{self, Write};
+use std::io::Write;
 use tabled::{Table, Tabled};
 
 #[derive(Tabled)]
@@ -20,11 +25,19 @@ struct RuleDisplay {
 }
 
 /// Display CODEOWNERS rules from the cache
-pub fn run(format: &OutputFormat, cache_file: Option<&std::path::Path>) -> Result<()> {
+///
+/// Rules are shown as parsed, in order, without resolving them against files.
+pub fn run(
+    repo: Option<&std::path::Path>, format: &OutputFormat, cache_file: Option<&std::path::Path>,
+) -> Result<()> {
+    // Repository path
+    let repo = repo.unwrap_or_else(|| std::path::Path::new("."));
+
     // Load the cache
-    let cache = sync_cache(std::path::Path::new("."), cache_file)?;
+    let cache = sync_cache(repo, cache_file)?;
 
     // Process the rules from the cache
+    let mut stdout = stdout_writer();
     match format {
         OutputFormat::Text => {
             // Create table data
@@ -89,31 +102,22 @@ pub fn run(format: &OutputFormat, cache_file: Option<&std::path::Path>) -> Resul
                 ))
                 .with(tabled::settings::Padding::new(1, 1, 0, 0));
 
-            println!("{}", table);
-            println!("Total: {} rules", cache.entries.len());
+            writeln!(stdout, "{}", table)?;
+            writeln!(stdout, "Total: {} rules", cache.entries.len())?;
         }
         OutputFormat::Json => {
-            // Convert to a more friendly JSON structure
-            let rules_data: Vec<_> = cache
-                .entries
-                .iter()
-                .map(|entry| {
-                    serde_json::json!({
-                        "pattern": entry.pattern,
-                        "source_file": entry.source_file.to_string_lossy().to_string(),
-                        "line_number": entry.line_number,
-                        "owners": entry.owners.iter().map(|o| {
-                            serde_json::json!({
-                                "identifier": o.identifier,
-                                "type": o.owner_type.to_string()
-                            })
-                        }).collect::<Vec<_>>(),
-                        "tags": entry.tags.iter().map(|t| &t.0).collect::<Vec<_>>()
-                    })
-                })
-                .collect();
-
-            println!("{}", serde_json::to_string_pretty(&rules_data).unwrap());
+            let rules_data = rules_json(&cache.entries);
+            writeln!(
+                stdout,
+                "{}",
+                serde_json::to_string_pretty(&rules_data).unwrap()
+            )?;
+        }
+        OutputFormat::Dot => {
+            return Err(Error::with_kind(
+                ErrorKind::Validation,
+                "Rule list is not available as dot",
+            ));
         }
         OutputFormat::Bincode => {
             let encoded =
@@ -121,11 +125,110 @@ pub fn run(format: &OutputFormat, cache_file: Option<&std::path::Path>) -> Resul
                     .map_err(|e| Error::new(&format!("Serialization error: {}", e)))?;
 
             // Write raw binary bytes to stdout
-            io::stdout()
+            stdout
                 .write_all(&encoded)
                 .map_err(|e| Error::new(&format!("IO error: {}", e)))?;
         }
+        OutputFormat::Html => {
+            let rows: Vec<Vec<String>> = cache
+                .entries
+                .iter()
+                .map(|entry| {
+                    vec![
+                        entry.pattern.clone(),
+                        entry.source_file.to_string_lossy().to_string(),
+                        entry.line_number.to_string(),
+                        entry
+                            .owners
+                            .iter()
+                            .map(|o| o.identifier.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        entry
+                            .tags
+                            .iter()
+                            .map(|t| t.0.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    ]
+                })
+                .collect();
+            write!(
+                stdout,
+                "{}",
+                html::render_table(
+                    "Rules",
+                    &["Pattern", "Source", "Line", "Owners", "Tags"],
+                    &rows
+                )
+            )?;
+        }
     }
 
-    Ok(())
+    stdout.finish()
+}
+
+/// Rules as friendlier JSON objects, one per entry in order
+fn rules_json(entries: &[CodeownersEntry]) -> Vec<serde_json::Value> {
+    entries
+        .iter()
+        .map(|entry| {
+            serde_json::json!({
+                "pattern": entry.pattern,
+                "source_file": entry.source_file.to_string_lossy().to_string(),
+                "line_number": entry.line_number,
+                "owners": entry.owners.iter().map(|o| {
+                    serde_json::json!({
+                        "identifier": o.identifier,
+                        "type": o.owner_type.to_string()
+                    })
+                }).collect::<Vec<_>>(),
+                "tags": entry.tags.iter().map(|t| &t.0).collect::<Vec<_>>(),
+                "comment": entry.comment
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_codeowners_str;
+    use std::path::Path;
+
+    #[test]
+    fn test_rules_json_lists_every_parsed_entry() -> Result<()> {
+        let content = "\
+# Default owners
+* @org/core
+
+/src/ @org/backend #api
+docs/**/*.md docs@example.com #docs # keep in sync
+vendor/ NOOWNER
+";
+        let entries = parse_codeowners_str(content, Path::new("CODEOWNERS"))?;
+        let rules = rules_json(&entries);
+
+        assert_eq!(rules.len(), 4);
+        let listed: Vec<(&str, u64)> = rules
+            .iter()
+            .map(|rule| {
+                (
+                    rule["pattern"].as_str().unwrap(),
+                    rule["line_number"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            vec![("*", 1), ("/src/", 3), ("docs/**/*.md", 4), ("vendor/", 5)]
+        );
+        assert!(rules.iter().all(|rule| rule["source_file"] == "CODEOWNERS"));
+        assert_eq!(rules[1]["owners"][0]["identifier"], "@org/backend");
+        assert_eq!(rules[1]["tags"], serde_json::json!(["api"]));
+        assert_eq!(rules[2]["owners"][0]["identifier"], "docs@example.com");
+        assert_eq!(rules[2]["comment"], "keep in sync");
+
+        Ok(())
+    }
 }
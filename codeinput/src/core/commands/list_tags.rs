@@ -1,8 +1,19 @@
 use crate::{
-    core::{cache::sync_cache, display::truncate_string, types::OutputFormat},
-    utils::error::{Error, Result},
+    core::{
+        cache::sync_cache,
+        common::collect_tags,
+        display::{
+            encode_bincode_output, insert_file_list, remove_header, sample_file_names,
+            stdout_writer, truncate_string, write_json_pretty, JsonEnvelope,
+        },
+        html,
+        types::{BincodePayload, CodeownersEntry, FileEntry, OutputFormat, Tag},
+    },
+    utils::error::{Error, ErrorKind, Result},
 };
-use std::io::{self, Write};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Write;
+use std::path::PathBuf;
 use tabled::{Table, Tabled};
 
 #[derive(Tabled)]
@@ -15,9 +26,28 @@ struct TagDisplay {
     sample_files: String,
 }
 
+#[derive(Tabled)]
+struct CooccurrenceDisplay {
+    #[tabled(rename = "Tag")]
+    first: String,
+    #[tabled(rename = "Co-occurs With")]
+    second: String,
+    #[tabled(rename = "Files")]
+    file_count: usize,
+}
+
+/// Number of files carrying both tags of a pair, keyed by the pair in name order
+type Cooccurrence<'a> = BTreeMap<(&'a Tag, &'a Tag), usize>;
+
 /// Audit and analyze tag usage across CODEOWNERS files
+///
+/// `max_files` caps the sample files shown per tag in text output (default 5)
+/// and the file list embedded in JSON output (default unlimited). With
+/// `json_envelope`, JSON output is wrapped with the query and the number of tags.
+/// With `no_header`, text output has no header row and no total line.
 pub fn run(
-    repo: Option<&std::path::Path>, format: &OutputFormat, cache_file: Option<&std::path::Path>,
+    repo: Option<&std::path::Path>, max_files: Option<usize>, format: &OutputFormat,
+    cache_file: Option<&std::path::Path>, json_envelope: bool, no_header: bool,
 ) -> Result<()> {
     // Repository path
     let repo = repo.unwrap_or_else(|| std::path::Path::new("."));
@@ -30,32 +60,15 @@ pub fn run(
     tags_with_counts.sort_by(|a, b| b.1.len().cmp(&a.1.len()));
 
     // Process the tags from the cache
+    let mut stdout = stdout_writer();
     match format {
         OutputFormat::Text => {
             // Create table data
             let table_data: Vec<TagDisplay> = tags_with_counts
                 .iter()
                 .map(|(tag, paths)| {
-                    // Prepare sample file list - show filenames only, not full paths
-                    let file_samples = if paths.is_empty() {
-                        "None".to_string()
-                    } else {
-                        let samples: Vec<_> = paths
-                            .iter()
-                            .take(5) // Show max 5 files as samples
-                            .map(|p| {
-                                p.file_name()
-                                    .map(|f| f.to_string_lossy().to_string())
-                                    .unwrap_or_else(|| p.to_string_lossy().to_string())
-                            })
-                            .collect();
-
-                        let mut display = samples.join(", ");
-                        if paths.len() > 5 {
-                            display.push_str(&format!(" (+{})", paths.len() - 5));
-                        }
-                        display
-                    };
+                    // Sample file list - show filenames only, not full paths
+                    let file_samples = sample_file_names(paths, max_files.unwrap_or(5));
 
                     TagDisplay {
                         name: truncate_string(&tag.0, 30),
@@ -80,35 +93,317 @@ pub fn run(
                     terminal_width.saturating_sub(4),
                 ))
                 .with(tabled::settings::Padding::new(1, 1, 0, 0));
+            if no_header {
+                remove_header(&mut table);
+            }
 
-            println!("{}", table);
-            println!("Total: {} tags", cache.tags_map.len());
+            writeln!(stdout, "{}", table)?;
+            if !no_header {
+                writeln!(stdout, "Total: {} tags", cache.tags_map.len())?;
+            }
         }
         OutputFormat::Json => {
             // Convert to a more friendly JSON structure
-            let tags_data: Vec<_> = tags_with_counts.iter()
+            let tags_data: Vec<_> = tags_with_counts
+                .iter()
                 .map(|(tag, paths)| {
-                    serde_json::json!({
+                    let mut value = serde_json::json!({
                         "name": tag.0,
                         "file_count": paths.len(),
-                        "files": paths.iter().map(|p| p.to_string_lossy().to_string()).collect::<Vec<_>>()
-                    })
+                    });
+                    insert_file_list(&mut value, paths, max_files);
+                    value
                 })
                 .collect();
 
-            println!("{}", serde_json::to_string_pretty(&tags_data).unwrap());
+            if json_envelope {
+                let envelope = JsonEnvelope {
+                    query: match max_files {
+                        Some(max_files) => serde_json::json!({ "max_files": max_files }),
+                        None => serde_json::json!({}),
+                    },
+                    total: tags_data.len(),
+                    results: &tags_data,
+                };
+                write_json_pretty(&mut stdout, &envelope)?;
+            } else {
+                write_json_pretty(&mut stdout, &tags_data)?;
+            }
+        }
+        OutputFormat::Dot => {
+            return Err(Error::with_kind(
+                ErrorKind::Validation,
+                "Tag list is not available as dot",
+            ));
         }
         OutputFormat::Bincode => {
-            let encoded =
-                bincode::serde::encode_to_vec(&tags_with_counts, bincode::config::standard())
-                    .map_err(|e| Error::new(&format!("Serialization error: {}", e)))?;
+            let encoded = encode_bincode_output(BincodePayload::Tags, &tags_with_counts)?;
 
-            // Write raw binary bytes to stdout
-            io::stdout()
+            // Write framed binary bytes to stdout
+            stdout
                 .write_all(&encoded)
                 .map_err(|e| Error::new(&format!("IO error: {}", e)))?;
         }
+        OutputFormat::Html => {
+            let rows: Vec<Vec<String>> = tags_with_counts
+                .iter()
+                .map(|(tag, paths)| vec![tag.0.clone(), paths.len().to_string()])
+                .collect();
+            write!(
+                stdout,
+                "{}",
+                html::render_table("Tags", &["Tag", "Files"], &rows)
+            )?;
+        }
+    }
+
+    stdout.finish()
+}
+
+/// Display tags declared in CODEOWNERS files that apply to no file
+///
+/// A tag is unused when every rule carrying it is shadowed or matches nothing.
+pub fn run_unused(
+    repo: Option<&std::path::Path>, format: &OutputFormat, cache_file: Option<&std::path::Path>,
+) -> Result<()> {
+    // Repository path
+    let repo = repo.unwrap_or_else(|| std::path::Path::new("."));
+
+    // Load the cache
+    let cache = sync_cache(repo, cache_file)?;
+
+    let unused = unused_tags(&cache.entries, &cache.tags_map);
+
+    let mut stdout = stdout_writer();
+    match format {
+        OutputFormat::Text => {
+            for tag in &unused {
+                writeln!(stdout, "{}", tag.0)?;
+            }
+            writeln!(stdout, "Total: {} unused tags", unused.len())?;
+        }
+        OutputFormat::Json => {
+            let names: Vec<&str> = unused.iter().map(|tag| tag.0.as_str()).collect();
+            write_json_pretty(&mut stdout, &names)?;
+        }
+        OutputFormat::Html => {
+            let rows: Vec<Vec<String>> = unused.iter().map(|tag| vec![tag.0.clone()]).collect();
+            write!(
+                stdout,
+                "{}",
+                html::render_table("Unused Tags", &["Tag"], &rows)
+            )?;
+        }
+        OutputFormat::Bincode | OutputFormat::Dot => {
+            return Err(Error::with_kind(
+                ErrorKind::Validation,
+                &format!("Unused tag report is not available as {}", format),
+            ));
+        }
+    }
+
+    stdout.finish()
+}
+
+/// Display how many files each pair of tags is applied to together
+///
+/// With `OutputFormat::Dot`, tags are rendered as Graphviz nodes joined by
+/// edges weighted with the number of files they share, e.g. for `dot -Tsvg`.
+pub fn run_cooccurrence(
+    repo: Option<&std::path::Path>, format: &OutputFormat, cache_file: Option<&std::path::Path>,
+) -> Result<()> {
+    // Repository path
+    let repo = repo.unwrap_or_else(|| std::path::Path::new("."));
+
+    // Load the cache
+    let cache = sync_cache(repo, cache_file)?;
+
+    let cooccurrence = tag_cooccurrence(&cache.files);
+    let mut pairs: Vec<_> = cooccurrence.iter().collect();
+    pairs.sort_by(|a, b| b.1.cmp(a.1));
+
+    let mut stdout = stdout_writer();
+    match format {
+        OutputFormat::Text => {
+            let table_data: Vec<CooccurrenceDisplay> = pairs
+                .iter()
+                .map(|((first, second), count)| CooccurrenceDisplay {
+                    first: truncate_string(&first.0, 30),
+                    second: truncate_string(&second.0, 30),
+                    file_count: **count,
+                })
+                .collect();
+
+            let mut table = Table::new(table_data);
+            table
+                .with(tabled::settings::Style::modern())
+                .with(tabled::settings::Padding::new(1, 1, 0, 0));
+
+            writeln!(stdout, "{}", table)?;
+            writeln!(stdout, "Total: {} tag pairs", pairs.len())?;
+        }
+        OutputFormat::Json => {
+            let pairs_data: Vec<_> = pairs
+                .iter()
+                .map(|((first, second), count)| {
+                    serde_json::json!({
+                        "tags": [first.0, second.0],
+                        "file_count": count,
+                    })
+                })
+                .collect();
+            write_json_pretty(&mut stdout, &pairs_data)?;
+        }
+        OutputFormat::Html => {
+            let rows: Vec<Vec<String>> = pairs
+                .iter()
+                .map(|((first, second), count)| {
+                    vec![first.0.clone(), second.0.clone(), count.to_string()]
+                })
+                .collect();
+            write!(
+                stdout,
+                "{}",
+                html::render_table(
+                    "Tag Co-occurrence",
+                    &["Tag", "Co-occurs With", "Files"],
+                    &rows
+                )
+            )?;
+        }
+        OutputFormat::Dot => {
+            write!(
+                stdout,
+                "{}",
+                cooccurrence_dot(cache.tags_map.keys(), &cooccurrence)
+            )?;
+        }
+        OutputFormat::Bincode => {
+            return Err(Error::with_kind(
+                ErrorKind::Validation,
+                "Tag co-occurrence is not available as bincode",
+            ));
+        }
+    }
+
+    stdout.finish()
+}
+
+/// Count the files carrying each pair of distinct tags
+fn tag_cooccurrence(files: &[FileEntry]) -> Cooccurrence<'_> {
+    let mut cooccurrence = Cooccurrence::new();
+    for file in files {
+        let tags: BTreeSet<&Tag> = file.tags.iter().collect();
+        for (i, first) in tags.iter().enumerate() {
+            for second in tags.iter().skip(i + 1) {
+                *cooccurrence.entry((*first, *second)).or_default() += 1;
+            }
+        }
+    }
+    cooccurrence
+}
+
+/// Render tags and their co-occurrence as an undirected Graphviz graph
+///
+/// Every tag becomes a node, isolated or not; nodes and edges are sorted by name.
+fn cooccurrence_dot<'a>(
+    tags: impl IntoIterator<Item = &'a Tag>, cooccurrence: &Cooccurrence,
+) -> String {
+    let tags: BTreeSet<&Tag> = tags.into_iter().collect();
+    let mut dot = String::from("graph tags {\n");
+    for tag in tags {
+        dot.push_str(&format!("  {};\n", dot_id(&tag.0)));
+    }
+    for ((first, second), count) in cooccurrence {
+        dot.push_str(&format!(
+            "  {} -- {} [weight={}, label=\"{}\"];\n",
+            dot_id(&first.0),
+            dot_id(&second.0),
+            count,
+            count
+        ));
     }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Quote `name` as a DOT identifier
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Tags declared in `entries` that are not applied to any file, sorted by name
+fn unused_tags(entries: &[CodeownersEntry], tags_map: &HashMap<Tag, Vec<PathBuf>>) -> Vec<Tag> {
+    let mut unused: Vec<Tag> = collect_tags(entries)
+        .into_iter()
+        .filter(|tag| tags_map.get(tag).is_none_or(|paths| paths.is_empty()))
+        .collect();
+    unused.sort_by(|a, b| a.0.cmp(&b.0));
+    unused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{tag_resolver::build_tags_map, types::FileEntry};
 
-    Ok(())
+    #[test]
+    fn test_unused_tags_on_non_matching_pattern() {
+        let entry = |pattern: &str, tag: &str| CodeownersEntry {
+            source_file: PathBuf::from("CODEOWNERS"),
+            line_number: 0,
+            pattern: pattern.to_string(),
+            owners: vec![],
+            tags: vec![Tag(tag.to_string())],
+            comment: None,
+            raw: String::new(),
+        };
+        let entries = vec![entry("*.rs", "rust"), entry("*.go", "golang")];
+
+        // Only the Rust rule matches a file
+        let files = vec![FileEntry {
+            path: PathBuf::from("src/main.rs"),
+            owners: vec![],
+            tags: vec![Tag("rust".to_string())],
+            inline_override: false,
+        }];
+        let tags_map = build_tags_map(&files, collect_tags(&entries));
+
+        assert_eq!(
+            unused_tags(&entries, &tags_map),
+            vec![Tag("golang".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_cooccurrence_dot_graph() {
+        let tag = |name: &str| Tag(name.to_string());
+        let file = |path: &str, tags: &[&str]| FileEntry {
+            path: PathBuf::from(path),
+            owners: vec![],
+            tags: tags.iter().map(|name| tag(name)).collect(),
+            inline_override: false,
+        };
+        let files = vec![
+            file("src/api.rs", &["backend", "api"]),
+            file("src/db.rs", &["backend", "api", "storage"]),
+            file("web/app.ts", &["frontend"]),
+        ];
+
+        let cooccurrence = tag_cooccurrence(&files);
+        let all_tags = [tag("api"), tag("backend"), tag("frontend"), tag("storage")];
+        assert_eq!(
+            cooccurrence_dot(&all_tags, &cooccurrence),
+            "graph tags {\n  \
+             \"api\";\n  \
+             \"backend\";\n  \
+             \"frontend\";\n  \
+             \"storage\";\n  \
+             \"api\" -- \"backend\" [weight=2, label=\"2\"];\n  \
+             \"api\" -- \"storage\" [weight=1, label=\"1\"];\n  \
+             \"backend\" -- \"storage\" [weight=1, label=\"1\"];\n\
+             }\n"
+        );
+        assert_eq!(dot_id("say \"hi\""), "\"say \\\"hi\\\"\"");
+    }
 }
@@ -0,0 +1,95 @@
+use crate::{
+    core::{
+        cache::sync_cache,
+        commands::resolve::{read_paths_from, resolve_paths},
+        display::stdout_writer,
+        types::{Owner, OwnerType},
+    },
+    utils::error::Result,
+};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+
+/// Print the owners of a list of paths as one line of `@` mentions
+///
+/// `paths_from` is read as for `resolve`: a file with one path per line, or
+/// `-` for stdin. Every user and team owning any of the paths is mentioned
+/// once, in order of first appearance, e.g. `@org/backend @alice`. Email
+/// owners, `NOOWNER` and owners of unknown type can't be mentioned and are
+/// left out.
+pub fn run(paths_from: &Path, repo: Option<&Path>, cache_file: Option<&Path>) -> Result<()> {
+    // Repository path
+    let repo = repo.unwrap_or_else(|| Path::new("."));
+
+    let paths = read_paths_from(paths_from)?;
+
+    // Load the cache
+    let cache = sync_cache(repo, cache_file)?;
+
+    let resolved = resolve_paths(&cache, repo, &paths);
+    let mut stdout = stdout_writer();
+    writeln!(
+        stdout,
+        "{}",
+        mention_line(resolved.iter().flat_map(|(_, owners, _)| owners))
+    )?;
+
+    stdout.finish()
+}
+
+/// Space-joined mentions of the users and teams among `owners`, without duplicates
+fn mention_line<'a>(owners: impl IntoIterator<Item = &'a Owner>) -> String {
+    let mut seen = HashSet::new();
+    owners
+        .into_iter()
+        .filter(|owner| matches!(owner.owner_type, OwnerType::User | OwnerType::Team))
+        .filter(|owner| seen.insert(owner.identifier.as_str()))
+        .map(|owner| owner.identifier.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        parse::analyze_repo,
+        types::{OwnerMerge, PatternAnchor},
+    };
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_mentions_are_deduped_across_paths() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+
+        fs::write(
+            base_path.join("CODEOWNERS"),
+            "*.rs @org/backend @alice\n\
+             /docs/ @alice docs@example.com\n\
+             /vendor/ NOOWNER\n",
+        )?;
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::write(base_path.join("src").join("main.rs"), "fn main() {}")?;
+        fs::write(base_path.join("src").join("lib.rs"), "")?;
+        let cache = analyze_repo(base_path, false, OwnerMerge::Winner, PatternAnchor::FileDir)?;
+
+        let paths: Vec<PathBuf> = ["src/main.rs", "docs/guide.md", "src/lib.rs", "vendor/x.c"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let resolved = resolve_paths(&cache, base_path, &paths);
+
+        assert_eq!(
+            mention_line(resolved.iter().flat_map(|(_, owners, _)| owners)),
+            "@org/backend @alice"
+        );
+        assert_eq!(mention_line(&[]), "");
+
+        Ok(())
+    }
+}
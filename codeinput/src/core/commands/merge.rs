@@ -0,0 +1,309 @@
+use crate::{
+    core::{
+        cache::{load_cache, store_cache},
+        display::stdout_writer,
+        identity::IdentityMap,
+        owner_resolver::build_owners_map,
+        tag_resolver::build_tags_map,
+        types::{CacheEncoding, CodeownersCache, CodeownersEntry, FileEntry, Owner, Tag},
+    },
+    utils::error::{Error, ErrorKind, Result},
+};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Merge several caches into one and store it in `output`
+///
+/// Files are deduplicated by path and rules by CODEOWNERS file and line, so
+/// an edited pattern replaces the old one; on conflict the cache listed later wins.
+pub fn run(caches: &[PathBuf], output: &Path, encoding: CacheEncoding) -> Result<()> {
+    if caches.is_empty() {
+        return Err(Error::with_kind(
+            ErrorKind::Validation,
+            "At least one cache file is required",
+        ));
+    }
+
+    let loaded = caches
+        .iter()
+        .map(|path| {
+            load_cache(path).map_err(|e| {
+                Error::with_kind(
+                    e.kind,
+                    &format!("Failed to load cache from {}: {}", path.display(), e),
+                )
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let merged = merge_caches(loaded)?;
+    store_cache(&merged, output, encoding)?;
+
+    let mut stdout = stdout_writer();
+    writeln!(
+        stdout,
+        "Merged {} caches into {}: {} rules, {} files",
+        caches.len(),
+        output.display(),
+        merged.entries.len(),
+        merged.files.len()
+    )?;
+
+    stdout.finish()
+}
+
+/// Union the given caches, later caches taking precedence
+///
+/// The owner and tag maps are rebuilt from the merged files, and the hash is
+/// derived from the hashes of the inputs.
+pub(crate) fn merge_caches(caches: Vec<CodeownersCache>) -> Result<CodeownersCache> {
+    let case_insensitive = caches.first().is_some_and(|cache| cache.case_insensitive);
+    if caches
+        .iter()
+        .any(|cache| cache.case_insensitive != case_insensitive)
+    {
+        return Err(Error::with_kind(
+            ErrorKind::Validation,
+            "Cannot merge caches built with and without --ignore-case",
+        ));
+    }
+    let owner_merge = caches
+        .first()
+        .map(|cache| cache.owner_merge)
+        .unwrap_or_default();
+    if caches.iter().any(|cache| cache.owner_merge != owner_merge) {
+        return Err(Error::with_kind(
+            ErrorKind::Validation,
+            "Cannot merge caches built with different --owner-merge modes",
+        ));
+    }
+    let anchor = caches.first().map(|cache| cache.anchor).unwrap_or_default();
+    if caches.iter().any(|cache| cache.anchor != anchor) {
+        return Err(Error::with_kind(
+            ErrorKind::Validation,
+            "Cannot merge caches built with different --anchor modes",
+        ));
+    }
+
+    // A merged cache covers the files of every input
+    let follow_links = caches.iter().any(|cache| cache.follow_links);
+    let dedupe_paths = caches.iter().any(|cache| cache.dedupe_paths);
+    // Only one commit can be diffed against when updating the merged cache
+    let head_commit = match caches.split_first() {
+        Some((first, rest))
+            if rest
+                .iter()
+                .all(|cache| cache.head_commit == first.head_commit) =>
+        {
+            first.head_commit.clone()
+        }
+        _ => None,
+    };
+
+    let mut hasher = Sha256::new();
+    let mut entries: Vec<CodeownersEntry> = Vec::new();
+    let mut entry_index: HashMap<(PathBuf, usize), usize> = HashMap::new();
+    let mut files: Vec<FileEntry> = Vec::new();
+    let mut file_index: HashMap<PathBuf, usize> = HashMap::new();
+    let mut owners: HashSet<Owner> = HashSet::new();
+    let mut tags: HashSet<Tag> = HashSet::new();
+    let mut owner_aliases: HashMap<String, String> = HashMap::new();
+    let mut identity_map: HashMap<String, String> = HashMap::new();
+
+    for cache in caches {
+        hasher.update(cache.hash);
+        owner_aliases.extend(cache.owner_aliases);
+        identity_map.extend(cache.identity_map);
+        owners.extend(cache.owners_map.into_keys());
+        tags.extend(cache.tags_map.into_keys());
+
+        // A rule is replaced by whatever is at its line now, even with another pattern
+        for entry in cache.entries {
+            let key = (entry.source_file.clone(), entry.line_number);
+            match entry_index.get(&key) {
+                Some(&index) => entries[index] = entry,
+                None => {
+                    entry_index.insert(key, entries.len());
+                    entries.push(entry);
+                }
+            }
+        }
+
+        for file in cache.files {
+            match file_index.get(&file.path) {
+                Some(&index) => files[index] = file,
+                None => {
+                    file_index.insert(file.path.clone(), files.len());
+                    files.push(file);
+                }
+            }
+        }
+    }
+
+    let mut owners_map = build_owners_map(&files, owners.into_iter().collect());
+    // Rebuilding the owner map undid the merges of the inputs' identity maps
+    if !identity_map.is_empty() {
+        owners_map = IdentityMap::new(identity_map.clone())?.merge_owners_map(owners_map, &files);
+    }
+    let tags_map = build_tags_map(&files, tags.into_iter().collect());
+
+    Ok(CodeownersCache {
+        hash: hasher.finalize().into(),
+        entries,
+        files,
+        owners_map,
+        tags_map,
+        case_insensitive,
+        owner_merge,
+        anchor,
+        follow_links,
+        dedupe_paths,
+        owner_aliases,
+        head_commit,
+        identity_map,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{OwnerMerge, PatternAnchor};
+    use tempfile::TempDir;
+
+    fn create_cache(codeowners: &str, owner: &str, tag: &str, paths: &[&str]) -> CodeownersCache {
+        let owner: Owner = owner.parse().unwrap();
+        let tag = Tag(tag.to_string());
+        let files: Vec<FileEntry> = paths
+            .iter()
+            .map(|path| FileEntry {
+                path: PathBuf::from(path),
+                owners: vec![owner.clone()],
+                tags: vec![tag.clone()],
+                inline_override: false,
+            })
+            .collect();
+        let entries = vec![CodeownersEntry {
+            source_file: PathBuf::from(codeowners),
+            line_number: 0,
+            pattern: "*".to_string(),
+            owners: vec![owner.clone()],
+            tags: vec![tag.clone()],
+            comment: None,
+            raw: String::new(),
+        }];
+
+        CodeownersCache {
+            hash: [0; 32],
+            owners_map: build_owners_map(&files, vec![owner]),
+            tags_map: build_tags_map(&files, vec![tag]),
+            entries,
+            files,
+            case_insensitive: false,
+            owner_merge: OwnerMerge::Winner,
+            anchor: PatternAnchor::FileDir,
+            follow_links: false,
+            dedupe_paths: false,
+            owner_aliases: HashMap::new(),
+            head_commit: None,
+            identity_map: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_merge_disjoint_caches() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let first = temp_dir.path().join("frontend.cache");
+        let second = temp_dir.path().join("backend.cache");
+        let output = temp_dir.path().join("merged.cache");
+
+        store_cache(
+            &create_cache(
+                "web/CODEOWNERS",
+                "@org/frontend",
+                "web",
+                &["web/app.tsx", "web/index.html"],
+            ),
+            &first,
+            CacheEncoding::Bincode,
+        )?;
+        store_cache(
+            &create_cache("api/CODEOWNERS", "@org/backend", "api", &["api/server.rs"]),
+            &second,
+            CacheEncoding::Json,
+        )?;
+
+        run(&[first, second], &output, CacheEncoding::Bincode)?;
+
+        let merged = load_cache(&output)?;
+        assert_eq!(merged.entries.len(), 2);
+        assert_eq!(merged.files.len(), 3);
+        assert_eq!(merged.owners_map.len(), 2);
+        assert_eq!(merged.tags_map.len(), 2);
+        let frontend = merged
+            .owners_map
+            .iter()
+            .find(|(owner, _)| owner.identifier == "@org/frontend")
+            .map(|(_, files)| files.len());
+        assert_eq!(frontend, Some(2));
+        assert_ne!(merged.hash, [0; 32]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_prefers_later_cache_for_same_path() -> Result<()> {
+        let merged = merge_caches(vec![
+            create_cache("CODEOWNERS", "@org/old", "old", &["src/lib.rs"]),
+            create_cache("CODEOWNERS", "@org/new", "new", &["src/lib.rs"]),
+        ])?;
+
+        assert_eq!(merged.entries.len(), 1);
+        assert_eq!(merged.entries[0].owners[0].identifier, "@org/new");
+        assert_eq!(merged.files.len(), 1);
+        assert_eq!(merged.files[0].owners[0].identifier, "@org/new");
+
+        // The replaced owner no longer owns any files
+        let old_files = merged
+            .owners_map
+            .iter()
+            .find(|(owner, _)| owner.identifier == "@org/old")
+            .map(|(_, files)| files.len());
+        assert_eq!(old_files, Some(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_replaces_rule_with_edited_pattern() -> Result<()> {
+        let old = create_cache("CODEOWNERS", "@org/team", "team", &["src/lib.rs"]);
+        let mut new = create_cache("CODEOWNERS", "@org/team", "team", &["src/lib.rs"]);
+        new.entries[0].pattern = "src/".to_string();
+
+        let merged = merge_caches(vec![old, new])?;
+        let patterns: Vec<&str> = merged.entries.iter().map(|e| e.pattern.as_str()).collect();
+        assert_eq!(patterns, vec!["src/"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_applies_identity_maps_of_inputs() -> Result<()> {
+        let web = create_cache("web/CODEOWNERS", "@alice", "web", &["web/app.tsx"]);
+        let mut api = create_cache("api/CODEOWNERS", "alice@corp.com", "api", &["api/main.rs"]);
+        api.identity_map = HashMap::from([("alice@corp.com".to_string(), "@alice".to_string())]);
+
+        let merged = merge_caches(vec![web, api])?;
+        assert_eq!(merged.identity_map.len(), 1);
+        let owners: Vec<&str> = merged
+            .owners_map
+            .keys()
+            .map(|owner| owner.identifier.as_str())
+            .collect();
+        assert_eq!(owners, vec!["@alice"]);
+        assert_eq!(merged.owners_map.values().next().unwrap().len(), 2);
+
+        Ok(())
+    }
+}
@@ -1,8 +1,18 @@
+pub mod audit;
+pub mod cache_info;
 pub mod config;
+pub mod contacts;
+pub mod dump;
+pub mod export;
+pub mod flatten;
 pub mod infer_owners;
 pub mod inspect;
 pub mod list_files;
 pub mod list_owners;
 pub mod list_rules;
 pub mod list_tags;
+pub mod mentions;
+pub mod merge;
 pub mod parse;
+pub mod resolve;
+pub mod verify_cache;
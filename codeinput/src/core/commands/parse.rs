@@ -1,18 +1,124 @@
 use crate::{
     core::{
-        cache::{build_cache, load_cache, store_cache},
-        common::{find_codeowners_files, find_files, get_repo_hash},
-        parser::parse_codeowners,
-        types::{CacheEncoding, CodeownersEntry},
+        cache::{
+            build_cache, load_cache, set_identity_map, set_owner_aliases, store_cache,
+            update_cache_files,
+        },
+        commands::merge::merge_caches,
+        common::{
+            collect_owners, collect_tags, dedupe_paths, filter_walked_paths, find_changed_files,
+            find_codeowners_files_skipping, find_files, find_files_changed_since,
+            format_walk_errors, get_repo_hash, head_commit, is_codeowners_file,
+            CODEOWNERS_IGNORE_FILE,
+        },
+        display::stdout_writer,
+        identity::IdentityMap,
+        parse::BuildSettings,
+        parser::{apply_owner_aliases, load_owner_aliases, parse_codeowners, parse_codeowners_str},
+        types::{CacheEncoding, CodeownersEntry, OwnerMerge, PatternAnchor},
+    },
+    utils::{
+        app_config::AppConfig,
+        error::{Error, Result},
     },
-    utils::{app_config::AppConfig, error::Result},
 };
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+/// Wall-clock timings for each phase of a parse run
+#[derive(Debug, Default)]
+struct Profile {
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl Profile {
+    /// Run `f` and record how long it took under `phase`
+    fn time<T>(&mut self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.phases.push((phase, start.elapsed()));
+        result
+    }
+
+    /// Render the recorded phases as a summary table
+    fn render(&self) -> String {
+        let total: Duration = self.phases.iter().map(|(_, elapsed)| *elapsed).sum();
+        let mut out = format!("{:<28} {:>12}\n", "Phase", "Time (ms)");
+        for (phase, elapsed) in &self.phases {
+            out.push_str(&format!(
+                "{:<28} {:>12.3}\n",
+                phase,
+                elapsed.as_secs_f64() * 1000.0
+            ));
+        }
+        out.push_str(&format!(
+            "{:<28} {:>12.3}\n",
+            "Total",
+            total.as_secs_f64() * 1000.0
+        ));
+        out
+    }
+}
+
+/// Options for `parse`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ParseOptions<'a> {
+    /// Only discover and parse, printing a summary; no cache is built or written
+    pub dry_run: bool,
+    /// Print a timing breakdown of each phase to stderr
+    pub profile: bool,
+    /// Match patterns case-insensitively; recorded in the cache
+    pub ignore_case: bool,
+    /// How the owners of overlapping rules are combined; recorded in the cache
+    pub owner_merge: OwnerMerge,
+    /// Where patterns are anchored; recorded in the cache
+    pub anchor: PatternAnchor,
+    /// Walk into symlinked files and directories
+    pub follow_links: bool,
+    /// List every path that could not be read during the walk
+    pub verbose: bool,
+    /// Only re-resolve files changed in git, updating the existing cache
+    pub changed_only: bool,
+    /// TOML file mapping old owner identifiers to their replacements
+    pub alias_map: Option<&'a std::path::Path>,
+    /// Merge the result into the existing cache instead of replacing it
+    pub append: bool,
+    /// TOML file mapping owner emails to the handles of the same people
+    pub identity_map: Option<&'a std::path::Path>,
+    /// Treat walked paths resolving to the same file, e.g. through symlinks, as one
+    pub dedupe_paths: bool,
+}
 
 /// Preprocess CODEOWNERS files and build ownership map
 pub fn run(
     path: &std::path::Path, cache_file: Option<&std::path::Path>, encoding: CacheEncoding,
+    options: &ParseOptions,
 ) -> Result<()> {
-    println!("Parsing CODEOWNERS files at {}", path.display());
+    let mut timings = Profile::default();
+    let mut stdout = stdout_writer();
+    let result = parse_and_store(
+        path,
+        cache_file,
+        encoding,
+        options,
+        &mut timings,
+        &mut stdout,
+    );
+
+    if options.profile {
+        eprint!("{}", timings.render());
+    }
+
+    result?;
+    stdout.finish()
+}
+
+fn parse_and_store(
+    path: &std::path::Path, cache_file: Option<&std::path::Path>, encoding: CacheEncoding,
+    options: &ParseOptions, timings: &mut Profile, out: &mut impl Write,
+) -> Result<()> {
+    writeln!(out, "Parsing CODEOWNERS files at {}", path.display())?;
 
     let cache_file = match cache_file {
         Some(file) => path.join(file),
@@ -22,32 +128,748 @@ pub fn run(
         }
     };
 
-    // Collect all CODEOWNERS files in the specified path
-    let codeowners_files = find_codeowners_files(path)?;
+    let identities = options.identity_map.map(IdentityMap::load).transpose()?;
+    let aliases = options.alias_map.map(load_owner_aliases).transpose()?;
+
+    if options.changed_only
+        && update_changed_files(
+            path,
+            &cache_file,
+            encoding,
+            options,
+            aliases.as_ref(),
+            identities.as_ref(),
+            timings,
+            out,
+        )?
+    {
+        return Ok(());
+    }
+
+    // Collect all CODEOWNERS files in the specified path, skipping configured directories
+    let skip_dirs = AppConfig::fetch()
+        .map(|config| config.skip_dirs)
+        .unwrap_or_default();
+    let codeowners_files = timings.time("Find CODEOWNERS files", || {
+        find_codeowners_files_skipping(path, &skip_dirs)
+    })?;
 
     // Parse each CODEOWNERS file and collect entries
-    let parsed_codeowners: Vec<CodeownersEntry> = codeowners_files
-        .iter()
-        .filter_map(|file| {
-            let parsed = parse_codeowners(file).ok()?;
-            Some(parsed)
-        })
-        .flatten()
-        .collect();
+    let mut parsed_codeowners: Vec<CodeownersEntry> =
+        timings.time("Parse CODEOWNERS files", || {
+            codeowners_files
+                .iter()
+                .filter_map(|file| {
+                    let parsed = parse_codeowners(file).ok()?;
+                    Some(parsed)
+                })
+                .flatten()
+                .collect()
+        });
+
+    // Canonicalize renamed owners
+    if let Some(aliases) = &aliases {
+        apply_owner_aliases(&mut parsed_codeowners, aliases)?;
+    }
 
     // Collect all files in the specified path
-    let files = find_files(path)?;
+    let (files, walk_errors) =
+        timings.time("Walk files", || find_files(path, options.follow_links))?;
+    if let Some(warning) = format_walk_errors(&walk_errors, options.verbose) {
+        eprintln!("{}", warning);
+    }
+    let files = if options.dedupe_paths {
+        timings.time("Dedupe paths", || dedupe_paths(files, true))
+    } else {
+        files
+    };
+
+    if options.dry_run {
+        writeln!(out, "Dry run: no cache will be written")?;
+        writeln!(out, "  CODEOWNERS files: {}", codeowners_files.len())?;
+        writeln!(out, "  Entries:          {}", parsed_codeowners.len())?;
+        writeln!(out, "  Files:            {}", files.len())?;
+        writeln!(
+            out,
+            "  Unique owners:    {}",
+            collect_owners(&parsed_codeowners).len()
+        )?;
+        writeln!(
+            out,
+            "  Unique tags:      {}",
+            collect_tags(&parsed_codeowners).len()
+        )?;
+        writeln!(out, "  Cache file:       {}", cache_file.display())?;
+        return Ok(());
+    }
 
     // Build the cache from the parsed CODEOWNERS entries and the files
     let hash = get_repo_hash(path)?;
 
-    let cache = build_cache(parsed_codeowners, files, hash)?;
+    let mut cache = timings.time("Resolve ownership", || {
+        build_cache(
+            parsed_codeowners,
+            files,
+            hash,
+            options.ignore_case,
+            options.owner_merge,
+            options.anchor,
+            path,
+        )
+    })?;
+    cache.follow_links = options.follow_links;
+    cache.dedupe_paths = options.dedupe_paths;
+    set_owner_aliases(&mut cache, aliases.unwrap_or_default());
+
+    // Fold the partial cache into the one left by earlier runs
+    if options.append {
+        // An unreadable cache is an error rather than something to overwrite
+        if cache_file.exists() {
+            let existing = load_cache(&cache_file)?;
+            cache = timings.time("Merge with existing cache", || {
+                merge_caches(vec![existing, cache])
+            })?;
+            // The merged cache reflects the current repository state, so it stays fresh
+            cache.hash = hash;
+        } else {
+            writeln!(out, "No existing cache, starting a new one")?;
+        }
+    }
+
+    // Count each person's email and handle as one owner
+    if let Some(identities) = &identities {
+        set_identity_map(&mut cache, identities);
+    }
 
     // Store the cache in the specified file
-    store_cache(&cache, &cache_file, encoding)?;
+    timings.time("Serialize cache", || {
+        store_cache(&cache, &cache_file, encoding)
+    })?;
 
     // Test the cache by loading it back
     let _cache = load_cache(&cache_file)?;
 
     Ok(())
 }
+
+/// Update an existing cache with only the files changed in git
+///
+/// Changes are the uncommitted ones plus those committed since the commit the
+/// cache was built at. Returns `false`, leaving the cache untouched, when a
+/// full build is needed instead: there is no readable cache yet, it was built
+/// with other settings than `options`, that commit is unknown, or a
+/// CODEOWNERS file changed. Without `aliases`, the cache's own are kept.
+#[allow(clippy::too_many_arguments)]
+fn update_changed_files(
+    path: &std::path::Path, cache_file: &std::path::Path, encoding: CacheEncoding,
+    options: &ParseOptions, aliases: Option<&HashMap<String, String>>,
+    identities: Option<&IdentityMap>, timings: &mut Profile, out: &mut impl Write,
+) -> Result<bool> {
+    let Ok(mut cache) = load_cache(cache_file) else {
+        writeln!(out, "No existing cache, building it from scratch")?;
+        return Ok(false);
+    };
+
+    // Files resolved with other settings than the unchanged ones would be inconsistent
+    let built_with = BuildSettings::of(&cache);
+    let requested = BuildSettings {
+        case_insensitive: options.ignore_case,
+        owner_merge: options.owner_merge,
+        anchor: options.anchor,
+        follow_links: options.follow_links,
+        dedupe_paths: options.dedupe_paths,
+        owner_aliases: aliases
+            .cloned()
+            .unwrap_or_else(|| built_with.owner_aliases.clone()),
+        // Only merges owners, so a new identity map is applied without a rebuild
+        identity_map: built_with.identity_map.clone(),
+    };
+    if requested != built_with {
+        writeln!(
+            out,
+            "Cache was built with other settings, rebuilding the whole cache"
+        )?;
+        return Ok(false);
+    }
+
+    // Commits made since the cache was built, e.g. pulled or checked out, count as changes too
+    let head = head_commit(path);
+    let mut changed = timings.time("Find changed files", || find_changed_files(path))?;
+    if cache.head_commit != head {
+        let Some(since) = &cache.head_commit else {
+            writeln!(
+                out,
+                "Cache doesn't record the commit it was built at, rebuilding the whole cache"
+            )?;
+            return Ok(false);
+        };
+        match find_files_changed_since(path, since) {
+            Ok(committed) => changed.extend(committed),
+            Err(_) => {
+                writeln!(
+                    out,
+                    "Commit {} is no longer in the repository, rebuilding the whole cache",
+                    since
+                )?;
+                return Ok(false);
+            }
+        }
+    }
+    let changed = dedupe_paths(changed, false);
+
+    let skip_dirs = AppConfig::fetch()
+        .map(|config| config.skip_dirs)
+        .unwrap_or_default();
+    if changed
+        .iter()
+        .any(|file| is_codeowners_file(path, file, &skip_dirs))
+    {
+        writeln!(out, "CODEOWNERS changed, rebuilding the whole cache")?;
+        return Ok(false);
+    }
+    if changed.contains(&path.join(CODEOWNERS_IGNORE_FILE)) {
+        writeln!(
+            out,
+            "{} changed, rebuilding the whole cache",
+            CODEOWNERS_IGNORE_FILE
+        )?;
+        return Ok(false);
+    }
+    // Leave out what walking the repository would skip
+    let changed = filter_walked_paths(path, changed)?;
+
+    timings.time("Resolve ownership", || {
+        update_cache_files(&mut cache, path, &changed)
+    });
+    // Without a new identity map, keep merging owners like the cache was built
+    let identities = match identities {
+        Some(identities) => identities.clone(),
+        None => IdentityMap::new(cache.identity_map.clone())?,
+    };
+    set_identity_map(&mut cache, &identities);
+    cache.head_commit = head;
+    cache.hash = get_repo_hash(path)?;
+
+    timings.time("Serialize cache", || {
+        store_cache(&cache, cache_file, encoding)
+    })?;
+    writeln!(out, "Updated ownership of {} changed files", changed.len())?;
+
+    Ok(true)
+}
+
+/// Parse a CODEOWNERS document from stdin and print its entries as JSON
+///
+/// Nothing is read from or written to the filesystem.
+pub fn run_stdin() -> Result<()> {
+    let mut content = String::new();
+    std::io::stdin().read_to_string(&mut content)?;
+
+    let entries = parse_codeowners_str(&content, std::path::Path::new("<stdin>"))?;
+
+    let mut stdout = stdout_writer();
+    writeln!(
+        stdout,
+        "{}",
+        serde_json::to_string_pretty(&entries)
+            .map_err(|e| Error::new(&format!("Serialization error: {}", e)))?
+    )?;
+
+    stdout.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parse::{parse_repo_with, BuildSettings};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_dry_run_does_not_write_cache() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+
+        fs::write(
+            base_path.join("CODEOWNERS"),
+            "*.rs @rust-team #rust\n/docs/ @docs-team\n",
+        )?;
+        fs::create_dir_all(base_path.join("docs"))?;
+        fs::write(base_path.join("main.rs"), "fn main() {}")?;
+        fs::write(base_path.join("docs").join("README.md"), "# Docs")?;
+
+        let cache_file = std::path::Path::new(".codeowners.cache");
+        let options = ParseOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        run(
+            base_path,
+            Some(cache_file),
+            CacheEncoding::Bincode,
+            &options,
+        )?;
+
+        assert!(!base_path.join(cache_file).exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_fails_on_invalid_pattern() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+
+        fs::write(
+            base_path.join("CODEOWNERS"),
+            "*.rs @rust-team\nsrc/[invalid @docs-team\n",
+        )?;
+        fs::write(base_path.join("main.rs"), "fn main() {}")?;
+
+        let cache_file = std::path::Path::new(".codeowners.cache");
+        let error = run(
+            base_path,
+            Some(cache_file),
+            CacheEncoding::Bincode,
+            &ParseOptions::default(),
+        )
+        .unwrap_err();
+        assert_eq!(error.kind, crate::utils::error::ErrorKind::Validation);
+        assert!(error.to_string().contains("src/[invalid"));
+        assert!(!base_path.join(cache_file).exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_alias_map_merges_owners_in_cache() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+
+        fs::write(
+            base_path.join("CODEOWNERS"),
+            "*.rs @org/old-team\n*.md @org/new-team\n",
+        )?;
+        fs::write(base_path.join("main.rs"), "fn main() {}")?;
+        fs::write(base_path.join("README.md"), "# Readme")?;
+        fs::write(
+            base_path.join("special.txt"),
+            "!!!CODEOWNERS @org/old-team\nspecial",
+        )?;
+        let alias_file = base_path.join("aliases.toml");
+        fs::write(&alias_file, "\"@org/old-team\" = \"@org/new-team\"\n")?;
+
+        let cache_file = std::path::Path::new(".codeowners.cache");
+        let options = ParseOptions {
+            alias_map: Some(&alias_file),
+            ..Default::default()
+        };
+        run(
+            base_path,
+            Some(cache_file),
+            CacheEncoding::Bincode,
+            &options,
+        )?;
+
+        // Inline declarations are aliased too
+        let cache = load_cache(&base_path.join(cache_file))?;
+        assert_eq!(cache.owners_map.len(), 1);
+        let (owner, files) = cache.owners_map.iter().next().unwrap();
+        assert_eq!(owner.identifier, "@org/new-team");
+        assert_eq!(files.len(), 3);
+
+        // Rebuilding the cache keeps the aliases without the alias file
+        fs::remove_file(&alias_file)?;
+        let rebuilt = parse_repo_with(base_path, cache_file, &BuildSettings::of(&cache))?;
+        assert_eq!(rebuilt.owner_aliases, cache.owner_aliases);
+        assert_eq!(rebuilt.owners_map, cache.owners_map);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_profile_records_each_phase() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+
+        fs::write(base_path.join("CODEOWNERS"), "*.rs @rust-team\n")?;
+        fs::write(base_path.join("main.rs"), "fn main() {}")?;
+
+        let mut timings = Profile::default();
+        parse_and_store(
+            base_path,
+            Some(std::path::Path::new(".codeowners.cache")),
+            CacheEncoding::Bincode,
+            &ParseOptions::default(),
+            &mut timings,
+            &mut std::io::sink(),
+        )?;
+
+        let rendered = timings.render();
+        for phase in [
+            "Find CODEOWNERS files",
+            "Parse CODEOWNERS files",
+            "Walk files",
+            "Resolve ownership",
+            "Serialize cache",
+            "Total",
+        ] {
+            assert!(rendered.contains(phase), "missing phase: {}", phase);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_changed_only_updates_only_changed_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        let repo = git2::Repository::init(base_path)?;
+
+        fs::write(base_path.join("CODEOWNERS"), "*.rs @rust-team\n")?;
+        fs::write(base_path.join("a.rs"), "")?;
+        fs::write(base_path.join("b.rs"), "")?;
+
+        // Commit everything so only later edits show up as changes
+        let mut index = repo.index()?;
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+        index.write()?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let signature = git2::Signature::now("Test", "test@example.com")?;
+        repo.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[])?;
+
+        let cache_file = std::path::Path::new(".codeowners.cache");
+        run(
+            base_path,
+            Some(cache_file),
+            CacheEncoding::Bincode,
+            &ParseOptions::default(),
+        )?;
+
+        // Mark a.rs in the cache so we can tell whether it was re-resolved
+        let mut cache = load_cache(&base_path.join(cache_file))?;
+        let a_rs = base_path.join("a.rs");
+        for file in cache.files.iter_mut().filter(|file| file.path == a_rs) {
+            file.owners[0].identifier = "@untouched".to_string();
+        }
+        store_cache(&cache, &base_path.join(cache_file), CacheEncoding::Bincode)?;
+
+        // Stage a new file and delete a committed one
+        fs::write(base_path.join("c.rs"), "")?;
+        index.add_path(std::path::Path::new("c.rs"))?;
+        index.write()?;
+        fs::remove_file(base_path.join("b.rs"))?;
+
+        let options = ParseOptions {
+            changed_only: true,
+            ..Default::default()
+        };
+        run(
+            base_path,
+            Some(cache_file),
+            CacheEncoding::Bincode,
+            &options,
+        )?;
+
+        let cache = load_cache(&base_path.join(cache_file))?;
+        let owner_of = |name: &str| {
+            cache
+                .files
+                .iter()
+                .find(|file| file.path == base_path.join(name))
+                .map(|file| file.owners[0].identifier.clone())
+        };
+        assert_eq!(owner_of("a.rs").as_deref(), Some("@untouched"));
+        assert_eq!(owner_of("b.rs"), None);
+        assert_eq!(owner_of("c.rs").as_deref(), Some("@rust-team"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_changed_only_rebuilds_with_new_settings() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        let repo = git2::Repository::init(base_path)?;
+
+        fs::write(base_path.join("CODEOWNERS"), "*.RS @rust-team\n")?;
+        fs::write(base_path.join("a.rs"), "")?;
+
+        let mut index = repo.index()?;
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+        index.write()?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let signature = git2::Signature::now("Test", "test@example.com")?;
+        repo.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[])?;
+
+        let cache_file = std::path::Path::new(".codeowners.cache");
+        run(
+            base_path,
+            Some(cache_file),
+            CacheEncoding::Bincode,
+            &ParseOptions::default(),
+        )?;
+        let a_rs_owners = |cache: &crate::core::types::CodeownersCache| {
+            cache
+                .files
+                .iter()
+                .find(|file| file.path == base_path.join("a.rs"))
+                .map(|file| file.owners.len())
+        };
+        assert_eq!(
+            a_rs_owners(&load_cache(&base_path.join(cache_file))?),
+            Some(0)
+        );
+
+        // Nothing changed in git, but the whole cache must match case-insensitively now
+        let options = ParseOptions {
+            changed_only: true,
+            ignore_case: true,
+            ..Default::default()
+        };
+        run(
+            base_path,
+            Some(cache_file),
+            CacheEncoding::Bincode,
+            &options,
+        )?;
+
+        let cache = load_cache(&base_path.join(cache_file))?;
+        assert!(cache.case_insensitive);
+        assert_eq!(a_rs_owners(&cache), Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_changed_only_picks_up_commits_since_last_parse() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        let repo = git2::Repository::init(base_path)?;
+        let signature = git2::Signature::now("Test", "test@example.com")?;
+        let commit_all = |message: &str| -> Result<()> {
+            let mut index = repo.index()?;
+            index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+            index.write()?;
+            let tree = repo.find_tree(index.write_tree()?)?;
+            let parents = match repo.head() {
+                Ok(head) => vec![head.peel_to_commit()?],
+                Err(_) => Vec::new(),
+            };
+            let parents: Vec<&git2::Commit> = parents.iter().collect();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &parents,
+            )?;
+            Ok(())
+        };
+
+        fs::write(base_path.join("CODEOWNERS"), "*.rs @rust-team\n")?;
+        fs::write(base_path.join(".codeownersignore"), "vendor/\n")?;
+        fs::write(base_path.join("a.rs"), "")?;
+        commit_all("initial")?;
+
+        let cache_file = std::path::Path::new(".codeowners.cache");
+        run(
+            base_path,
+            Some(cache_file),
+            CacheEncoding::Bincode,
+            &ParseOptions::default(),
+        )?;
+        let options = ParseOptions {
+            changed_only: true,
+            ..Default::default()
+        };
+        let owner_of = |name: &str| -> Result<Option<String>> {
+            let cache = load_cache(&base_path.join(cache_file))?;
+            Ok(cache
+                .files
+                .iter()
+                .find(|file| file.path == base_path.join(name))
+                .map(|file| file.owners[0].identifier.clone()))
+        };
+
+        // A committed file leaves nothing for git status to report
+        fs::create_dir_all(base_path.join("vendor"))?;
+        fs::write(base_path.join("vendor/lib.rs"), "")?;
+        fs::write(base_path.join("b.rs"), "")?;
+        commit_all("add files")?;
+        run(
+            base_path,
+            Some(cache_file),
+            CacheEncoding::Bincode,
+            &options,
+        )?;
+        assert_eq!(owner_of("b.rs")?.as_deref(), Some("@rust-team"));
+        // Files the walk skips stay out of the cache
+        assert_eq!(owner_of("vendor/lib.rs")?, None);
+        assert_eq!(owner_of(".codeownersignore")?, None);
+
+        // A committed CODEOWNERS change rebuilds the cache
+        fs::write(base_path.join("CODEOWNERS"), "*.rs @new-team\n")?;
+        commit_all("change owners")?;
+        run(
+            base_path,
+            Some(cache_file),
+            CacheEncoding::Bincode,
+            &options,
+        )?;
+        assert_eq!(owner_of("a.rs")?.as_deref(), Some("@new-team"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_identity_map_is_kept_in_cache() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path().join("repo");
+        fs::create_dir_all(&base_path)?;
+        let repo = git2::Repository::init(&base_path)?;
+        fs::write(
+            base_path.join("CODEOWNERS"),
+            "*.rs alice@corp.com\n*.md @alice\n",
+        )?;
+        fs::write(base_path.join("main.rs"), "")?;
+        fs::write(base_path.join("README.md"), "")?;
+
+        // Commit everything so only later edits show up as changes
+        let mut index = repo.index()?;
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+        index.write()?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let signature = git2::Signature::now("Test", "test@example.com")?;
+        repo.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[])?;
+        let identity_file = temp_dir.path().join("identities.toml");
+        fs::write(&identity_file, "\"Alice@corp.com\" = \"@alice\"\n")?;
+
+        let cache_file = std::path::Path::new(".codeowners.cache");
+        let options = ParseOptions {
+            identity_map: Some(&identity_file),
+            ..Default::default()
+        };
+        run(
+            &base_path,
+            Some(cache_file),
+            CacheEncoding::Bincode,
+            &options,
+        )?;
+        let alice_files = |cache: &crate::core::types::CodeownersCache| {
+            assert_eq!(cache.owners_map.len(), 1);
+            cache.owners_map[&"@alice".parse().unwrap()].len()
+        };
+
+        let cache = load_cache(&base_path.join(cache_file))?;
+        assert_eq!(
+            cache.identity_map.get("alice@corp.com").map(String::as_str),
+            Some("@alice")
+        );
+        assert_eq!(alice_files(&cache), 2);
+
+        // Rebuilding a stale cache merges the same identities
+        let rebuilt = parse_repo_with(&base_path, cache_file, &BuildSettings::of(&cache))?;
+        assert_eq!(rebuilt.identity_map, cache.identity_map);
+        assert_eq!(alice_files(&rebuilt), 2);
+
+        // So does updating changed files without --identity-map
+        fs::write(base_path.join("lib.rs"), "")?;
+        let options = ParseOptions {
+            changed_only: true,
+            ..Default::default()
+        };
+        run(
+            &base_path,
+            Some(cache_file),
+            CacheEncoding::Bincode,
+            &options,
+        )?;
+        assert_eq!(alice_files(&load_cache(&base_path.join(cache_file))?), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_keeps_unreadable_cache() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+        fs::write(base_path.join("CODEOWNERS"), "*.rs @rust-team\n")?;
+        fs::write(base_path.join("main.rs"), "fn main() {}")?;
+
+        let cache_file = temp_dir.path().join("combined.cache");
+        fs::write(&cache_file, "not a cache")?;
+        let options = ParseOptions {
+            append: true,
+            ..Default::default()
+        };
+
+        assert!(run(
+            base_path,
+            Some(&cache_file),
+            CacheEncoding::Bincode,
+            &options
+        )
+        .is_err());
+        assert_eq!(fs::read_to_string(&cache_file)?, "not a cache");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_combines_disjoint_subtrees() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+        fs::write(base_path.join("README.md"), "# Monorepo")?;
+        let cache_file = base_path.join(".codeowners.cache");
+        let options = ParseOptions {
+            append: true,
+            ..Default::default()
+        };
+
+        for (service, owner) in [("api", "@org/backend"), ("web", "@org/frontend")] {
+            let service_path = base_path.join(service);
+            fs::create_dir_all(&service_path)?;
+            fs::write(
+                service_path.join("CODEOWNERS"),
+                format!("* {} #{}\n", owner, service),
+            )?;
+            fs::write(service_path.join("main.rs"), "fn main() {}")?;
+        }
+        for service in ["api", "web"] {
+            run(
+                &base_path.join(service),
+                Some(&cache_file),
+                CacheEncoding::Bincode,
+                &options,
+            )?;
+        }
+
+        let cache = load_cache(&cache_file)?;
+        // A fresh hash keeps list commands from rebuilding the whole repo over it
+        assert_eq!(cache.hash, get_repo_hash(base_path)?);
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.owners_map.len(), 2);
+        assert_eq!(cache.tags_map.len(), 2);
+        assert!(!cache
+            .files
+            .iter()
+            .any(|file| file.path.ends_with("README.md")));
+        for (service, owner) in [("api", "@org/backend"), ("web", "@org/frontend")] {
+            let main_rs = base_path.join(service).join("main.rs");
+            let file = cache.files.iter().find(|file| file.path == main_rs);
+            assert_eq!(
+                file.map(|file| file.owners[0].identifier.as_str()),
+                Some(owner)
+            );
+        }
+
+        Ok(())
+    }
+}
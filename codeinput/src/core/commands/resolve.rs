@@ -0,0 +1,219 @@
+use crate::{
+    core::{
+        cache::sync_cache,
+        display::stdout_writer,
+        html,
+        resolver::resolve_batch,
+        types::{CodeownersCache, OutputFormat, Owner, Tag},
+    },
+    utils::error::{Error, ErrorKind, Result},
+};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Resolve owners and tags for a list of paths
+///
+/// `paths_from` is a file with one path per line, or `-` for stdin. Relative
+/// paths are taken relative to the repository. Paths missing from the cache
+/// are resolved against the cached CODEOWNERS rules.
+pub fn run(
+    paths_from: &Path, repo: Option<&Path>, format: &OutputFormat, cache_file: Option<&Path>,
+) -> Result<()> {
+    // Repository path
+    let repo = repo.unwrap_or_else(|| Path::new("."));
+
+    let paths = read_paths_from(paths_from)?;
+
+    // Load the cache
+    let cache = sync_cache(repo, cache_file)?;
+
+    let resolved = resolve_paths(&cache, repo, &paths);
+
+    let mut stdout = stdout_writer();
+    match format {
+        OutputFormat::Text => {
+            for (path, owners, tags) in &resolved {
+                writeln!(stdout, "{}", text_line(path, owners, tags))?;
+            }
+        }
+        OutputFormat::Json => {
+            let map: serde_json::Map<String, serde_json::Value> = resolved
+                .iter()
+                .map(|(path, owners, tags)| {
+                    (
+                        path.to_string_lossy().to_string(),
+                        serde_json::json!({
+                            "owners": owners.iter().map(|o| &o.identifier).collect::<Vec<_>>(),
+                            "tags": tags.iter().map(|t| &t.0).collect::<Vec<_>>(),
+                        }),
+                    )
+                })
+                .collect();
+            writeln!(
+                stdout,
+                "{}",
+                serde_json::to_string_pretty(&map)
+                    .map_err(|e| Error::new(&format!("JSON serialization error: {}", e)))?
+            )?;
+        }
+        OutputFormat::Html => {
+            let join = |values: Vec<&str>| values.join(", ");
+            let rows: Vec<Vec<String>> = resolved
+                .iter()
+                .map(|(path, owners, tags)| {
+                    vec![
+                        path.to_string_lossy().to_string(),
+                        join(owners.iter().map(|o| o.identifier.as_str()).collect()),
+                        join(tags.iter().map(|t| t.0.as_str()).collect()),
+                    ]
+                })
+                .collect();
+            write!(
+                stdout,
+                "{}",
+                html::render_table("Resolved Files", &["File Path", "Owners", "Tags"], &rows)
+            )?;
+        }
+        OutputFormat::Bincode | OutputFormat::Dot => {
+            return Err(Error::with_kind(
+                ErrorKind::Validation,
+                &format!("Resolved paths are not available as {}", format),
+            ));
+        }
+    }
+
+    stdout.finish()
+}
+
+/// Read the paths listed in `paths_from`, or on stdin for `-`
+pub(crate) fn read_paths_from(paths_from: &Path) -> Result<Vec<PathBuf>> {
+    if paths_from == Path::new("-") {
+        return read_paths(io::stdin().lock());
+    }
+    let file = std::fs::File::open(paths_from).map_err(|e| {
+        Error::with_kind(
+            ErrorKind::Io,
+            &format!("Failed to open {}: {}", paths_from.display(), e),
+        )
+    })?;
+    read_paths(io::BufReader::new(file))
+}
+
+/// Read newline-delimited paths, skipping blank lines
+fn read_paths(reader: impl BufRead) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim_end_matches('\r');
+        if !line.trim().is_empty() {
+            paths.push(PathBuf::from(line));
+        }
+    }
+    Ok(paths)
+}
+
+/// Owners and tags for each of `paths`, in order
+///
+/// Cached files are looked up directly; the rest are resolved with the
+/// cache's rules. Returned paths are the ones given, not the repository-joined
+/// ones.
+pub(crate) fn resolve_paths(
+    cache: &CodeownersCache, repo: &Path, paths: &[PathBuf],
+) -> Vec<(PathBuf, Vec<Owner>, Vec<Tag>)> {
+    let cached: HashMap<&Path, _> = cache
+        .files
+        .iter()
+        .map(|file| (file.path.as_path(), file))
+        .collect();
+    let full_paths: Vec<PathBuf> = paths.iter().map(|path| repo.join(path)).collect();
+
+    let uncached: Vec<PathBuf> = full_paths
+        .iter()
+        .filter(|path| !cached.contains_key(path.as_path()))
+        .cloned()
+        .collect();
+    let mut resolved: HashMap<PathBuf, (Vec<Owner>, Vec<Tag>)> = HashMap::new();
+    if !uncached.is_empty() {
+        let matchers = cache.entry_matchers(repo);
+        resolved = resolve_batch(&uncached, &matchers, cache.owner_merge)
+            .into_iter()
+            .map(|(path, owners, tags)| (path, (owners, tags)))
+            .collect();
+    }
+
+    paths
+        .iter()
+        .zip(&full_paths)
+        .map(|(path, full_path)| {
+            let (owners, tags) = match cached.get(full_path.as_path()) {
+                Some(file) => (file.owners.clone(), file.tags.clone()),
+                None => resolved.get(full_path).cloned().unwrap_or_default(),
+            };
+            (path.clone(), owners, tags)
+        })
+        .collect()
+}
+
+/// Render `path: owner1 owner2 #tag1 #tag2`, or `path: (no owners)`
+fn text_line(path: &Path, owners: &[Owner], tags: &[Tag]) -> String {
+    let mut fields: Vec<String> = owners.iter().map(|o| o.identifier.clone()).collect();
+    if fields.is_empty() {
+        fields.push("(no owners)".to_string());
+    }
+    fields.extend(tags.iter().map(|t| format!("#{}", t.0)));
+    format!("{}: {}", path.display(), fields.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        parse::analyze_repo,
+        types::{OwnerMerge, PatternAnchor},
+    };
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolve_paths_from_stdin() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+
+        fs::write(
+            base_path.join("CODEOWNERS"),
+            "*.rs @rust-team #backend\n/docs/ @docs-team\n",
+        )?;
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::write(base_path.join("src").join("main.rs"), "fn main() {}")?;
+        let cache = analyze_repo(base_path, false, OwnerMerge::Winner, PatternAnchor::FileDir)?;
+
+        // Output of `git diff --name-only`, including a file added since the cache was built
+        let stdin = io::Cursor::new("src/main.rs\r\n\ndocs/new.md\nREADME\n");
+        let paths = read_paths(stdin)?;
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("src/main.rs"),
+                PathBuf::from("docs/new.md"),
+                PathBuf::from("README"),
+            ]
+        );
+
+        let lines: Vec<String> = resolve_paths(&cache, base_path, &paths)
+            .iter()
+            .map(|(path, owners, tags)| text_line(path, owners, tags))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "src/main.rs: @rust-team #backend",
+                "docs/new.md: @docs-team",
+                "README: (no owners)",
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    core::{
+        cache::{load_cache, lock_path},
+        display::stdout_writer,
+        parse::{analyze_repo_with, BuildSettings},
+        types::{CodeownersCache, CodeownersEntry, FileEntry},
+    },
+    utils::{
+        app_config::AppConfig,
+        error::{Error, ErrorKind, Result},
+    },
+};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Maximum number of paths listed per section of the diff summary
+const MAX_LISTED: usize = 10;
+
+/// Differences between the on-disk cache and a fresh analysis
+#[derive(Debug, Default)]
+struct CacheDiff {
+    entries_added: Vec<String>,
+    entries_removed: Vec<String>,
+    files_added: Vec<PathBuf>,
+    files_removed: Vec<PathBuf>,
+    files_changed: Vec<PathBuf>,
+}
+
+impl CacheDiff {
+    fn is_empty(&self) -> bool {
+        self.entries_added.is_empty()
+            && self.entries_removed.is_empty()
+            && self.files_added.is_empty()
+            && self.files_removed.is_empty()
+            && self.files_changed.is_empty()
+    }
+
+    fn print_summary(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut print_section = |title: &str, items: Vec<String>| -> io::Result<()> {
+            if items.is_empty() {
+                return Ok(());
+            }
+            writeln!(out, "  {} ({}):", title, items.len())?;
+            for item in items.iter().take(MAX_LISTED) {
+                writeln!(out, "    {}", item)?;
+            }
+            if items.len() > MAX_LISTED {
+                writeln!(out, "    ... and {} more", items.len() - MAX_LISTED)?;
+            }
+            Ok(())
+        };
+        let paths = |paths: &[PathBuf]| paths.iter().map(|p| p.display().to_string()).collect();
+
+        print_section("Rules added", self.entries_added.clone())?;
+        print_section("Rules removed", self.entries_removed.clone())?;
+        print_section("Files added", paths(&self.files_added))?;
+        print_section("Files removed", paths(&self.files_removed))?;
+        print_section(
+            "Files with changed owners or tags",
+            paths(&self.files_changed),
+        )?;
+        Ok(())
+    }
+}
+
+/// Verify that the cache on disk matches the current state of the repository
+///
+/// The cache is rebuilt in memory and compared rule by rule and file by file.
+/// The repository hash is not compared, so a cache committed alongside the
+/// files it describes still verifies. Returns a validation error listing the
+/// differences when the cache is stale.
+pub fn run(repo: Option<&Path>, cache_file: Option<&Path>) -> Result<()> {
+    // Repository path
+    let repo = repo.unwrap_or_else(|| Path::new("."));
+
+    let cache_file = match cache_file {
+        Some(file) => repo.join(file),
+        None => repo.join(AppConfig::fetch()?.cache_file),
+    };
+
+    let on_disk = load_cache(&cache_file).map_err(|e| {
+        Error::with_kind(
+            e.kind,
+            &format!("Failed to load cache from {}: {}", cache_file.display(), e),
+        )
+    })?;
+
+    // Rebuild with the same settings the cache was built with
+    let current = analyze_repo_with(repo, &BuildSettings::of(&on_disk))?;
+
+    let diff = diff_caches(repo, &current, &on_disk, &cache_file);
+    let mut stdout = stdout_writer();
+    if diff.is_empty() {
+        writeln!(stdout, "Cache {} is up to date", cache_file.display())?;
+        return stdout.finish();
+    }
+
+    writeln!(stdout, "Cache {} is out of date:", cache_file.display())?;
+    diff.print_summary(&mut stdout)?;
+    stdout.finish()?;
+
+    Err(Error::with_kind(
+        ErrorKind::Validation,
+        &format!(
+            "Cache {} does not match the repository; run `codeowners parse` to rebuild it",
+            cache_file.display()
+        ),
+    ))
+}
+
+/// Compare a freshly built cache against the one loaded from disk
+///
+/// The cache file and its lock file are skipped, since they may or may not
+/// have existed when either cache was built.
+fn diff_caches(
+    repo: &Path, current: &CodeownersCache, on_disk: &CodeownersCache, cache_file: &Path,
+) -> CacheDiff {
+    let skip: Vec<PathBuf> = std::iter::once(cache_file.to_path_buf())
+        .chain(lock_path(cache_file))
+        .map(|path| relative_to(repo, &path))
+        .collect();
+    let entry_keys = |cache: &CodeownersCache| -> BTreeSet<String> {
+        cache
+            .entries
+            .iter()
+            .map(|entry| describe_entry(repo, entry))
+            .collect()
+    };
+
+    let current_entries = entry_keys(current);
+    let on_disk_entries = entry_keys(on_disk);
+    let current_files = file_map(repo, current, &skip);
+    let on_disk_files = file_map(repo, on_disk, &skip);
+
+    CacheDiff {
+        entries_added: current_entries
+            .difference(&on_disk_entries)
+            .cloned()
+            .collect(),
+        entries_removed: on_disk_entries
+            .difference(&current_entries)
+            .cloned()
+            .collect(),
+        files_added: current_files
+            .keys()
+            .filter(|path| !on_disk_files.contains_key(*path))
+            .cloned()
+            .collect(),
+        files_removed: on_disk_files
+            .keys()
+            .filter(|path| !current_files.contains_key(*path))
+            .cloned()
+            .collect(),
+        files_changed: current_files
+            .iter()
+            .filter(|(path, file)| {
+                on_disk_files
+                    .get(*path)
+                    .is_some_and(|cached| cached.owners != file.owners || cached.tags != file.tags)
+            })
+            .map(|(path, _)| path.clone())
+            .collect(),
+    }
+}
+
+/// Files of a cache keyed by their path relative to the repository
+fn file_map<'a>(
+    repo: &Path, cache: &'a CodeownersCache, skip: &[PathBuf],
+) -> BTreeMap<PathBuf, &'a FileEntry> {
+    cache
+        .files
+        .iter()
+        .map(|file| (relative_to(repo, &file.path), file))
+        .filter(|(path, _)| !skip.contains(path))
+        .collect()
+}
+
+/// One-line description of a rule, used as its identity when diffing
+fn describe_entry(repo: &Path, entry: &CodeownersEntry) -> String {
+    let owners: Vec<String> = entry.owners.iter().map(|o| o.to_string()).collect();
+    let tags: Vec<String> = entry.tags.iter().map(|t| format!("#{}", t)).collect();
+    format!(
+        "{}:{} {} {} {}",
+        relative_to(repo, &entry.source_file).display(),
+        entry.line_number,
+        entry.pattern,
+        owners.join(" "),
+        tags.join(" ")
+    )
+    .trim_end()
+    .to_string()
+}
+
+fn relative_to(repo: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(repo).unwrap_or(path).to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        parse::{analyze_repo, parse_repo},
+        types::{OwnerMerge, PatternAnchor},
+    };
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn create_test_repo() -> Result<TempDir> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+
+        fs::write(
+            base_path.join("CODEOWNERS"),
+            "*.rs @rust-team #rust\n/docs/ @docs-team\n",
+        )?;
+        fs::create_dir_all(base_path.join("docs"))?;
+        fs::write(base_path.join("main.rs"), "fn main() {}")?;
+        fs::write(base_path.join("docs").join("README.md"), "# Docs")?;
+
+        Ok(temp_dir)
+    }
+
+    #[test]
+    fn test_verify_cache_up_to_date() -> Result<()> {
+        let temp_dir = create_test_repo()?;
+        let cache_file = Path::new(".codeowners.cache");
+        parse_repo(
+            temp_dir.path(),
+            cache_file,
+            false,
+            OwnerMerge::Winner,
+            PatternAnchor::FileDir,
+        )?;
+
+        run(Some(temp_dir.path()), Some(cache_file))
+    }
+
+    #[test]
+    fn test_verify_cache_skips_visible_cache_and_lock_files() -> Result<()> {
+        let temp_dir = create_test_repo()?;
+        // Unlike the default hidden name, the walk picks up this cache and its lock file
+        let cache_file = Path::new("owners.cache");
+        parse_repo(
+            temp_dir.path(),
+            cache_file,
+            false,
+            OwnerMerge::Winner,
+            PatternAnchor::FileDir,
+        )?;
+        assert!(temp_dir.path().join("owners.cache.lock").is_file());
+
+        run(Some(temp_dir.path()), Some(cache_file))
+    }
+
+    #[test]
+    fn test_verify_cache_detects_stale_cache() -> Result<()> {
+        let temp_dir = create_test_repo()?;
+        let base_path = temp_dir.path();
+        let cache_file = Path::new(".codeowners.cache");
+        let stale = parse_repo(
+            base_path,
+            cache_file,
+            false,
+            OwnerMerge::Winner,
+            PatternAnchor::FileDir,
+        )?;
+
+        // Change ownership of Rust files and add a new file after the cache was built
+        fs::write(
+            base_path.join("CODEOWNERS"),
+            "*.rs @core-team #rust\n/docs/ @docs-team\n",
+        )?;
+        fs::write(base_path.join("lib.rs"), "")?;
+
+        let current = analyze_repo(base_path, false, OwnerMerge::Winner, PatternAnchor::FileDir)?;
+        let diff = diff_caches(base_path, &current, &stale, &base_path.join(cache_file));
+        assert_eq!(
+            diff.entries_added,
+            vec!["CODEOWNERS:0 *.rs @core-team #rust"]
+        );
+        assert_eq!(
+            diff.entries_removed,
+            vec!["CODEOWNERS:0 *.rs @rust-team #rust"]
+        );
+        assert_eq!(diff.files_added, vec![PathBuf::from("lib.rs")]);
+        assert!(diff.files_removed.is_empty());
+        assert_eq!(diff.files_changed, vec![PathBuf::from("main.rs")]);
+
+        let err = run(Some(base_path), Some(cache_file)).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Validation);
+
+        Ok(())
+    }
+}
@@ -1,15 +1,56 @@
-use crate::utils::error::{Error, Result};
-use git2::{DiffFormat, DiffOptions, Repository};
-use ignore::Walk;
+use crate::utils::app_config::AppConfig;
+use crate::utils::error::{Error, ErrorKind, Result};
+use crate::utils::types::HashMode;
+use git2::{DiffFormat, DiffOptions, Repository, StatusOptions};
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::WalkBuilder;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use sha2::{Digest, Sha256};
-use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::path::{Component, Path, PathBuf};
 
-use super::types::{CodeownersEntry, Owner, Tag};
+use super::types::{CodeownersEntry, FileEntry, Owner, Tag};
+
+/// Directories never searched for CODEOWNERS files
+const ALWAYS_SKIPPED_DIRS: &[&str] = &[".git"];
+
+/// Optional file at the repository root listing paths to leave out of ownership resolution
+pub const CODEOWNERS_IGNORE_FILE: &str = ".codeownersignore";
 
 /// Find CODEOWNERS files recursively in the given directory and its subdirectories
+///
+/// Symlinked directories and CODEOWNERS files are followed and resolved to
+/// their targets; a directory already visited through another link is skipped.
+/// `.git` directories are never searched.
 pub fn find_codeowners_files<P: AsRef<Path>>(base_path: P) -> Result<Vec<PathBuf>> {
+    find_codeowners_files_skipping(base_path, &[])
+}
+
+/// Find CODEOWNERS files like [`find_codeowners_files`], also skipping any
+/// directory whose name is listed in `skip_dirs`
+pub fn find_codeowners_files_skipping<P: AsRef<Path>>(
+    base_path: P, skip_dirs: &[String],
+) -> Result<Vec<PathBuf>> {
     let mut result = Vec::new();
-    if let Ok(entries) = std::fs::read_dir(base_path) {
+    let mut visited = HashSet::new();
+    collect_codeowners_files(base_path.as_ref(), skip_dirs, &mut visited, &mut result);
+
+    Ok(result)
+}
+
+fn collect_codeowners_files(
+    dir: &Path, skip_dirs: &[String], visited: &mut HashSet<PathBuf>, result: &mut Vec<PathBuf>,
+) {
+    // Guard against symlink loops
+    let Ok(canonical) = dir.canonicalize() else {
+        return;
+    };
+    if !visited.insert(canonical) {
+        return;
+    }
+
+    if let Ok(entries) = std::fs::read_dir(dir) {
         for entry in entries.flatten() {
             let path = entry.path();
             if path.is_file()
@@ -20,25 +61,202 @@ pub fn find_codeowners_files<P: AsRef<Path>>(base_path: P) -> Result<Vec<PathBuf
                     .unwrap_or(false)
             {
                 result.push(path);
-            } else if path.is_dir() {
-                result.extend(find_codeowners_files(path)?);
+            } else if path.is_dir() && !is_skipped_dir(&path, skip_dirs) {
+                collect_codeowners_files(&path, skip_dirs, visited, result);
             }
         }
     }
+}
 
-    Ok(result)
+fn is_skipped_dir(path: &Path, skip_dirs: &[String]) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| ALWAYS_SKIPPED_DIRS.contains(&n) || skip_dirs.iter().any(|skip| skip == n))
+        .unwrap_or(false)
+}
+
+/// Find the root of the git repository containing `path`
+///
+/// Walks up from `path` using git's own discovery rules. Falls back to `path`
+/// itself when it is not inside a git repository or the repository is bare.
+pub fn find_repo_root(path: &Path) -> PathBuf {
+    Repository::discover(path)
+        .ok()
+        .and_then(|repo| repo.workdir().map(|dir| dir.to_path_buf()))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// A path that could not be read while walking the repository
+#[derive(Debug, Clone)]
+pub struct WalkError {
+    /// Path the error occurred at, when known
+    pub path: Option<PathBuf>,
+    pub message: String,
+}
+
+impl std::fmt::Display for WalkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}: {}", path.display(), self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
 }
 
-/// Find all files in the given directory and its subdirectories
-pub fn find_files<P: AsRef<Path>>(base_path: P) -> Result<Vec<PathBuf>> {
-    let result = Walk::new(base_path)
-        .filter_map(|entry| entry.ok())
+/// Find all files under `base_path`, honoring ignore files
+///
+/// Paths listed in `base_path/.codeownersignore` are skipped as well.
+/// With `follow_links`, symlinked files and directories are walked as well.
+/// Symlink cycles are detected by the walker, logged and skipped. Paths that
+/// cannot be read (e.g. permission errors or broken symlinks) are returned
+/// alongside the files that were walked successfully.
+pub fn find_files<P: AsRef<Path>>(
+    base_path: P, follow_links: bool,
+) -> Result<(Vec<PathBuf>, Vec<WalkError>)> {
+    let mut errors = Vec::new();
+    let mut builder = WalkBuilder::new(&base_path);
+    builder.follow_links(follow_links);
+    if let Some(overrides) = codeownersignore_override(base_path.as_ref())? {
+        builder.overrides(overrides);
+    }
+    let result = builder
+        .build()
+        .filter_map(|entry| match entry {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                if is_loop_error(&e) {
+                    log::warn!("Skipping symlink cycle: {}", e);
+                } else {
+                    errors.push(walk_error(&e));
+                }
+                None
+            }
+        })
         .filter(|e| e.path().is_file())
         .filter(|e| e.clone().file_name().to_str().unwrap() != "CODEOWNERS")
         .map(|entry| entry.into_path())
         .collect::<Vec<_>>();
 
-    Ok(result)
+    Ok((result, errors))
+}
+
+/// Build an override excluding the paths listed in `base_path/.codeownersignore`
+///
+/// Lines use gitignore syntax relative to `base_path`; blank lines and `#`
+/// comments are skipped. Re-including paths with `!` is not supported. Returns
+/// `None` when there is no such file.
+fn codeownersignore_override(base_path: &Path) -> Result<Option<Override>> {
+    let ignore_file = base_path.join(CODEOWNERS_IGNORE_FILE);
+    let Ok(content) = std::fs::read_to_string(&ignore_file) else {
+        return Ok(None);
+    };
+
+    let invalid = |line_number: usize, e: ignore::Error| {
+        Error::with_kind(
+            ErrorKind::Validation,
+            &format!("{}:{}: {}", ignore_file.display(), line_number, e),
+        )
+    };
+
+    let mut builder = OverrideBuilder::new(base_path);
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('!') {
+            log::warn!(
+                "{}:{}: negated patterns are not supported, ignoring '{}'",
+                ignore_file.display(),
+                index + 1,
+                line
+            );
+            continue;
+        }
+        // Overrides whitelist by default, so every line becomes an ignore glob
+        builder
+            .add(&format!("!{}", line))
+            .map_err(|e| invalid(index + 1, e))?;
+    }
+
+    builder.build().map(Some).map_err(|e| invalid(0, e))
+}
+
+/// `path` with every `\\` replaced by a `/` separator
+///
+/// Cached paths use `/` on every platform, so a cache built on Windows can be
+/// read on Linux and the other way round. A Unix file name containing a
+/// backslash is split at it too.
+pub(crate) fn normalize_separators(path: PathBuf) -> PathBuf {
+    match path.to_str() {
+        Some(s) if s.contains('\\') => PathBuf::from(s.replace('\\', "/")),
+        _ => path,
+    }
+}
+
+/// Drop repeated paths, keeping the first of each in order
+///
+/// With `canonicalize`, paths reaching the same file through `.`, `..` or a
+/// symlink are repeats too. Paths that can't be resolved are compared as given.
+pub fn dedupe_paths(paths: Vec<PathBuf>, canonicalize: bool) -> Vec<PathBuf> {
+    let mut seen = HashSet::with_capacity(paths.len());
+    paths
+        .into_iter()
+        .filter(|path| {
+            let key = if canonicalize {
+                std::fs::canonicalize(path).unwrap_or_else(|_| path.clone())
+            } else {
+                path.clone()
+            };
+            seen.insert(key)
+        })
+        .collect()
+}
+
+/// Render a warning for paths skipped by [`find_files`], or `None` if there were none
+///
+/// The skipped paths themselves are only listed when `verbose` is set.
+pub fn format_walk_errors(errors: &[WalkError], verbose: bool) -> Option<String> {
+    if errors.is_empty() {
+        return None;
+    }
+
+    let mut warning = format!(
+        "Warning: {} path(s) could not be read and were skipped; ownership data may be incomplete",
+        errors.len()
+    );
+    if verbose {
+        for error in errors {
+            warning.push_str(&format!("\n  {}", error));
+        }
+    }
+    Some(warning)
+}
+
+fn walk_error(err: &ignore::Error) -> WalkError {
+    match err {
+        ignore::Error::WithPath { path, err } => WalkError {
+            path: Some(path.clone()),
+            message: err.to_string(),
+        },
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+            walk_error(err)
+        }
+        _ => WalkError {
+            path: None,
+            message: err.to_string(),
+        },
+    }
+}
+
+fn is_loop_error(err: &ignore::Error) -> bool {
+    match err {
+        ignore::Error::Loop { .. } => true,
+        ignore::Error::WithPath { err, .. } | ignore::Error::WithDepth { err, .. } => {
+            is_loop_error(err)
+        }
+        _ => false,
+    }
 }
 
 /// Collect all unique owners from CODEOWNERS entries
@@ -67,9 +285,257 @@ pub fn collect_tags(entries: &[CodeownersEntry]) -> Vec<Tag> {
     tags.into_iter().collect()
 }
 
-pub fn get_repo_hash(repo_path: &Path) -> Result<[u8; 32]> {
+/// Build a map from each key to the files it applies to in a single parallel pass
+///
+/// Every key in `keys` is present in the result (possibly with no files), keys
+/// not listed in `keys` are ignored, and paths keep the order of `file_entries`.
+pub(crate) fn build_file_map<K, F>(
+    file_entries: &[FileEntry], keys: Vec<K>, select: F,
+) -> HashMap<K, Vec<PathBuf>>
+where
+    K: Eq + Hash + Clone + Send + Sync,
+    F: Fn(&FileEntry) -> &[K] + Sync,
+{
+    let wanted: HashSet<&K> = keys.iter().collect();
+
+    let mut map = file_entries
+        .par_iter()
+        .fold(HashMap::<K, Vec<PathBuf>>::new, |mut acc, file_entry| {
+            let values = select(file_entry);
+            for (i, key) in values.iter().enumerate() {
+                // Count each file once per key, even if the key is listed twice
+                if values[..i].contains(key) || !wanted.contains(key) {
+                    continue;
+                }
+                acc.entry(key.clone())
+                    .or_default()
+                    .push(file_entry.path.clone());
+            }
+            acc
+        })
+        .reduce(HashMap::new, |mut left, right| {
+            for (key, paths) in right {
+                left.entry(key).or_default().extend(paths);
+            }
+            left
+        });
+
+    for key in keys {
+        map.entry(key).or_default();
+    }
+
+    map
+}
+
+/// Find files with staged, unstaged or untracked changes
+///
+/// Paths are joined onto `repo_path`, matching the paths produced by
+/// [`find_files`]. Ignored files are not included; deleted files are.
+pub fn find_changed_files(repo_path: &Path) -> Result<Vec<PathBuf>> {
+    let repo = Repository::open(repo_path)
+        .map_err(|e| Error::with_source("Failed to open repo", Box::new(e)))?;
+
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true);
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .map_err(|e| Error::with_source("Failed to get repository status", Box::new(e)))?;
+
+    Ok(statuses
+        .iter()
+        .filter_map(|entry| entry.path().map(|path| repo_path.join(path)))
+        .collect())
+}
+
+/// Find files changed by the commits between `since` and `HEAD`
+///
+/// `since` is a commit id, such as the one a cache was built at. Paths are
+/// joined onto `repo_path` like those from [`find_changed_files`], and both
+/// the old and new path of a renamed file are included. Fails when `since` is
+/// not in the repository, e.g. after a rebase.
+pub fn find_files_changed_since(repo_path: &Path, since: &str) -> Result<Vec<PathBuf>> {
     let repo = Repository::open(repo_path)
         .map_err(|e| Error::with_source("Failed to open repo", Box::new(e)))?;
+    let tree = |spec: &str| {
+        repo.revparse_single(spec)
+            .and_then(|object| object.peel_to_tree())
+            .map_err(|e| {
+                Error::with_source(&format!("Failed to find commit {}", spec), Box::new(e))
+            })
+    };
+    let diff = repo
+        .diff_tree_to_tree(Some(&tree(since)?), Some(&tree("HEAD")?), None)
+        .map_err(|e| Error::with_source("Failed to get diff", Box::new(e)))?;
+
+    let paths = diff
+        .deltas()
+        .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+        .flatten()
+        .map(|path| repo_path.join(path))
+        .collect();
+    Ok(dedupe_paths(paths, false))
+}
+
+/// Id of the commit `HEAD` points at, or `None` outside git and before the first commit
+pub fn head_commit(repo_path: &Path) -> Option<String> {
+    let repo = Repository::discover(repo_path).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    Some(commit.id().to_string())
+}
+
+/// Whether `path` is a CODEOWNERS file that [`find_codeowners_files_skipping`]
+/// would find in `repo_path`
+pub fn is_codeowners_file(repo_path: &Path, path: &Path, skip_dirs: &[String]) -> bool {
+    let Ok(relative) = path.strip_prefix(repo_path) else {
+        return false;
+    };
+    relative
+        .file_name()
+        .is_some_and(|name| name == "CODEOWNERS")
+        && !relative
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .any(|component| match component {
+                Component::Normal(name) => name.to_str().is_some_and(|name| {
+                    ALWAYS_SKIPPED_DIRS.contains(&name) || skip_dirs.iter().any(|skip| skip == name)
+                }),
+                _ => false,
+            })
+}
+
+/// Keep only the paths that [`find_files`] would walk in `repo_path`
+///
+/// For paths reported by git, e.g. from [`find_changed_files`]: hidden paths,
+/// CODEOWNERS files and paths listed in `.codeownersignore` are dropped.
+/// Files ignored by git are expected to be left out already.
+pub fn filter_walked_paths(repo_path: &Path, paths: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+    let overrides = codeownersignore_override(repo_path)?;
+    let is_ignored = |path: &Path, is_dir: bool| {
+        overrides
+            .as_ref()
+            .is_some_and(|overrides| overrides.matched(path, is_dir).is_ignore())
+    };
+
+    Ok(paths
+        .into_iter()
+        .filter(|path| {
+            let Ok(relative) = path.strip_prefix(repo_path) else {
+                return false;
+            };
+            let hidden = relative.components().any(|component| match component {
+                Component::Normal(name) => name.to_string_lossy().starts_with('.'),
+                _ => false,
+            });
+            // The walk doesn't descend into ignored directories
+            let in_ignored_dir = path
+                .ancestors()
+                .skip(1)
+                .take_while(|dir| dir.starts_with(repo_path) && *dir != repo_path)
+                .any(|dir| is_ignored(dir, true));
+            !hidden
+                && relative
+                    .file_name()
+                    .is_some_and(|name| name != "CODEOWNERS")
+                && !in_ignored_dir
+                && !is_ignored(path, false)
+        })
+        .collect())
+}
+
+/// Find the author of the last commit changing each of `paths`
+///
+/// Authors are `Name <email>`. Walks the history from `HEAD` once, newest
+/// first, comparing each commit to its first parent, and stops as soon as
+/// every path has an author. Paths are joined onto `repo_path` like those
+/// from [`find_files`]; paths never committed are left out of the result.
+pub fn last_commit_authors(repo_path: &Path, paths: &[&Path]) -> Result<HashMap<PathBuf, String>> {
+    let repo = Repository::discover(repo_path)
+        .map_err(|e| Error::with_source("Failed to open repo", Box::new(e)))?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| Error::new("Repository has no working directory"))?;
+
+    // Git reports paths relative to the working directory, with `/` separators
+    let prefix = repo_path
+        .canonicalize()?
+        .strip_prefix(workdir.canonicalize()?)
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let mut pending: HashMap<String, &Path> = paths
+        .iter()
+        .filter_map(|path| {
+            let relative = prefix.join(path.strip_prefix(repo_path).ok()?);
+            let relative = normalize_separators(relative)
+                .into_os_string()
+                .into_string()
+                .ok()?;
+            Some((relative, *path))
+        })
+        .collect();
+
+    let mut authors = HashMap::with_capacity(pending.len());
+    let mut revwalk = repo.revwalk()?;
+    if revwalk.push_head().is_err() {
+        // Nothing committed yet
+        return Ok(authors);
+    }
+    revwalk.set_sorting(git2::Sort::TIME)?;
+    for oid in revwalk {
+        if pending.is_empty() {
+            break;
+        }
+        let commit = repo.find_commit(oid?)?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+
+        let author = commit.author();
+        for delta in diff.deltas() {
+            let changed = delta
+                .new_file()
+                .path()
+                .and_then(Path::to_str)
+                .and_then(|path| pending.remove(path));
+            if let Some(path) = changed {
+                authors.insert(
+                    path.to_path_buf(),
+                    format!(
+                        "{} <{}>",
+                        author.name().unwrap_or_default(),
+                        author.email().unwrap_or_default()
+                    ),
+                );
+            }
+        }
+    }
+
+    Ok(authors)
+}
+
+/// Hash the repository state with the configured `hash_mode`
+pub fn get_repo_hash(repo_path: &Path) -> Result<[u8; 32]> {
+    let hash_mode = AppConfig::fetch()
+        .map(|config| config.hash_mode)
+        .unwrap_or_default();
+    get_repo_hash_with_mode(repo_path, hash_mode)
+}
+
+/// Hash the repository state, deciding whether a cache built from it is stale
+///
+/// `HashMode::HeadOnly` skips diffing the working tree, which is slow with
+/// large uncommitted changes. `HashMode::None` always returns all zeros
+/// without opening the repository. `repo_path` may be any directory inside
+/// the repository; the hash is that of the whole repository.
+pub fn get_repo_hash_with_mode(repo_path: &Path, hash_mode: HashMode) -> Result<[u8; 32]> {
+    if hash_mode == HashMode::None {
+        return Ok([0; 32]);
+    }
+
+    let repo = Repository::discover(repo_path)
+        .map_err(|e| Error::with_source("Failed to open repo", Box::new(e)))?;
 
     // 1. Get HEAD commit hash (or zeros if unborn)
     let head_oid = repo
@@ -90,7 +556,7 @@ pub fn get_repo_hash(repo_path: &Path) -> Result<[u8; 32]> {
     // 3. Calculate hash of unstaged changes
     // TODO: this doesn't work and also we need to exclude .codeowners.cache file
     // otherwise the hash will change every time we parse the repo
-    let unstaged_hash = {
+    let unstaged_hash = if hash_mode == HashMode::Full {
         let diff = repo
             .diff_index_to_workdir(None, Some(DiffOptions::new().include_untracked(true)))
             .map_err(|e| Error::with_source("Failed to get diff", Box::new(e)))?;
@@ -101,14 +567,22 @@ pub fn get_repo_hash(repo_path: &Path) -> Result<[u8; 32]> {
             true
         })
         .map_err(|e| Error::with_source("Failed to print diff", Box::new(e)))?;
-        hasher.finalize()
+        Some(hasher.finalize())
+    } else {
+        None
     };
 
-    // 4. Combine all components into final hash
+    // 4. Combine all components into final hash, including the ignore file
+    // even when git itself ignores it
     let mut hasher = Sha256::new();
     hasher.update(head_oid.unwrap_or(git2::Oid::zero()).as_bytes());
     hasher.update(index_tree.as_bytes());
-    hasher.update(&unstaged_hash);
+    if let Some(unstaged_hash) = unstaged_hash {
+        hasher.update(unstaged_hash);
+    }
+    if let Ok(ignore_content) = std::fs::read(repo_path.join(CODEOWNERS_IGNORE_FILE)) {
+        hasher.update(&ignore_content);
+    }
 
     Ok(hasher.finalize().into())
 }
@@ -116,6 +590,7 @@ pub fn get_repo_hash(repo_path: &Path) -> Result<[u8; 32]> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::types::{OwnerMerge, PatternAnchor};
     use std::fs::{self, File};
     use tempfile::TempDir;
 
@@ -161,6 +636,217 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_find_codeowners_files_skips_git_and_configured_dirs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+
+        File::create(base_path.join("CODEOWNERS"))?;
+        for dir in [".git", "node_modules/dep"] {
+            fs::create_dir_all(base_path.join(dir))?;
+            File::create(base_path.join(dir).join("CODEOWNERS"))?;
+        }
+
+        let mut found = find_codeowners_files(base_path)?;
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                base_path.join("CODEOWNERS"),
+                base_path.join("node_modules/dep/CODEOWNERS")
+            ]
+        );
+
+        let found = find_codeowners_files_skipping(base_path, &["node_modules".to_string()])?;
+        assert_eq!(found, vec![base_path.join("CODEOWNERS")]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_repo_root_from_nested_dir() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        Repository::init(base_path)?;
+
+        let nested_dir = base_path.join("src").join("nested");
+        fs::create_dir_all(&nested_dir)?;
+
+        let root = find_repo_root(&nested_dir);
+        assert_eq!(root.canonicalize()?, base_path.canonicalize()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_repo_root_outside_repo() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let nested_dir = temp_dir.path().join("not-a-repo");
+        fs::create_dir_all(&nested_dir)?;
+
+        // Temp directories may live inside a repository on some machines
+        if Repository::discover(&nested_dir).is_err() {
+            assert_eq!(find_repo_root(&nested_dir), nested_dir);
+        }
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_repo_hash_modes_are_stable() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        let repo = git2::Repository::init(base_path)?;
+        fs::write(base_path.join("CODEOWNERS"), "* @org/core\n")?;
+
+        let mut index = repo.index()?;
+        index.add_path(Path::new("CODEOWNERS"))?;
+        index.write()?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let signature = git2::Signature::now("Test", "test@example.com")?;
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])?;
+
+        let hash = |mode| get_repo_hash_with_mode(base_path, mode);
+        let full = hash(HashMode::Full)?;
+        let head_only = hash(HashMode::HeadOnly)?;
+        assert_eq!(hash(HashMode::Full)?, full);
+        assert_eq!(hash(HashMode::HeadOnly)?, head_only);
+        assert_eq!(hash(HashMode::None)?, [0; 32]);
+        assert_ne!(full, head_only);
+
+        // Uncommitted changes only invalidate the full hash
+        fs::write(base_path.join("CODEOWNERS"), "* @org/other\n")?;
+        assert_ne!(hash(HashMode::Full)?, full);
+        assert_eq!(hash(HashMode::HeadOnly)?, head_only);
+        assert_eq!(hash(HashMode::None)?, [0; 32]);
+
+        // No repository is needed without hashing
+        let not_a_repo = TempDir::new()?;
+        assert_eq!(
+            get_repo_hash_with_mode(not_a_repo.path(), HashMode::None)?,
+            [0; 32]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_codeownersignore_removes_files_from_cache() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        git2::Repository::init(base_path)?;
+
+        fs::write(base_path.join("CODEOWNERS"), "* @org/core\n")?;
+        fs::create_dir_all(base_path.join("generated"))?;
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::write(base_path.join("generated").join("api.rs"), "")?;
+        fs::write(base_path.join("src").join("main.rs"), "")?;
+        fs::write(base_path.join("src").join("schema.pb.rs"), "")?;
+
+        let hash_before = get_repo_hash(base_path)?;
+        fs::write(
+            base_path.join(CODEOWNERS_IGNORE_FILE),
+            "# Generated code\ngenerated/\n*.pb.rs\n",
+        )?;
+        assert_ne!(get_repo_hash(base_path)?, hash_before);
+
+        let cache = crate::core::parse::analyze_repo(
+            base_path,
+            false,
+            OwnerMerge::Winner,
+            PatternAnchor::FileDir,
+        )?;
+        let mut files: Vec<PathBuf> = cache
+            .files
+            .iter()
+            .map(|file| file.path.strip_prefix(base_path).unwrap().to_path_buf())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec![PathBuf::from("src/main.rs")]);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_files_follow_links() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+
+        let real_dir = base_path.join("real");
+        fs::create_dir_all(&real_dir)?;
+        File::create(real_dir.join("lib.rs"))?;
+        File::create(real_dir.join("CODEOWNERS"))?;
+
+        let repo_dir = base_path.join("repo");
+        fs::create_dir_all(&repo_dir)?;
+        File::create(repo_dir.join("main.rs"))?;
+        std::os::unix::fs::symlink(&real_dir, repo_dir.join("linked"))?;
+        // A cycle back to the repository root must not be walked forever
+        std::os::unix::fs::symlink(&repo_dir, repo_dir.join("linked").join("loop"))?;
+
+        let (files, _) = find_files(&repo_dir, false)?;
+        assert_eq!(files, vec![repo_dir.join("main.rs")]);
+
+        let (mut files, errors) = find_files(&repo_dir, true)?;
+        assert!(errors.is_empty());
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                repo_dir.join("linked").join("lib.rs"),
+                repo_dir.join("main.rs")
+            ]
+        );
+
+        // Symlinked CODEOWNERS files are found once, despite the cycle
+        let found = find_codeowners_files(&repo_dir)?;
+        assert_eq!(found, vec![repo_dir.join("linked").join("CODEOWNERS")]);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_files_reports_unreadable_paths() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        File::create(base_path.join("main.rs"))?;
+        std::os::unix::fs::symlink(base_path.join("missing.rs"), base_path.join("broken.rs"))?;
+
+        let locked_dir = base_path.join("locked");
+        fs::create_dir_all(&locked_dir)?;
+        File::create(locked_dir.join("secret.rs"))?;
+        fs::set_permissions(&locked_dir, fs::Permissions::from_mode(0o000))?;
+        // Permissions are not enforced for privileged users
+        let locked_is_unreadable = fs::read_dir(&locked_dir).is_err();
+
+        let result = find_files(base_path, true);
+        fs::set_permissions(&locked_dir, fs::Permissions::from_mode(0o755))?;
+        let (files, errors) = result?;
+
+        assert!(files.contains(&base_path.join("main.rs")));
+        assert!(!files.contains(&base_path.join("broken.rs")));
+        let expected_errors = if locked_is_unreadable { 2 } else { 1 };
+        assert_eq!(errors.len(), expected_errors);
+        assert!(errors
+            .iter()
+            .any(|e| e.path.as_deref() == Some(base_path.join("broken.rs").as_path())));
+
+        let warning = format_walk_errors(&errors, false).unwrap();
+        assert!(warning.contains(&format!("{} path(s) could not be read", expected_errors)));
+        assert!(!warning.contains("broken.rs"));
+        assert!(format_walk_errors(&errors, true)
+            .unwrap()
+            .contains("broken.rs"));
+        assert!(format_walk_errors(&[], true).is_none());
+
+        Ok(())
+    }
+
     #[test]
     fn test_find_codeowners_files_nonexistent_dir() -> Result<()> {
         let nonexistent_dir = PathBuf::from("/nonexistent/directory");
@@ -2,6 +2,190 @@
 //!
 //! This module contains functions for formatting and truncating text content
 //! to fit within terminal display constraints while maintaining readability.
+//! Lengths are measured in terminal display columns, so wide characters such as
+//! CJK ideographs count as two columns.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tabled::Table;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use super::types::BincodePayload;
+use crate::utils::app_config::AppConfig;
+use crate::utils::error::{Error, ErrorKind, Result};
+
+/// Encodes a value as bincode, preceded by the header for its payload type.
+///
+/// Consumers read the first `BINCODE_HEADER_LEN` bytes with
+/// `BincodePayload::from_header` to learn how to decode the rest.
+pub(crate) fn encode_bincode_output<T: serde::Serialize>(
+    payload: BincodePayload, value: &T,
+) -> Result<Vec<u8>> {
+    let mut encoded = payload.header().to_vec();
+    bincode::serde::encode_into_std_write(value, &mut encoded, bincode::config::standard())
+        .map_err(|e| Error::new(&format!("Serialization error: {}", e)))?;
+
+    Ok(encoded)
+}
+
+/// Writes a value as pretty-printed JSON followed by a newline.
+///
+/// The JSON is serialized straight into `writer`, so large outputs are never
+/// held in memory as a whole. The bytes match `to_string_pretty` plus `\n`.
+pub(crate) fn write_json_pretty<W: Write, T: Serialize>(mut writer: W, value: &T) -> Result<()> {
+    let mut serializer = serde_json::Serializer::pretty(&mut writer);
+    value
+        .serialize(&mut serializer)
+        .map_err(|e| Error::new(&format!("Serialization error: {}", e)))?;
+    writeln!(writer)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Writer that passes through at most `limit` bytes and drops the rest.
+///
+/// Backs `--max-output-bytes`. Call `finish` once the
+/// output is written: it flushes and fails with a truncation notice if anything
+/// was dropped. A limit of 0 disables the check.
+pub(crate) struct LimitedWriter<W: Write> {
+    inner: W,
+    limit: u64,
+    written: u64,
+    truncated: bool,
+}
+
+impl<W: Write> LimitedWriter<W> {
+    pub(crate) fn new(inner: W, limit: u64) -> Self {
+        LimitedWriter {
+            inner,
+            limit,
+            written: 0,
+            truncated: false,
+        }
+    }
+
+    /// Flushes the output, failing if it was cut off at the limit.
+    pub(crate) fn finish(mut self) -> Result<()> {
+        self.inner.flush()?;
+        if self.truncated {
+            return Err(Error::with_kind(
+                ErrorKind::Io,
+                &format!(
+                    "Output truncated after {} bytes (--max-output-bytes); \
+                     for the full output, use --format json and redirect it to a file",
+                    self.limit
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for LimitedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.limit == 0 {
+            return self.inner.write(buf);
+        }
+
+        let remaining = self.limit - self.written;
+        if remaining == 0 {
+            // Swallow the rest so callers finish normally; `finish` reports it
+            self.truncated |= !buf.is_empty();
+            return Ok(buf.len());
+        }
+
+        let len = buf.len().min(remaining as usize);
+        let written = self.inner.write(&buf[..len])?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Buffered stdout, limited to the configured `max_output_bytes`.
+pub(crate) fn stdout_writer() -> LimitedWriter<io::BufWriter<io::StdoutLock<'static>>> {
+    let limit = AppConfig::fetch()
+        .map(|config| config.max_output_bytes)
+        .unwrap_or(0);
+    LimitedWriter::new(io::BufWriter::new(io::stdout().lock()), limit)
+}
+
+/// Output writer for `path`, or for stdout without one
+///
+/// Only stdout is capped by `max_output_bytes`; a file gets the full output.
+pub(crate) fn output_writer(path: Option<&Path>) -> Result<LimitedWriter<Box<dyn Write>>> {
+    let Some(path) = path else {
+        let limit = AppConfig::fetch()
+            .map(|config| config.max_output_bytes)
+            .unwrap_or(0);
+        return Ok(LimitedWriter::new(
+            Box::new(io::BufWriter::new(io::stdout().lock())),
+            limit,
+        ));
+    };
+    let file = std::fs::File::create(path)?;
+    Ok(LimitedWriter::new(Box::new(io::BufWriter::new(file)), 0))
+}
+
+/// JSON list output wrapped with the query that produced it and a result count.
+///
+/// Serializes as `{ "query": {...}, "total": N, "results": [...] }`.
+#[derive(Serialize)]
+pub(crate) struct JsonEnvelope<T: Serialize> {
+    pub(crate) query: serde_json::Value,
+    pub(crate) total: usize,
+    pub(crate) results: T,
+}
+
+/// Lists the file names of up to `limit` paths, e.g. `main.rs, lib.rs (+3)`.
+///
+/// The number of omitted paths is appended in parentheses; an empty list is
+/// shown as `None`.
+pub(crate) fn sample_file_names(paths: &[PathBuf], limit: usize) -> String {
+    if paths.is_empty() {
+        return "None".to_string();
+    }
+
+    let samples: Vec<_> = paths
+        .iter()
+        .take(limit)
+        .map(|p| {
+            p.file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_else(|| p.to_string_lossy().to_string())
+        })
+        .collect();
+    let mut display = samples.join(", ");
+    if paths.len() > limit {
+        display.push_str(&format!(" (+{})", paths.len() - limit));
+    }
+    display
+}
+
+/// Adds `paths` to a JSON object as `files`, keeping at most `max_files` of them.
+///
+/// When paths are left out, `files_truncated: true` is added as well.
+pub(crate) fn insert_file_list(
+    object: &mut serde_json::Value, paths: &[PathBuf], max_files: Option<usize>,
+) {
+    let limit = max_files.unwrap_or(usize::MAX);
+    let files: Vec<String> = paths
+        .iter()
+        .take(limit)
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    object["files"] = files.into();
+    if paths.len() > limit {
+        object["files_truncated"] = true.into();
+    }
+}
 
 /// Truncates a file path to fit within the specified maximum length while preserving readability.
 ///
@@ -16,7 +200,7 @@
 ///
 /// # Returns
 ///
-/// A truncated path string that fits within `max_len` characters
+/// A truncated path string that fits within `max_len` display columns
 ///
 /// # Examples
 ///
@@ -33,7 +217,7 @@
 /// assert_eq!(truncate_path("very-long-filename", 10), "very-lo...");
 /// ```
 pub(crate) fn truncate_path(path: &str, max_len: usize) -> String {
-    if path.len() <= max_len {
+    if path.width() <= max_len {
         path.to_string()
     } else {
         // Find the last slash to preserve filename
@@ -41,37 +225,33 @@ pub(crate) fn truncate_path(path: &str, max_len: usize) -> String {
             let filename = &path[last_slash..]; // includes the slash
 
             // If filename itself is too long, truncate it
-            if filename.len() >= max_len {
-                let available_chars = max_len.saturating_sub(3);
-                if available_chars == 0 {
+            if filename.width() >= max_len {
+                let available_cols = max_len.saturating_sub(3);
+                if available_cols == 0 {
                     "...".to_string()
                 } else {
-                    let chars: Vec<char> = filename.chars().collect();
-                    let end_idx = (chars.len()).min(available_chars + 3); // +3 to account for the "..." we'll replace
-                    let truncated: String = chars[3..end_idx].iter().collect(); // skip first 3 chars after "..."
-                    format!("...{}", truncated)
+                    // skip first 3 chars after "..."
+                    let rest: String = filename.chars().skip(3).collect();
+                    format!("...{}", take_width(&rest, available_cols))
                 }
             } else {
                 // Filename fits, now figure out how much path start we can include
-                let remaining_space = max_len.saturating_sub(filename.len()).saturating_sub(3); // reserve 3 for "..."
+                // reserve 3 for "..."
+                let remaining_space = max_len.saturating_sub(filename.width()).saturating_sub(3);
 
                 if remaining_space == 0 {
                     format!("...{}", filename)
                 } else {
-                    let path_chars: Vec<char> = path.chars().collect();
-                    let path_start: String = path_chars[..remaining_space].iter().collect();
-                    format!("{}...{}", path_start, filename)
+                    format!("{}...{}", take_width(path, remaining_space), filename)
                 }
             }
         } else {
             // No slash found, just truncate normally
-            let available_chars = max_len.saturating_sub(3);
-            if available_chars == 0 {
+            let available_cols = max_len.saturating_sub(3);
+            if available_cols == 0 {
                 "...".to_string()
             } else {
-                let chars: Vec<char> = path.chars().collect();
-                let truncated: String = chars[..available_chars].iter().collect();
-                format!("{}...", truncated)
+                format!("{}...", take_width(path, available_cols))
             }
         }
     }
@@ -90,7 +270,7 @@ pub(crate) fn truncate_path(path: &str, max_len: usize) -> String {
 ///
 /// # Returns
 ///
-/// A truncated string that fits within `max_len` characters
+/// A truncated string that fits within `max_len` display columns
 ///
 /// # Examples
 ///
@@ -107,24 +287,121 @@ pub(crate) fn truncate_path(path: &str, max_len: usize) -> String {
 /// assert_eq!(truncate_string("hello", 3), "...");
 ///
 pub(crate) fn truncate_string(s: &str, max_len: usize) -> String {
-    let chars: Vec<char> = s.chars().collect();
-
-    if chars.len() <= max_len {
+    if s.width() <= max_len {
         s.to_string()
     } else {
-        let available_chars = max_len.saturating_sub(3);
-        if available_chars == 0 {
+        let available_cols = max_len.saturating_sub(3);
+        if available_cols == 0 {
             "...".to_string()
         } else {
-            let truncated: String = chars[..available_chars].iter().collect();
-            format!("{}...", truncated)
+            format!("{}...", take_width(s, available_cols))
+        }
+    }
+}
+
+/// Drops the column header row and the borders of `table`, leaving only the data rows.
+///
+/// Apply it after the table's style, which it replaces.
+pub(crate) fn remove_header(table: &mut Table) {
+    table
+        .with(tabled::settings::Remove::row(
+            tabled::settings::object::Rows::first(),
+        ))
+        .with(tabled::settings::Style::blank());
+}
+
+/// Returns the longest prefix of `s` whose display width does not exceed `max_cols`.
+///
+/// Wide characters (e.g. CJK) count as two columns, so they are never split
+/// across the boundary.
+fn take_width(s: &str, max_cols: usize) -> String {
+    let mut cols = 0;
+    s.chars()
+        .take_while(|c| {
+            cols += c.width().unwrap_or(0);
+            cols <= max_cols
+        })
+        .collect()
+}
+
+/// Wraps `text` in an OSC 8 escape sequence so terminals render it as a link to `url`.
+pub(crate) fn hyperlink(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// Returns a `file://` URL for `path`, made absolute against the current directory.
+///
+/// Bytes other than unreserved URL characters and `/` are percent-encoded.
+pub(crate) fn file_url(path: &Path) -> String {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut url = String::from("file://");
+    for byte in absolute.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                url.push(byte as char)
+            }
+            _ => url.push_str(&format!("%{:02X}", byte)),
         }
     }
+    url
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::types::{FileEntry, Owner, OwnerType, Tag};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_write_json_pretty_matches_to_string_pretty() -> Result<()> {
+        let files: Vec<FileEntry> = (0..50_000)
+            .map(|i| FileEntry {
+                path: PathBuf::from(format!("src/module_{}/file_{}.rs", i % 100, i)),
+                owners: vec![Owner {
+                    identifier: format!("@org/team-{}", i % 7),
+                    owner_type: OwnerType::Team,
+                }],
+                tags: vec![Tag(format!("area/{}", i % 3))],
+                inline_override: false,
+            })
+            .collect();
+
+        let mut streamed = Vec::new();
+        write_json_pretty(&mut streamed, &files)?;
+
+        let expected = serde_json::to_string_pretty(&files).unwrap() + "\n";
+        assert_eq!(streamed, expected.as_bytes());
+
+        let parsed: Vec<FileEntry> = serde_json::from_slice(&streamed).unwrap();
+        assert_eq!(parsed.len(), files.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_limited_writer_truncates_past_limit() {
+        let mut output = Vec::new();
+        let mut writer = LimitedWriter::new(&mut output, 16);
+        writeln!(writer, "0123456789").unwrap();
+        writeln!(writer, "0123456789").unwrap();
+        let error = writer.finish().unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Io);
+        assert!(error.to_string().contains("--max-output-bytes"));
+        assert_eq!(output, b"0123456789\n01234");
+
+        // Output that fits, exactly or without a limit, passes through untouched
+        let mut output = Vec::new();
+        let mut writer = LimitedWriter::new(&mut output, 11);
+        writeln!(writer, "0123456789").unwrap();
+        assert!(writer.finish().is_ok());
+        let names: Vec<String> = (0..1000).map(|i| format!("file_{}", i)).collect();
+        let mut unlimited = Vec::new();
+        let mut writer = LimitedWriter::new(&mut unlimited, 0);
+        write_json_pretty(&mut writer, &names).unwrap();
+        assert!(writer.finish().is_ok());
+        let expected = serde_json::to_string_pretty(&names).unwrap() + "\n";
+        assert_eq!(unlimited, expected.as_bytes());
+    }
 
     #[test]
     fn test_truncate_path_no_truncation_needed() {
@@ -222,8 +499,83 @@ mod tests {
 
     #[test]
     fn test_truncate_string_unicode() {
-        // Test with unicode characters (current implementation may have issues)
+        // Test with unicode characters, measured in display columns
         assert_eq!(truncate_string("café", 4), "café");
-        assert_eq!(truncate_string("hello 世界", 8), "hello 世界");
+        assert_eq!(truncate_string("hello 世界", 10), "hello 世界");
+        assert_eq!(truncate_string("hello 世界", 8), "hello...");
+    }
+
+    #[test]
+    fn test_truncate_string_wide_characters() {
+        // Each CJK character occupies two display columns
+        assert_eq!(truncate_string("@チーム開発者", 13), "@チーム開発者");
+        assert_eq!(truncate_string("@チーム開発者", 10), "@チーム...");
+        // A wide character is never split across the boundary
+        assert_eq!(truncate_string("@チーム開発者", 9), "@チー...");
+    }
+
+    #[test]
+    fn test_truncate_path_wide_characters() {
+        assert_eq!(truncate_path("src/文档/说明.md", 20), "src/文档/说明.md");
+        assert_eq!(
+            truncate_path("src/文档/说明文件.md", 18),
+            "src.../说明文件.md"
+        );
+        assert_eq!(truncate_path("文档/说明文件名称.md", 10), "...文件名");
+    }
+
+    #[test]
+    fn test_encode_bincode_output_is_self_describing() {
+        use crate::core::types::{
+            FileEntry, Owner, OwnerType, Tag, BINCODE_HEADER_LEN, BINCODE_MAGIC,
+        };
+        use std::path::PathBuf;
+
+        let owner = Owner {
+            identifier: "@org/team".to_string(),
+            owner_type: OwnerType::Team,
+        };
+        let files = vec![FileEntry {
+            path: PathBuf::from("src/main.rs"),
+            owners: vec![owner.clone()],
+            tags: vec![Tag("core".to_string())],
+            inline_override: false,
+        }];
+        let owners = vec![(owner, vec![PathBuf::from("src/main.rs")])];
+        let tags = vec![(Tag("core".to_string()), vec![PathBuf::from("src/main.rs")])];
+
+        let outputs = [
+            encode_bincode_output(BincodePayload::Files, &files).unwrap(),
+            encode_bincode_output(BincodePayload::Owners, &owners).unwrap(),
+            encode_bincode_output(BincodePayload::Tags, &tags).unwrap(),
+        ];
+
+        for bytes in outputs {
+            assert_eq!(bytes[..4], BINCODE_MAGIC);
+            let payload = &bytes[BINCODE_HEADER_LEN..];
+            let config = bincode::config::standard();
+
+            // Decode each payload according to the type announced in its header
+            match BincodePayload::from_header(&bytes).unwrap() {
+                BincodePayload::Files => {
+                    let (decoded, _): (Vec<FileEntry>, _) =
+                        bincode::serde::decode_from_slice(payload, config).unwrap();
+                    assert_eq!(decoded[0].path, files[0].path);
+                }
+                BincodePayload::Owners => {
+                    let (decoded, _): (Vec<(Owner, Vec<PathBuf>)>, _) =
+                        bincode::serde::decode_from_slice(payload, config).unwrap();
+                    assert_eq!(decoded, owners);
+                }
+                BincodePayload::Tags => {
+                    let (decoded, _): (Vec<(Tag, Vec<PathBuf>)>, _) =
+                        bincode::serde::decode_from_slice(payload, config).unwrap();
+                    assert_eq!(decoded, tags);
+                }
+            }
+        }
+
+        // Unframed bytes are rejected
+        assert_eq!(BincodePayload::from_header(b"not a header"), None);
     }
 }
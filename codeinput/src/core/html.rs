@@ -0,0 +1,93 @@
+//! Self-contained HTML reports.
+//!
+//! Reports are a single page with inline CSS and JavaScript and no external
+//! resources, so they can be shared as one file. Every cell is HTML-escaped.
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;width:100%}\
+th,td{border:1px solid #ccc;padding:4px 8px;text-align:left;vertical-align:top}\
+th{background:#f4f4f4;cursor:pointer;user-select:none}\
+tr:nth-child(even) td{background:#fafafa}";
+
+/// Sorts the table by the clicked column, numerically when both cells are numbers
+const SCRIPT: &str = "document.querySelectorAll('th').forEach(function(th,col){\
+th.addEventListener('click',function(){\
+var body=th.closest('table').tBodies[0];\
+var asc=th.dataset.order!=='asc';th.dataset.order=asc?'asc':'desc';\
+Array.from(body.rows).sort(function(a,b){\
+var x=a.cells[col].textContent,y=b.cells[col].textContent;\
+var nx=parseFloat(x),ny=parseFloat(y);\
+var cmp=(!isNaN(nx)&&!isNaN(ny))?nx-ny:x.localeCompare(y);\
+return asc?cmp:-cmp;}).forEach(function(row){body.appendChild(row);});});});";
+
+/// Renders a titled, sortable table as a complete HTML document.
+pub(crate) fn render_table(title: &str, headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape_html(title)));
+    html.push_str(&format!("<style>{}</style>\n", STYLE));
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", escape_html(title)));
+    html.push_str(&format!("<p>Total: {}</p>\n", rows.len()));
+
+    html.push_str("<table>\n<thead>\n<tr>");
+    for header in headers {
+        html.push_str(&format!("<th>{}</th>", escape_html(header)));
+    }
+    html.push_str("</tr>\n</thead>\n<tbody>\n");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            html.push_str(&format!("<td>{}</td>", escape_html(cell)));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n");
+
+    html.push_str(&format!("<script>{}</script>\n", SCRIPT));
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Escapes text for use in HTML element content and attribute values.
+pub(crate) fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_table_rows_are_escaped() {
+        let rows = vec![
+            vec!["src/main.rs".to_string(), "@org/core".to_string()],
+            vec![
+                "docs/<script>alert(1)</script>.md".to_string(),
+                "a&b@example.com".to_string(),
+            ],
+        ];
+
+        let html = render_table("Files", &["File Path", "Owners"], &rows);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<th>File Path</th><th>Owners</th>"));
+        assert!(html.contains("<tr><td>src/main.rs</td><td>@org/core</td></tr>"));
+        assert!(html.contains(
+            "<td>docs/&lt;script&gt;alert(1)&lt;/script&gt;.md</td><td>a&amp;b@example.com</td>"
+        ));
+        assert!(!html.contains("<script>alert(1)"));
+        assert!(html.contains("<p>Total: 2</p>"));
+    }
+}
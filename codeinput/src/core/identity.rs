@@ -0,0 +1,167 @@
+//! Merging the owner identities of a single person.
+//!
+//! Someone listed as `alice@corp.com` in one CODEOWNERS file and as `@alice`
+//! in another shows up as two owners with their files split between them. An
+//! identity map names the handle behind each email, so the owner map counts
+//! both as one owner. Rules and files keep the owners they declare.
+
+use crate::utils::error::{Error, ErrorKind, Result};
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::parser::parse_owner;
+use super::types::{FileEntry, Owner, OwnerType};
+
+/// Emails mapped to the `@handle` of the same person
+#[derive(Debug, Default, Clone)]
+pub struct IdentityMap {
+    /// Lowercased email -> handle
+    handles: HashMap<String, String>,
+}
+
+impl IdentityMap {
+    /// Map each email key of `handles` to its handle value
+    ///
+    /// Fails unless every key is an email and every value a user handle.
+    /// Emails are compared case-insensitively.
+    pub fn new(handles: HashMap<String, String>) -> Result<Self> {
+        let mut map = HashMap::with_capacity(handles.len());
+        for (email, handle) in handles {
+            if parse_owner(&email)?.owner_type != OwnerType::Email {
+                return Err(Error::with_kind(
+                    ErrorKind::Validation,
+                    &format!("Identity map key {} is not an email", email),
+                ));
+            }
+            if parse_owner(&handle)?.owner_type != OwnerType::User {
+                return Err(Error::with_kind(
+                    ErrorKind::Validation,
+                    &format!("Identity map value {} is not a user handle", handle),
+                ));
+            }
+            map.insert(email.to_lowercase(), handle);
+        }
+        Ok(Self { handles: map })
+    }
+
+    /// Load an identity map from a TOML file, e.g. `"alice@corp.com" = "@alice"`
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let handles = toml::from_str(&content).map_err(|e| {
+            Error::with_kind(
+                ErrorKind::Validation,
+                &format!("Invalid identity map {}: {}", path.display(), e),
+            )
+        })?;
+        Self::new(handles)
+    }
+
+    /// Lowercased emails and the handle each one maps to
+    pub fn handles(&self) -> &HashMap<String, String> {
+        &self.handles
+    }
+
+    /// The owner `owner` counts as: the handle of a mapped email, itself otherwise
+    pub fn canonical(&self, owner: &Owner) -> Owner {
+        if owner.owner_type != OwnerType::Email {
+            return owner.clone();
+        }
+        match self.handles.get(&owner.identifier.to_lowercase()) {
+            Some(handle) => Owner {
+                identifier: handle.clone(),
+                owner_type: OwnerType::User,
+            },
+            None => owner.clone(),
+        }
+    }
+
+    /// Merge the files of mapped emails into the owner map entry of their handle
+    ///
+    /// A file owned by both identities is listed once, and merged entries keep
+    /// the order of `files`. Unmapped owners are left as they are.
+    pub fn merge_owners_map(
+        &self, owners_map: HashMap<Owner, Vec<PathBuf>>, files: &[FileEntry],
+    ) -> HashMap<Owner, Vec<PathBuf>> {
+        let mut merged: HashMap<Owner, Vec<PathBuf>> = HashMap::with_capacity(owners_map.len());
+        let mut targets = HashSet::new();
+        for (owner, paths) in owners_map {
+            let canonical = self.canonical(&owner);
+            if canonical != owner {
+                targets.insert(canonical.clone());
+            }
+            merged.entry(canonical).or_default().extend(paths);
+        }
+
+        let position: HashMap<&Path, usize> = files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| (file.path.as_path(), index))
+            .collect();
+        for target in targets {
+            if let Some(paths) = merged.get_mut(&target) {
+                paths.sort_by_key(|path| position.get(path.as_path()).copied());
+                paths.dedup();
+            }
+        }
+
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::owner_resolver::build_owners_map;
+
+    fn file(path: &str, owners: &[&str]) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from(path),
+            owners: owners.iter().map(|o| parse_owner(o).unwrap()).collect(),
+            tags: vec![],
+            inline_override: false,
+        }
+    }
+
+    #[test]
+    fn test_merge_email_into_handle() -> Result<()> {
+        let files = vec![
+            file("src/main.rs", &["@alice"]),
+            file("docs/guide.md", &["Alice@corp.com"]),
+            file("src/lib.rs", &["@alice", "alice@corp.com"]),
+            file("web/app.ts", &["bob@corp.com"]),
+        ];
+        let owners: Vec<Owner> = ["@alice", "Alice@corp.com", "alice@corp.com", "bob@corp.com"]
+            .iter()
+            .map(|o| parse_owner(o).unwrap())
+            .collect();
+        let owners_map = build_owners_map(&files, owners);
+
+        let identities = IdentityMap::new(HashMap::from([(
+            "alice@corp.com".to_string(),
+            "@alice".to_string(),
+        )]))?;
+        let merged = identities.merge_owners_map(owners_map, &files);
+
+        // Both spellings of the email merge into the handle, each file once
+        assert_eq!(merged.len(), 2);
+        assert_eq!(
+            merged[&parse_owner("@alice")?],
+            vec![
+                PathBuf::from("src/main.rs"),
+                PathBuf::from("docs/guide.md"),
+                PathBuf::from("src/lib.rs"),
+            ]
+        );
+        // Unmapped identities stay separate
+        assert_eq!(
+            merged[&parse_owner("bob@corp.com")?],
+            vec![PathBuf::from("web/app.ts")]
+        );
+
+        let reversed = HashMap::from([("@alice".to_string(), "alice@corp.com".to_string())]);
+        assert!(IdentityMap::new(reversed).is_err());
+
+        Ok(())
+    }
+}
@@ -1,28 +1,101 @@
+use crate::utils::app_config::AppConfig;
 use crate::utils::error::Result;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::Read;
 use std::path::Path;
 
 use super::parser::parse_owner;
 use super::types::{InlineCodeownersEntry, Owner, Tag};
 
-/// Detects inline CODEOWNERS declaration in the first 50 lines of a file
+/// Number of lines at the top of a file searched for an inline declaration
+const INLINE_SCAN_LINES: usize = 50;
+
+/// Bytes read from the top of a file when `max_inline_scan_bytes` isn't configured
+const DEFAULT_MAX_INLINE_SCAN_BYTES: u64 = 8192;
+
+/// Marker of an inline declaration anywhere in a line
+const INLINE_MARKER: &str = "!!!CODEOWNERS";
+
+/// Directive of an inline declaration at the start of a comment
+const INLINE_DIRECTIVE: &str = "@codeowners:";
+
+/// Comment openers for the languages of common file extensions
+///
+/// `*` covers the continuation lines of block comments. Files with other
+/// extensions accept every opener.
+fn comment_openers(file_path: &Path) -> &'static [&'static str] {
+    const C_STYLE: &[&str] = &["//", "/*", "*"];
+    const HASH: &[&str] = &["#"];
+    const MARKUP: &[&str] = &["<!--"];
+    const CSS: &[&str] = &["/*", "*"];
+    const DASH: &[&str] = &["--"];
+    const ANY: &[&str] = &["//", "/*", "*", "#", "<!--", "--"];
+
+    let extension = file_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some(
+            "rs" | "c" | "h" | "cc" | "cpp" | "hpp" | "cs" | "go" | "java" | "kt" | "kts" | "scala"
+            | "swift" | "dart" | "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "proto" | "zig",
+        ) => C_STYLE,
+        Some(
+            "py" | "rb" | "sh" | "bash" | "zsh" | "pl" | "r" | "yaml" | "yml" | "toml" | "ini"
+            | "cfg" | "conf" | "tf" | "ex" | "exs" | "nix",
+        ) => HASH,
+        Some("html" | "htm" | "xml" | "svg" | "vue" | "svelte" | "md" | "markdown") => MARKUP,
+        Some("css" | "scss" | "less") => CSS,
+        Some("sql" | "lua" | "hs" | "elm") => DASH,
+        _ => ANY,
+    }
+}
+
+/// Detects an inline CODEOWNERS declaration in the first 50 lines of a file
+///
+/// A declaration follows either the `!!!CODEOWNERS` marker anywhere in a line,
+/// or the `@codeowners:` directive opening a comment in the file's language,
+/// e.g. `// @codeowners: @team #tag` in a `.rs` file. Only the first
+/// `max_inline_scan_bytes` of the file are read.
 pub fn detect_inline_codeowners(file_path: &Path) -> Result<Option<InlineCodeownersEntry>> {
+    let max_bytes = AppConfig::fetch()
+        .map(|config| config.max_inline_scan_bytes)
+        .unwrap_or(DEFAULT_MAX_INLINE_SCAN_BYTES);
+    detect_inline_codeowners_with_limit(file_path, max_bytes)
+}
+
+/// Detects an inline CODEOWNERS declaration in the first `max_bytes` of a file
+///
+/// Files with a null byte in that head are taken to be binary and skipped.
+/// A line cut off by the limit is not scanned. With `max_bytes` of 0, nothing is read and no declaration is found.
+pub fn detect_inline_codeowners_with_limit(
+    file_path: &Path, max_bytes: u64,
+) -> Result<Option<InlineCodeownersEntry>> {
     let file = match File::open(file_path) {
         Ok(f) => f,
         Err(_) => return Ok(None), // File doesn't exist or can't be read
     };
 
-    let reader = BufReader::new(file);
-    let lines = reader.lines().take(50);
+    let mut head = Vec::new();
+    if file.take(max_bytes).read_to_end(&mut head).is_err() || head.contains(&0) {
+        return Ok(None);
+    }
+    // A line cut off at the limit would yield truncated owners, so leave it out
+    if head.len() as u64 == max_bytes {
+        let complete = head.iter().rposition(|&byte| byte == b'\n').unwrap_or(0);
+        head.truncate(complete);
+    }
+
+    let openers = comment_openers(file_path);
+    let lines = head.split(|&byte| byte == b'\n').take(INLINE_SCAN_LINES);
 
-    for (line_num, line_result) in lines.enumerate() {
-        let line = match line_result {
-            Ok(l) => l,
-            Err(_) => continue, // Skip lines that can't be read
+    for (line_num, line) in lines.enumerate() {
+        let line = match std::str::from_utf8(line) {
+            Ok(l) => l.strip_suffix('\r').unwrap_or(l),
+            Err(_) => continue, // Skip lines that aren't valid UTF-8
         };
 
-        if let Some(entry) = parse_inline_codeowners_line(&line, line_num + 1, file_path)? {
+        if let Some(entry) = parse_inline_codeowners_line(line, line_num + 1, file_path, openers)? {
             return Ok(Some(entry));
         }
     }
@@ -30,14 +103,34 @@ pub fn detect_inline_codeowners(file_path: &Path) -> Result<Option<InlineCodeown
     Ok(None)
 }
 
+/// The text after the `@codeowners:` directive, if `line` is a comment opening with it
+fn after_directive<'a>(line: &'a str, openers: &[&str]) -> Option<&'a str> {
+    let line = line.trim_start();
+    openers.iter().find_map(|opener| {
+        let comment = line.strip_prefix(opener)?.trim_start();
+        let directive = comment.get(..INLINE_DIRECTIVE.len())?;
+        directive
+            .eq_ignore_ascii_case(INLINE_DIRECTIVE)
+            .then(|| &comment[INLINE_DIRECTIVE.len()..])
+    })
+}
+
 /// Parse a single line for inline CODEOWNERS declaration
 fn parse_inline_codeowners_line(
-    line: &str, line_number: usize, file_path: &Path,
+    line: &str, line_number: usize, file_path: &Path, openers: &[&str],
 ) -> Result<Option<InlineCodeownersEntry>> {
-    // Look for !!!CODEOWNERS marker
-    if let Some(marker_pos) = line.find("!!!CODEOWNERS") {
-        // Extract everything after the marker
-        let after_marker = &line[marker_pos + "!!!CODEOWNERS".len()..];
+    // Look for the !!!CODEOWNERS marker, then for a comment with the directive
+    let after_marker = match line.find(INLINE_MARKER) {
+        Some(marker_pos) => Some(&line[marker_pos + INLINE_MARKER.len()..]),
+        None => after_directive(line, openers),
+    };
+    if let Some(after_marker) = after_marker {
+        // Drop the end of a comment closed on the same line
+        let after_marker = after_marker.trim_end();
+        let after_marker = ["-->", "*/"]
+            .iter()
+            .find_map(|closer| after_marker.strip_suffix(closer))
+            .unwrap_or(after_marker);
 
         // Split by whitespace to get tokens
         let tokens: Vec<&str> = after_marker.split_whitespace().collect();
@@ -362,6 +455,105 @@ fn main() {}
         Ok(())
     }
 
+    #[test]
+    fn test_detect_inline_directive_in_rust_line_comment() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("lib.rs");
+
+        let content = r#"//! Billing
+// @codeowners: @billing-team @alice #payments
+pub fn charge() {}
+"#;
+        fs::write(&file_path, content).unwrap();
+
+        let entry = detect_inline_codeowners(&file_path)?.unwrap();
+        assert_eq!(entry.line_number, 2);
+        assert_eq!(entry.owners.len(), 2);
+        assert_eq!(entry.owners[0].identifier, "@billing-team");
+        assert_eq!(entry.owners[1].identifier, "@alice");
+        assert_eq!(entry.tags.len(), 1);
+        assert_eq!(entry.tags[0].0, "payments");
+
+        // Outside a comment, or in a comment of another language, it is just text
+        fs::write(
+            &file_path,
+            "let owners = \"@codeowners: @x\";\n# @codeowners: @python-team\n",
+        )
+        .unwrap();
+        assert!(detect_inline_codeowners(&file_path)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_inline_directive_in_html_comment() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("index.html");
+
+        let content = r#"<!DOCTYPE html>
+<!-- @codeowners: @web-team #frontend-->
+<html></html>
+"#;
+        fs::write(&file_path, content).unwrap();
+
+        let entry = detect_inline_codeowners(&file_path)?.unwrap();
+        assert_eq!(entry.line_number, 2);
+        assert_eq!(entry.owners.len(), 1);
+        assert_eq!(entry.owners[0].identifier, "@web-team");
+        assert_eq!(entry.tags.len(), 1);
+        assert_eq!(entry.tags[0].0, "frontend");
+
+        // A line comment is not a comment in HTML
+        fs::write(&file_path, "// @codeowners: @web-team\n").unwrap();
+        assert!(detect_inline_codeowners(&file_path)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_inline_codeowners_reads_only_text_heads() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+
+        // A large binary file is rejected on its first null byte
+        let binary_path = temp_dir.path().join("blob.rs");
+        let mut binary = b"// !!!CODEOWNERS @binary-team\n\0".to_vec();
+        binary.resize(16 * 1024 * 1024, 0xff);
+        fs::write(&binary_path, &binary).unwrap();
+        assert!(detect_inline_codeowners_with_limit(&binary_path, 8192)?.is_none());
+
+        // A declaration past the limit is not read, even within the first lines
+        let text_path = temp_dir.path().join("long.rs");
+        let content = format!("// {}\n// !!!CODEOWNERS @late-team\n", "x".repeat(10_000));
+        fs::write(&text_path, content).unwrap();
+        assert!(detect_inline_codeowners_with_limit(&text_path, 8192)?.is_none());
+        let entry = detect_inline_codeowners_with_limit(&text_path, 16 * 1024)?.unwrap();
+        assert_eq!(entry.line_number, 2);
+        assert_eq!(entry.owners[0].identifier, "@late-team");
+
+        assert!(detect_inline_codeowners_with_limit(&text_path, 0)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_inline_codeowners_skips_line_cut_at_limit() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("app.ts");
+        let content = "// header\n// !!!CODEOWNERS @frontend-team\nexport {};\n";
+        fs::write(&file_path, content).unwrap();
+
+        // The limit falls inside `@frontend-team`
+        let cut = content.find("@frontend-team").unwrap() + "@front".len();
+        assert!(detect_inline_codeowners_with_limit(&file_path, cut as u64)?.is_none());
+
+        // Up to the end of the declaration's line, it is read in full
+        let line_end = content.find("\nexport").unwrap() + 1;
+        let entry = detect_inline_codeowners_with_limit(&file_path, line_end as u64)?.unwrap();
+        assert_eq!(entry.owners[0].identifier, "@frontend-team");
+
+        Ok(())
+    }
+
     #[test]
     fn test_detect_inline_codeowners_nonexistent_file() -> Result<()> {
         let temp_dir = TempDir::new().unwrap();
@@ -0,0 +1,115 @@
+//! JUnit XML reports.
+//!
+//! CI systems show each `<testcase>` as a passed or failed check. Text is
+//! escaped the same way as in HTML reports.
+
+use super::html::escape_html;
+
+/// A check in a JUnit report, failed when it has a failure message
+pub(crate) struct TestCase {
+    /// Name shown for the check, e.g. the rule it is about
+    pub name: String,
+    /// Group the check belongs to, e.g. the CODEOWNERS file of the rule
+    pub classname: String,
+    /// Source location of what was checked
+    pub file: String,
+    /// 1-based line number within `file`
+    pub line: usize,
+    /// Why the check failed, or `None` if it passed
+    pub failure: Option<String>,
+}
+
+/// Renders the test cases as a complete JUnit XML document with one suite.
+///
+/// Each failure is a `<failure>` element whose `message` attribute holds the
+/// first line of the message and whose text holds `file:line: message`.
+pub(crate) fn render_suite(name: &str, cases: &[TestCase]) -> String {
+    let failures = cases.iter().filter(|case| case.failure.is_some()).count();
+    let counts = format!("tests=\"{}\" failures=\"{}\"", cases.len(), failures);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"{}\" {}>\n",
+        escape_html(name),
+        counts
+    ));
+    xml.push_str(&format!(
+        "  <testsuite name=\"{}\" {} errors=\"0\" skipped=\"0\">\n",
+        escape_html(name),
+        counts
+    ));
+    for case in cases {
+        let attributes = format!(
+            "name=\"{}\" classname=\"{}\" file=\"{}\" line=\"{}\"",
+            escape_html(&case.name),
+            escape_html(&case.classname),
+            escape_html(&case.file),
+            case.line
+        );
+        let Some(failure) = &case.failure else {
+            xml.push_str(&format!("    <testcase {}/>\n", attributes));
+            continue;
+        };
+        xml.push_str(&format!("    <testcase {}>\n", attributes));
+        xml.push_str(&format!(
+            "      <failure message=\"{}\">{}:{}: {}</failure>\n",
+            escape_html(failure.lines().next().unwrap_or_default()),
+            escape_html(&case.file),
+            case.line,
+            escape_html(failure)
+        ));
+        xml.push_str("    </testcase>\n");
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_suite_counts_and_escapes_failures() {
+        let case = |name: &str, line: usize, failure: Option<&str>| TestCase {
+            name: name.to_string(),
+            classname: "CODEOWNERS".to_string(),
+            file: "CODEOWNERS".to_string(),
+            line,
+            failure: failure.map(str::to_string),
+        };
+        let cases = vec![
+            case("*.rs", 1, None),
+            case(
+                "docs/<draft>",
+                2,
+                Some("Tag #A&B does not match\nsecond line"),
+            ),
+            case("/src/", 3, None),
+        ];
+
+        let xml = render_suite("audit", &cases);
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(xml.contains("<testsuites name=\"audit\" tests=\"3\" failures=\"1\">"));
+        assert!(xml.contains("<testsuite name=\"audit\" tests=\"3\" failures=\"1\""));
+        assert!(xml.contains(
+            "<testcase name=\"*.rs\" classname=\"CODEOWNERS\" file=\"CODEOWNERS\" line=\"1\"/>"
+        ));
+        assert!(xml.contains("<testcase name=\"docs/&lt;draft&gt;\""));
+        assert!(xml.contains(
+            "<failure message=\"Tag #A&amp;B does not match\">CODEOWNERS:2: Tag #A&amp;B does not match\nsecond line</failure>"
+        ));
+
+        // Every opened element is closed
+        assert_eq!(xml.matches("<testcase ").count(), 3);
+        assert_eq!(
+            xml.matches("/>\n").count() + xml.matches("</testcase>").count(),
+            3
+        );
+        assert_eq!(
+            xml.matches("<failure ").count(),
+            xml.matches("</failure>").count()
+        );
+        assert!(xml.ends_with("  </testsuite>\n</testsuites>\n"));
+    }
+}
@@ -1,8 +1,11 @@
-pub(crate) mod cache;
+pub mod cache;
 pub mod commands;
-pub(crate) mod common;
+pub mod common;
 pub(crate) mod display;
+pub(crate) mod html;
+pub mod identity;
 pub(crate) mod inline_parser;
+pub(crate) mod junit;
 pub mod owner_resolver;
 pub(crate) mod parse;
 pub mod parser;
@@ -1,9 +1,12 @@
 use crate::utils::error::{Error, Result};
 use ignore::overrides::{Override, OverrideBuilder};
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use super::types::{CodeownersEntry, FileEntry, Owner};
+use super::common::build_file_map;
+use super::resolver::check_patterns;
+use super::types::{normalize_codeowners_pattern, CodeownersEntry, FileEntry, Owner};
 
 /// Find all files owned by a specific owner
 pub fn find_files_for_owner(files: &[FileEntry], owner: &Owner) -> Vec<PathBuf> {
@@ -20,6 +23,11 @@ pub fn find_files_for_owner(files: &[FileEntry], owner: &Owner) -> Vec<PathBuf>
         .collect()
 }
 
+/// Map each of `owners` to the files it owns, visiting every file entry once
+pub fn build_owners_map(files: &[FileEntry], owners: Vec<Owner>) -> HashMap<Owner, Vec<PathBuf>> {
+    build_file_map(files, owners, |file_entry| &file_entry.owners)
+}
+
 /// Find owners for a specific file based on all parsed CODEOWNERS entries
 pub fn find_owners_for_file<'a>(
     file_path: &'a Path, entries: &'a [CodeownersEntry],
@@ -59,7 +67,8 @@ pub fn find_owners_for_file<'a>(
             // Check if the pattern matches the target file
             let matches = {
                 let mut builder = OverrideBuilder::new(codeowners_dir);
-                if let Err(e) = builder.add(&entry.pattern) {
+                let pattern = normalize_codeowners_pattern(&entry.pattern);
+                if let Err(e) = builder.add(&pattern) {
                     eprintln!(
                         "Invalid pattern '{}' in {}: {}",
                         entry.pattern,
@@ -113,6 +122,17 @@ pub fn find_owners_for_file<'a>(
         .unwrap_or_default())
 }
 
+/// Same as [`find_owners_for_file`], but failing on invalid patterns
+///
+/// Instead of skipping entries whose pattern is not a valid glob, returns a
+/// validation error listing all of them (see [`check_patterns`]).
+pub fn find_owners_for_file_strict(
+    file_path: &Path, entries: &[CodeownersEntry],
+) -> Result<Vec<Owner>> {
+    check_patterns(entries)?;
+    find_owners_for_file(file_path, entries)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,6 +151,7 @@ mod tests {
             path: PathBuf::from(path),
             owners,
             tags: vec![],
+            inline_override: false,
         }
     }
 
@@ -143,6 +164,8 @@ mod tests {
             pattern: pattern.to_string(),
             owners,
             tags: vec![],
+            comment: None,
+            raw: String::new(),
         }
     }
 
@@ -235,6 +258,36 @@ mod tests {
         assert_eq!(team_result[0], PathBuf::from("src/lib.rs"));
     }
 
+    #[test]
+    fn test_build_owners_map_matches_find_files_for_owner() {
+        let alice = create_test_owner("@alice", OwnerType::User);
+        let team = create_test_owner("@org/team", OwnerType::Team);
+        let idle = create_test_owner("@idle", OwnerType::User);
+        let inline_only = create_test_owner("@inline", OwnerType::User);
+
+        let files: Vec<FileEntry> = (0..500)
+            .map(|i| {
+                let owners = match i % 4 {
+                    0 => vec![alice.clone()],
+                    1 => vec![alice.clone(), team.clone()],
+                    2 => vec![team.clone(), team.clone()],
+                    _ => vec![inline_only.clone()],
+                };
+                create_test_file_entry(&format!("src/file_{}.rs", i), owners)
+            })
+            .collect();
+
+        let owners = vec![alice.clone(), team.clone(), idle.clone()];
+        let map = build_owners_map(&files, owners.clone());
+
+        assert_eq!(map.len(), owners.len());
+        for owner in &owners {
+            assert_eq!(map[owner], find_files_for_owner(&files, owner));
+        }
+        assert!(map[&idle].is_empty());
+        assert!(!map.contains_key(&inline_only));
+    }
+
     #[test]
     fn test_find_owners_for_file_no_parent() {
         let entries = vec![];
@@ -380,6 +433,28 @@ mod tests {
         assert_eq!(result[0], rust_owner);
     }
 
+    #[test]
+    fn test_find_owners_for_file_trailing_slash_directory() {
+        let docs_owner = create_test_owner("@docs-team", OwnerType::Team);
+
+        let entries = vec![create_test_codeowners_entry(
+            "/project/CODEOWNERS",
+            1,
+            "/docs/",
+            vec![docs_owner.clone()],
+        )];
+
+        // Nested files under the directory are matched
+        let nested_file = Path::new("/project/docs/api/readme.md");
+        let result = find_owners_for_file(nested_file, &entries).unwrap();
+        assert_eq!(result, vec![docs_owner]);
+
+        // Files outside the directory are not
+        let other_file = Path::new("/project/src/docs.rs");
+        let result = find_owners_for_file(other_file, &entries).unwrap();
+        assert!(result.is_empty());
+    }
+
     #[test]
     fn test_find_owners_for_file_invalid_pattern() {
         let entries = vec![
@@ -403,4 +478,35 @@ mod tests {
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].identifier, "@rust-team");
     }
+
+    #[test]
+    fn test_find_owners_for_file_strict_rejects_invalid_pattern() {
+        let entries = vec![
+            create_test_codeowners_entry(
+                "/project/CODEOWNERS",
+                1,
+                "[invalid",
+                vec![create_test_owner("@team", OwnerType::Team)],
+            ),
+            create_test_codeowners_entry(
+                "/project/CODEOWNERS",
+                2,
+                "*.rs",
+                vec![create_test_owner("@rust-team", OwnerType::Team)],
+            ),
+        ];
+        let file_path = Path::new("/project/src/main.rs");
+
+        let error = find_owners_for_file_strict(file_path, &entries).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("1 invalid CODEOWNERS patterns"));
+        assert!(message.contains("/project/CODEOWNERS:1 '[invalid'"));
+
+        // Without the invalid entry, strict mode resolves like the lenient one
+        let owners = find_owners_for_file_strict(file_path, &entries[1..]).unwrap();
+        assert_eq!(
+            owners,
+            find_owners_for_file(file_path, &entries[1..]).unwrap()
+        );
+    }
 }
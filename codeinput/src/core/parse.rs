@@ -1,41 +1,156 @@
-use crate::utils::error::Result;
+use crate::utils::{app_config::AppConfig, error::Result};
+use std::collections::HashMap;
 
 use super::{
-    cache::{build_cache, store_cache},
-    common::{find_codeowners_files, find_files, get_repo_hash},
-    parser::parse_codeowners,
-    types::{CacheEncoding, CodeownersCache, CodeownersEntry},
+    cache::{build_cache, set_identity_map, set_owner_aliases, store_cache},
+    common::{
+        dedupe_paths, find_codeowners_files_skipping, find_files, format_walk_errors, get_repo_hash,
+    },
+    identity::IdentityMap,
+    parser::{apply_owner_aliases, parse_codeowners},
+    types::{CacheEncoding, CodeownersCache, CodeownersEntry, OwnerMerge, PatternAnchor},
 };
 
-pub fn parse_repo(repo: &std::path::Path, cache_file: &std::path::Path) -> Result<CodeownersCache> {
-    println!("Parsing CODEOWNERS files at {}", repo.display());
+/// How a cache is built, as recorded in it so a rebuild gives the same result
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct BuildSettings {
+    pub case_insensitive: bool,
+    pub owner_merge: OwnerMerge,
+    pub anchor: PatternAnchor,
+    pub follow_links: bool,
+    /// Resolve walked paths to their real location and keep one entry per file
+    pub dedupe_paths: bool,
+    /// Old owner identifier to new, applied to rules and inline declarations
+    pub owner_aliases: HashMap<String, String>,
+    /// Lowercased email to the handle the owner map counts it as
+    pub identity_map: HashMap<String, String>,
+}
+
+impl BuildSettings {
+    /// Matching modes only, with defaults for everything else
+    pub fn new(case_insensitive: bool, owner_merge: OwnerMerge, anchor: PatternAnchor) -> Self {
+        BuildSettings {
+            case_insensitive,
+            owner_merge,
+            anchor,
+            ..Default::default()
+        }
+    }
+
+    /// Settings `cache` was built with
+    pub fn of(cache: &CodeownersCache) -> Self {
+        BuildSettings {
+            case_insensitive: cache.case_insensitive,
+            owner_merge: cache.owner_merge,
+            anchor: cache.anchor,
+            follow_links: cache.follow_links,
+            dedupe_paths: cache.dedupe_paths,
+            owner_aliases: cache.owner_aliases.clone(),
+            identity_map: cache.identity_map.clone(),
+        }
+    }
+}
+
+pub fn parse_repo(
+    repo: &std::path::Path, cache_file: &std::path::Path, case_insensitive: bool,
+    owner_merge: OwnerMerge, anchor: PatternAnchor,
+) -> Result<CodeownersCache> {
+    let settings = BuildSettings::new(case_insensitive, owner_merge, anchor);
+    parse_repo_with(repo, cache_file, &settings)
+}
 
-    // Collect all CODEOWNERS files in the specified path
-    let codeowners_files = find_codeowners_files(repo)?;
+/// Build and store the cache for a repository with the given settings
+///
+/// Status is printed to stderr, as this runs ahead of commands whose output
+/// on stdout may be piped, e.g. JSON or JUnit XML.
+pub(crate) fn parse_repo_with(
+    repo: &std::path::Path, cache_file: &std::path::Path, settings: &BuildSettings,
+) -> Result<CodeownersCache> {
+    eprintln!("Parsing CODEOWNERS files at {}", repo.display());
+
+    let cache = analyze_repo_with(repo, settings)?;
+
+    // Store the cache in the specified file
+    store_cache(&cache, &repo.join(cache_file), CacheEncoding::Bincode)?;
+
+    eprintln!("CODEOWNERS parsing completed successfully");
+
+    Ok(cache)
+}
+
+/// Parse the entries of every CODEOWNERS file in a repository
+///
+/// Directories in the configured `skip_dirs` are not searched, and files that
+/// can't be parsed are left out.
+pub(crate) fn parse_repo_entries(repo: &std::path::Path) -> Result<Vec<CodeownersEntry>> {
+    // Collect all CODEOWNERS files in the specified path, skipping configured directories
+    let skip_dirs = AppConfig::fetch()
+        .map(|config| config.skip_dirs)
+        .unwrap_or_default();
+    let codeowners_files = find_codeowners_files_skipping(repo, &skip_dirs)?;
 
     // Parse each CODEOWNERS file and collect entries
-    let parsed_codeowners: Vec<CodeownersEntry> = codeowners_files
+    Ok(codeowners_files
         .iter()
         .filter_map(|file| {
             let parsed = parse_codeowners(file).ok()?;
             Some(parsed)
         })
         .flatten()
-        .collect();
+        .collect())
+}
+
+/// Build the cache for a repository in memory, without storing it
+///
+/// Only the matching modes are set; everything else uses its default.
+#[cfg(test)]
+pub fn analyze_repo(
+    repo: &std::path::Path, case_insensitive: bool, owner_merge: OwnerMerge, anchor: PatternAnchor,
+) -> Result<CodeownersCache> {
+    let settings = BuildSettings::new(case_insensitive, owner_merge, anchor);
+    analyze_repo_with(repo, &settings)
+}
+
+/// Build the cache for a repository in memory with the given settings
+pub(crate) fn analyze_repo_with(
+    repo: &std::path::Path, settings: &BuildSettings,
+) -> Result<CodeownersCache> {
+    let mut parsed_codeowners = parse_repo_entries(repo)?;
+    apply_owner_aliases(&mut parsed_codeowners, &settings.owner_aliases)?;
 
     // Collect all files in the specified path
-    let files = find_files(repo)?;
+    let (files, walk_errors) = find_files(repo, settings.follow_links)?;
+    if let Some(warning) = format_walk_errors(&walk_errors, false) {
+        eprintln!("{}", warning);
+    }
+    let files = if settings.dedupe_paths {
+        dedupe_paths(files, true)
+    } else {
+        files
+    };
 
     // Get the hash of the repository
     let hash = get_repo_hash(repo)?;
 
     // Build the cache from the parsed CODEOWNERS entries and the files
-    let cache = build_cache(parsed_codeowners, files, hash)?;
-
-    // Store the cache in the specified file
-    store_cache(&cache, &repo.join(cache_file), CacheEncoding::Bincode)?;
-
-    println!("CODEOWNERS parsing completed successfully");
+    let mut cache = build_cache(
+        parsed_codeowners,
+        files,
+        hash,
+        settings.case_insensitive,
+        settings.owner_merge,
+        settings.anchor,
+        repo,
+    )?;
+    cache.follow_links = settings.follow_links;
+    cache.dedupe_paths = settings.dedupe_paths;
+    set_owner_aliases(&mut cache, settings.owner_aliases.clone());
+    if !settings.identity_map.is_empty() {
+        set_identity_map(
+            &mut cache,
+            &IdentityMap::new(settings.identity_map.clone())?,
+        );
+    }
 
     Ok(cache)
 }
@@ -1,20 +1,70 @@
-use crate::utils::error::Result;
+use crate::utils::error::{Error, ErrorKind, Result};
+use std::collections::HashMap;
 use std::path::Path;
 
-use super::types::{CodeownersEntry, Owner, OwnerType, Tag};
+use super::types::{CodeownersEntry, FileEntry, Owner, Tag};
 
 /// Parse CODEOWNERS
 pub fn parse_codeowners(source_path: &Path) -> Result<Vec<CodeownersEntry>> {
     let content = std::fs::read_to_string(source_path)?;
 
+    parse_codeowners_str(&content, source_path)
+}
+
+/// Parse CODEOWNERS content that is already in memory
+///
+/// `source_label` is recorded as the `source_file` of every entry.
+pub fn parse_codeowners_str(content: &str, source_label: &Path) -> Result<Vec<CodeownersEntry>> {
     content
         .lines()
         .enumerate()
-        .filter_map(|(line_num, line)| parse_line(line, line_num, source_path).transpose())
+        .filter_map(|(line_num, line)| parse_line(line, line_num, source_label).transpose())
         .collect()
 }
 
+/// Characters a backslash escapes in a pattern instead of separating paths
+const ESCAPABLE: &[char] = &['*', '?', '[', ']', '{', '}', '!', '#', ' ', '\\'];
+
+/// `pattern` with backslash path separators, as written on Windows, replaced by `/`
+///
+/// A backslash escaping a glob metacharacter, `#` or a space is kept, e.g. in
+/// `\\#notes.md` or `docs\\[draft\\].md`.
+fn normalize_pattern_separators(pattern: &str) -> String {
+    let mut normalized = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            normalized.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some(&next) if ESCAPABLE.contains(&next) => {
+                normalized.push(c);
+                normalized.push(next);
+                chars.next();
+            }
+            _ => normalized.push('/'),
+        }
+    }
+    normalized
+}
+
+/// Warning for a duplicate owner or tag dropped from the 0-based line `line_num`
+///
+/// The location is shown as `path:line` with lines counted from 1, like editors do.
+fn duplicate_warning(source_path: &Path, line_num: usize, duplicate: &str) -> String {
+    format!(
+        "{}:{}: dropping duplicate {}",
+        source_path.display(),
+        line_num + 1,
+        duplicate
+    )
+}
+
 /// Parse a line of CODEOWNERS
+///
+/// A line with a pattern but no owners gives an entry that clears ownership,
+/// see [`CodeownersEntry::clears_ownership`].
 pub fn parse_line(
     line: &str, line_num: usize, source_path: &Path,
 ) -> Result<Option<CodeownersEntry>> {
@@ -24,45 +74,74 @@ pub fn parse_line(
         return Ok(None);
     }
 
-    // Split the line by whitespace into a series of tokens
-    let tokens: Vec<&str> = trimmed.split_whitespace().collect();
+    // Split the line by whitespace into a series of tokens; a double-quoted
+    // pattern is one token, keeping its whitespace but not its quotes
+    let tokens: Vec<&str> = match split_quoted_pattern(trimmed) {
+        Some((pattern, rest)) => std::iter::once(pattern)
+            .chain(rest.split_whitespace())
+            .collect(),
+        None => trimmed.split_whitespace().collect(),
+    };
     if tokens.is_empty() {
         return Ok(None);
     }
 
     // The first token is the pattern
-    let pattern = tokens[0].to_string();
+    let pattern = normalize_pattern_separators(tokens[0]);
 
     let mut owners: Vec<Owner> = Vec::new();
     let mut tags: Vec<Tag> = Vec::new();
 
     let mut i = 1; // Start after the pattern
 
-    // Collect owners until a token starts with '#'
+    // Collect owners until a token starts with '#', keeping the first of any duplicates
     while i < tokens.len() && !tokens[i].starts_with('#') {
-        owners.push(parse_owner(tokens[i])?);
+        let owner = parse_owner(tokens[i])?;
+        if owners.contains(&owner) {
+            log::warn!(
+                "{}",
+                duplicate_warning(source_path, line_num, &format!("owner {}", owner))
+            );
+        } else {
+            owners.push(owner);
+        }
         i += 1;
     }
 
+    let mut comment: Option<String> = None;
+
     // Collect tags with lookahead to check for comments
     while i < tokens.len() {
         let token = tokens[i];
         if token.starts_with('#') {
             if token == "#" {
-                // Comment starts, break
+                // Comment starts, capture the remaining tokens and break
+                comment = Some(tokens[i + 1..].join(" "));
                 break;
             } else {
                 // Check if the next token is not a tag (doesn't start with '#')
                 let next_is_non_tag = i + 1 < tokens.len() && !tokens[i + 1].starts_with('#');
                 if next_is_non_tag {
-                    // This token is part of the comment, break
+                    // This token is part of the comment, capture it without the marker
+                    let mut comment_tokens = vec![&token[1..]];
+                    comment_tokens.extend(&tokens[i + 1..]);
+                    comment = Some(comment_tokens.join(" "));
                     break;
                 }
-                tags.push(Tag(token[1..].to_string()));
+                let tag = Tag(token[1..].to_string());
+                if tags.contains(&tag) {
+                    log::warn!(
+                        "{}",
+                        duplicate_warning(source_path, line_num, &format!("tag #{}", tag.0))
+                    );
+                } else {
+                    tags.push(tag);
+                }
                 i += 1;
             }
         } else {
             // Non-tag, part of comment
+            comment = Some(tokens[i..].join(" "));
             break;
         }
     }
@@ -73,36 +152,101 @@ pub fn parse_line(
         pattern,
         owners,
         tags,
+        comment: comment.filter(|c| !c.is_empty()),
+        raw: trimmed.to_string(),
     }))
 }
 
+/// Split a line starting with a double-quoted pattern into the pattern and the rest
+///
+/// Returns `None` unless the line starts with a non-empty quoted pattern whose
+/// closing quote ends the token.
+fn split_quoted_pattern(line: &str) -> Option<(&str, &str)> {
+    let quoted = line.strip_prefix('"')?;
+    let end = quoted.find('"')?;
+    let (pattern, rest) = (&quoted[..end], &quoted[end + 1..]);
+    if pattern.is_empty() || rest.starts_with(|c: char| !c.is_whitespace()) {
+        return None;
+    }
+    Some((pattern, rest))
+}
+
 /// Parse an owner string into an Owner struct
 pub fn parse_owner(owner_str: &str) -> Result<Owner> {
-    let identifier = owner_str.to_string();
-    let owner_type = if identifier.eq_ignore_ascii_case("NOOWNER") {
-        OwnerType::Unowned
-    } else if owner_str.starts_with('@') {
-        let parts: Vec<&str> = owner_str[1..].split('/').collect();
-        if parts.len() == 2 {
-            OwnerType::Team
-        } else {
-            OwnerType::User
-        }
-    } else if owner_str.contains('@') {
-        OwnerType::Email
-    } else {
-        OwnerType::Unknown
-    };
+    let Ok(owner) = owner_str.parse::<Owner>();
+    Ok(owner)
+}
 
-    Ok(Owner {
-        identifier,
-        owner_type,
+/// Parse an owner, first replacing an aliased identifier with its target
+///
+/// Identifiers without an alias are parsed as-is.
+pub fn parse_owner_with_aliases(
+    owner_str: &str, aliases: &HashMap<String, String>,
+) -> Result<Owner> {
+    let canonical = aliases
+        .get(owner_str)
+        .map(String::as_str)
+        .unwrap_or(owner_str);
+    parse_owner(canonical)
+}
+
+/// Load an owner alias map from a TOML file
+///
+/// Each key is an old identifier and its value the identifier it should be
+/// replaced with, e.g. `"@org/old-team" = "@org/new-team"`.
+pub fn load_owner_aliases(path: &Path) -> Result<HashMap<String, String>> {
+    let content = std::fs::read_to_string(path)?;
+    toml::from_str(&content).map_err(|e| {
+        Error::with_kind(
+            ErrorKind::Validation,
+            &format!("Invalid alias map {}: {}", path.display(), e),
+        )
     })
 }
 
+/// Canonicalize the owners of parsed entries using an alias map
+///
+/// Owners that collapse into the same identifier within an entry are kept once.
+pub fn apply_owner_aliases(
+    entries: &mut [CodeownersEntry], aliases: &HashMap<String, String>,
+) -> Result<()> {
+    for entry in entries {
+        let mut owners: Vec<Owner> = Vec::with_capacity(entry.owners.len());
+        for owner in &entry.owners {
+            let owner = parse_owner_with_aliases(&owner.identifier, aliases)?;
+            if !owners.contains(&owner) {
+                owners.push(owner);
+            }
+        }
+        entry.owners = owners;
+    }
+
+    Ok(())
+}
+
+/// Canonicalize the owners of inline `!!!CODEOWNERS` declarations using an alias map
+///
+/// Rule owners are aliased by [`apply_owner_aliases`] when the CODEOWNERS
+/// files are parsed, but inline owners are only known once the files are
+/// resolved. Files whose owners come from rules are left as they are.
+pub fn apply_owner_aliases_to_inline(files: &mut [FileEntry], aliases: &HashMap<String, String>) {
+    for file in files.iter_mut().filter(|file| file.inline_override) {
+        let mut owners: Vec<Owner> = Vec::with_capacity(file.owners.len());
+        for owner in &file.owners {
+            let identifier = aliases.get(&owner.identifier).unwrap_or(&owner.identifier);
+            let Ok(owner) = identifier.parse::<Owner>();
+            if !owners.contains(&owner) {
+                owners.push(owner);
+            }
+        }
+        file.owners = owners;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::types::OwnerType;
 
     #[test]
     fn test_parse_owner_user() -> Result<()> {
@@ -140,6 +284,11 @@ mod tests {
         assert_eq!(owner.identifier, "@o/t");
         assert!(matches!(owner.owner_type, OwnerType::Team));
 
+        // Nested sub-teams
+        let owner = parse_owner("@org/team/subteam")?;
+        assert_eq!(owner.identifier, "@org/team/subteam");
+        assert!(matches!(owner.owner_type, OwnerType::Team));
+
         Ok(())
     }
 
@@ -266,6 +415,60 @@ mod tests {
         assert_eq!(entry.tags[0].0, "test");
         assert_eq!(entry.line_number, 1);
         assert_eq!(entry.source_file, source_path);
+        assert_eq!(entry.raw, "*.js @qa-team @bob #test");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_line_pattern_only_clears_ownership() -> Result<()> {
+        let source_path = Path::new("/test/CODEOWNERS");
+
+        let entry = parse_line("*.generated", 1, source_path)?.unwrap();
+        assert_eq!(entry.pattern, "*.generated");
+        assert!(entry.clears_ownership());
+        let entry = parse_line("*.generated #generated", 1, source_path)?.unwrap();
+        assert!(entry.clears_ownership());
+
+        // Owned rules, including explicit NOOWNER markers, don't
+        assert!(!parse_line("*.rs @rust-team", 1, source_path)?
+            .unwrap()
+            .clears_ownership());
+        assert!(!parse_line("*.lock NOOWNER", 1, source_path)?
+            .unwrap()
+            .clears_ownership());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_line_keeps_raw_line() -> Result<()> {
+        let source_path = Path::new("/test/CODEOWNERS");
+        let line = "  \"docs/Getting Started.md\"   @docs-team  # keep in sync  ";
+        let entry = parse_line(line, 4, source_path)?.unwrap();
+
+        assert_eq!(
+            entry.raw,
+            "\"docs/Getting Started.md\"   @docs-team  # keep in sync"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_line_normalizes_backslash_separators() -> Result<()> {
+        let source_path = Path::new("/test/CODEOWNERS");
+        let pattern = |line: &str| -> Result<String> {
+            Ok(parse_line(line, 0, source_path)?.unwrap().pattern)
+        };
+
+        assert_eq!(pattern(r"docs\api\ @docs-team")?, "docs/api/");
+        assert_eq!(pattern(r"\src\main.rs @rust-team")?, "/src/main.rs");
+        // Escapes are kept, also where a separator could be meant
+        assert_eq!(pattern(r"src\*.rs @rust-team")?, r"src\*.rs");
+        assert_eq!(pattern(r"notes\#1.md @alice")?, r"notes\#1.md");
+        assert_eq!(pattern(r"docs\[draft\].md @alice")?, r"docs\[draft\].md");
+        assert_eq!(pattern(r"web\\*.css @alice")?, r"web\\*.css");
 
         Ok(())
     }
@@ -347,6 +550,43 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_line_drops_duplicate_owners_and_tags() -> Result<()> {
+        let source_path = Path::new("/test/CODEOWNERS");
+        let entry =
+            parse_line("*.rs @team @alice @team #rust #rust #core", 0, source_path)?.unwrap();
+
+        let owners: Vec<&str> = entry.owners.iter().map(|o| o.identifier.as_str()).collect();
+        assert_eq!(owners, vec!["@team", "@alice"]);
+        let tags: Vec<&str> = entry.tags.iter().map(|t| t.0.as_str()).collect();
+        assert_eq!(tags, vec!["rust", "core"]);
+
+        assert_eq!(
+            duplicate_warning(source_path, 0, "owner @team"),
+            "/test/CODEOWNERS:1: dropping duplicate owner @team"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_line_quoted_pattern_keeps_whitespace() -> Result<()> {
+        let source_path = Path::new("/test/CODEOWNERS");
+        let entry = parse_line("\"  weird file.txt\" @team #odd", 3, source_path)?.unwrap();
+        assert_eq!(entry.pattern, "  weird file.txt");
+        assert_eq!(entry.owners.len(), 1);
+        assert_eq!(entry.owners[0].identifier, "@team");
+        assert_eq!(entry.tags[0].0, "odd");
+
+        // Without a separate closing quote the line is split as usual
+        let entry = parse_line("\"a\"b @team", 4, source_path)?.unwrap();
+        assert_eq!(entry.pattern, "\"a\"b");
+        let entry = parse_line("\"unclosed @team", 5, source_path)?.unwrap();
+        assert_eq!(entry.pattern, "\"unclosed");
+
+        Ok(())
+    }
+
     #[test]
     fn test_parse_line_with_pound_tag_edge_case() -> Result<()> {
         let source_path = Path::new("/test/CODEOWNERS");
@@ -360,6 +600,106 @@ mod tests {
         assert_eq!(entry.owners.len(), 1);
         assert_eq!(entry.owners[0].identifier, "@docs-team");
         assert_eq!(entry.tags.len(), 0); // No tags, just a comment
+        assert_eq!(entry.comment.as_deref(), Some("not a tag"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_line_captures_trailing_comment() -> Result<()> {
+        let source_path = Path::new("/test/CODEOWNERS");
+        let result = parse_line(
+            "/src/old/ @org/legacy #legacy #core # @deprecated use src/new/",
+            8,
+            source_path,
+        )?;
+
+        let entry = result.unwrap();
+        assert_eq!(entry.tags.len(), 2);
+        assert_eq!(entry.tags[0].0, "legacy");
+        assert_eq!(entry.tags[1].0, "core");
+        assert_eq!(entry.comment.as_deref(), Some("@deprecated use src/new/"));
+
+        // No comment at all
+        let entry = parse_line("*.rs @rust-team #rust", 9, source_path)?.unwrap();
+        assert_eq!(entry.tags.len(), 1);
+        assert!(entry.comment.is_none());
+
+        // Bare comment marker without text
+        let entry = parse_line("*.rs @rust-team #", 10, source_path)?.unwrap();
+        assert!(entry.comment.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_codeowners_str_multi_line() -> Result<()> {
+        let content = "# Global owners\n\
+                       * @org/everyone\n\
+                       \n\
+                       /docs/ @docs-team #docs\n\
+                       *.rs @rust-team @alice #rust #core\n";
+        let source_label = Path::new("<stdin>");
+
+        let entries = parse_codeowners_str(content, source_label)?;
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].pattern, "*");
+        assert_eq!(entries[0].line_number, 1);
+        assert_eq!(entries[0].owners[0].identifier, "@org/everyone");
+
+        assert_eq!(entries[1].pattern, "/docs/");
+        assert_eq!(entries[1].line_number, 3);
+        assert_eq!(entries[1].tags, vec![Tag("docs".to_string())]);
+
+        assert_eq!(entries[2].pattern, "*.rs");
+        assert_eq!(entries[2].owners.len(), 2);
+        assert_eq!(entries[2].tags.len(), 2);
+
+        assert!(entries
+            .iter()
+            .all(|entry| entry.source_file == Path::new("<stdin>")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_owner_aliases() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let alias_file = temp_dir.path().join("aliases.toml");
+        std::fs::write(
+            &alias_file,
+            "\"@org/old-team\" = \"@org/new-team\"\n\"@org/legacy\" = \"@org/new-team\"\n",
+        )?;
+        let aliases = load_owner_aliases(&alias_file)?;
+
+        let mut entries = parse_codeowners_str(
+            "*.rs @org/old-team @org/legacy\n*.md @org/legacy @alice\n",
+            Path::new("/test/CODEOWNERS"),
+        )?;
+        apply_owner_aliases(&mut entries, &aliases)?;
+
+        // Both aliases collapse into the single target owner
+        assert_eq!(entries[0].owners.len(), 1);
+        assert_eq!(entries[0].owners[0].identifier, "@org/new-team");
+        assert_eq!(entries[0].owners[0].owner_type, OwnerType::Team);
+
+        // Unmapped owners pass through
+        assert_eq!(entries[1].owners.len(), 2);
+        assert_eq!(entries[1].owners[0].identifier, "@org/new-team");
+        assert_eq!(entries[1].owners[1].identifier, "@alice");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_owner_aliases_invalid() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let alias_file = temp_dir.path().join("aliases.toml");
+        std::fs::write(&alias_file, "not valid toml =")?;
+
+        let err = load_owner_aliases(&alias_file).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Validation);
 
         Ok(())
     }
@@ -1,34 +1,282 @@
 use super::{
     inline_parser::detect_inline_codeowners,
-    types::{CodeownersEntryMatcher, Tag},
+    smart_iter::{chunk_size, parallel_threshold, SmartIter},
+    types::{
+        is_within_codeowners_dir, normalize_codeowners_pattern, CodeownersEntry,
+        CodeownersEntryMatcher, OwnerMerge, Tag,
+    },
 };
-use crate::utils::error::{Error, Result};
+use crate::utils::error::{Error, ErrorKind, Result};
+use ignore::overrides::OverrideBuilder;
 
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use super::types::Owner;
+use super::types::{FileEntry, Owner};
 
 /// Find both owners and tags for a specific file based on all parsed CODEOWNERS entries
+///
+/// With [`OwnerMerge::Winner`] they come from the highest-priority matching
+/// entry only; with [`OwnerMerge::Union`] from every matching entry.
 pub fn find_owners_and_tags_for_file(
-    file_path: &Path, entries: &[CodeownersEntryMatcher],
+    file_path: &Path, entries: &[CodeownersEntryMatcher], owner_merge: OwnerMerge,
 ) -> Result<(Vec<Owner>, Vec<Tag>)> {
+    resolve_file(file_path, entries, owner_merge).map(|file| (file.owners, file.tags))
+}
+
+/// Resolve a file's owners and tags, recording whether an inline declaration decided them
+pub(crate) fn resolve_file(
+    file_path: &Path, entries: &[CodeownersEntryMatcher], owner_merge: OwnerMerge,
+) -> Result<FileEntry> {
+    let file_entry = |owners, tags, inline_override| FileEntry {
+        path: file_path.to_path_buf(),
+        owners,
+        tags,
+        inline_override,
+    };
+
     // First, check for inline CODEOWNERS declaration (highest priority)
     if let Some(inline_entry) = detect_inline_codeowners(file_path)? {
-        return Ok((inline_entry.owners, inline_entry.tags));
+        return Ok(file_entry(inline_entry.owners, inline_entry.tags, true));
     }
 
     // Early return if no entries
     if entries.is_empty() {
-        return Ok((Vec::new(), Vec::new()));
+        return Ok(file_entry(Vec::new(), Vec::new(), false));
+    }
+
+    let matching = find_matching_entries_for_file(file_path, entries)?;
+    let (owners, tags) = match owner_merge {
+        // Extract both owners and tags from the highest priority entry, if any
+        OwnerMerge::Winner => matching
+            .first()
+            .map(|entry| (entry.owners.clone(), entry.tags.clone()))
+            .unwrap_or_default(),
+        // Combine all matching entries, highest priority first
+        OwnerMerge::Union => {
+            let mut owners: Vec<Owner> = Vec::new();
+            let mut tags: Vec<Tag> = Vec::new();
+            for entry in matching {
+                for owner in &entry.owners {
+                    if !owners.contains(owner) {
+                        owners.push(owner.clone());
+                    }
+                }
+                for tag in &entry.tags {
+                    if !tags.contains(tag) {
+                        tags.push(tag.clone());
+                    }
+                }
+            }
+            (owners, tags)
+        }
+    };
+    Ok(file_entry(owners, tags, false))
+}
+
+/// Resolve owners and tags for many files at once, in parallel
+///
+/// Results are returned in the same order as `paths`. Paths that cannot be
+/// resolved (e.g. without a parent directory) get no owners and no tags.
+pub fn resolve_batch(
+    paths: &[PathBuf], matchers: &[CodeownersEntryMatcher], owner_merge: OwnerMerge,
+) -> Vec<(PathBuf, Vec<Owner>, Vec<Tag>)> {
+    resolve_batch_with(paths, matchers, owner_merge, |_| {})
+        .into_iter()
+        .map(|file| (file.path, file.owners, file.tags))
+        .collect()
+}
+
+/// Same as [`resolve_batch`] but returning file entries, calling `on_resolved` after each file
+pub(crate) fn resolve_batch_with<F>(
+    paths: &[PathBuf], matchers: &[CodeownersEntryMatcher], owner_merge: OwnerMerge, on_resolved: F,
+) -> Vec<FileEntry>
+where
+    F: Fn(&Path) + Sync,
+{
+    paths
+        .smart_iter(parallel_threshold(), chunk_size())
+        .map(|path| {
+            let file = resolve_file(path, matchers, owner_merge).unwrap_or_else(|_| FileEntry {
+                path: path.clone(),
+                owners: Vec::new(),
+                tags: Vec::new(),
+                inline_override: false,
+            });
+            on_resolved(path);
+            file
+        })
+        .collect()
+}
+
+/// Resolve owners and tags for a directory from the files below it
+///
+/// Combines the owners and tags of every file in `files` under `dir` with those
+/// of the rule matching `dir` itself, if any, ordered by how many of the files
+/// carry them (most common first). `dir` must be joined to the repository the
+/// same way as the cached file paths.
+pub fn resolve_directory(
+    dir: &Path, files: &[FileEntry], entries: &[CodeownersEntryMatcher],
+) -> Result<(Vec<Owner>, Vec<Tag>)> {
+    let mut owners: HashMap<&Owner, usize> = HashMap::new();
+    let mut tags: HashMap<&Tag, usize> = HashMap::new();
+
+    // A rule such as `src/api` can own the directory without owning every file in it
+    if let Some(rule) = find_matching_entries_for_file(dir, entries)?.first() {
+        rule.owners.iter().for_each(|owner| {
+            owners.entry(owner).or_default();
+        });
+        rule.tags.iter().for_each(|tag| {
+            tags.entry(tag).or_default();
+        });
+    }
+
+    for file in files.iter().filter(|file| file.path.starts_with(dir)) {
+        for owner in &file.owners {
+            *owners.entry(owner).or_default() += 1;
+        }
+        for tag in &file.tags {
+            *tags.entry(tag).or_default() += 1;
+        }
     }
 
+    Ok((by_count(owners), by_count(tags)))
+}
+
+/// Values ordered by count (descending), then by value
+fn by_count<T: Ord + Clone>(counts: HashMap<&T, usize>) -> Vec<T> {
+    let mut counted: Vec<_> = counts.into_iter().collect();
+    counted.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+    counted
+        .into_iter()
+        .map(|(value, _)| value.clone())
+        .collect()
+}
+
+/// Check whether a single CODEOWNERS pattern matches a file
+///
+/// `codeowners_dir` is the directory of the CODEOWNERS file the pattern would
+/// live in; `file_path` is either absolute under it or relative to it. The
+/// pattern is interpreted exactly as when building a cache, so this can be used
+/// to unit-test CODEOWNERS rules without parsing a whole repository.
+///
+/// # Examples
+///
+/// ```
+/// use codeinput::core::resolver::matches_pattern;
+/// use std::path::Path;
+///
+/// let repo = Path::new("/repo");
+///
+/// assert!(matches_pattern("*.rs", repo, Path::new("/repo/src/main.rs"))?);
+/// assert!(matches_pattern("/docs/", repo, Path::new("/repo/docs/api/readme.md"))?);
+/// assert!(matches_pattern("src/*.rs", repo, Path::new("src/lib.rs"))?);
+///
+/// assert!(!matches_pattern("*.rs", repo, Path::new("/repo/README.md"))?);
+/// assert!(!matches_pattern("/docs/", repo, Path::new("/repo/src/docs.rs"))?);
+/// # Ok::<(), codeinput::utils::error::Error>(())
+/// ```
+///
+/// Invalid patterns are reported as validation errors:
+///
+/// ```
+/// use codeinput::core::resolver::matches_pattern;
+/// use std::path::Path;
+///
+/// assert!(matches_pattern("src/[", Path::new("/repo"), Path::new("/repo/src/a.rs")).is_err());
+/// ```
+pub fn matches_pattern(pattern: &str, codeowners_dir: &Path, file_path: &Path) -> Result<bool> {
+    let invalid = |e: ignore::Error| {
+        Error::with_kind(
+            ErrorKind::Validation,
+            &format!("Invalid pattern '{}': {}", pattern, e),
+        )
+    };
+
+    let mut builder = OverrideBuilder::new(codeowners_dir);
+    builder
+        .add(&normalize_codeowners_pattern(pattern))
+        .map_err(invalid)?;
+    let matcher = builder.build().map_err(invalid)?;
+
+    Ok(is_within_codeowners_dir(codeowners_dir, file_path)
+        && matcher.matched(file_path, false).is_whitelist())
+}
+
+/// Entries whose pattern is not a valid glob, with the reason, in entry order
+pub fn invalid_patterns(entries: &[CodeownersEntry]) -> Vec<(&CodeownersEntry, String)> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let codeowners_dir = entry.source_file.parent().unwrap_or(Path::new(""));
+            let mut builder = OverrideBuilder::new(codeowners_dir);
+            let error = builder
+                .add(&normalize_codeowners_pattern(&entry.pattern))
+                .and_then(|builder| builder.build())
+                .err()?;
+            Some((entry, error.to_string()))
+        })
+        .collect()
+}
+
+/// Fail if any entry's pattern is not a valid glob
+///
+/// The resolvers skip such entries with a warning on stderr; this instead
+/// returns a validation error listing every one of them, e.g. `src/[invalid`.
+pub fn check_patterns(entries: &[CodeownersEntry]) -> Result<()> {
+    let invalid: Vec<String> = invalid_patterns(entries)
+        .into_iter()
+        .map(|(entry, error)| {
+            format!(
+                "{}:{} '{}': {}",
+                entry.source_file.display(),
+                entry.line_number,
+                entry.pattern,
+                error
+            )
+        })
+        .collect();
+
+    if invalid.is_empty() {
+        return Ok(());
+    }
+    Err(Error::with_kind(
+        ErrorKind::Validation,
+        &format!(
+            "{} invalid CODEOWNERS patterns:\n  {}",
+            invalid.len(),
+            invalid.join("\n  ")
+        ),
+    ))
+}
+
+/// Find all CODEOWNERS entries matching a file, highest priority first
+///
+/// The first entry, if any, is the rule that decides the file's owners and
+/// tags. Inline `!!!CODEOWNERS` declarations are not considered here.
+pub fn find_matching_entries_for_file<'a>(
+    file_path: &Path, entries: &'a [CodeownersEntryMatcher],
+) -> Result<Vec<&'a CodeownersEntryMatcher>> {
+    Ok(find_matching_indices_for_file(file_path, entries)?
+        .into_iter()
+        .map(|index| &entries[index])
+        .collect())
+}
+
+/// Indices into `entries` of the entries matching a file, highest priority first
+///
+/// Like [`find_matching_entries_for_file`], for callers keeping per-rule state.
+pub(crate) fn find_matching_indices_for_file(
+    file_path: &Path, entries: &[CodeownersEntryMatcher],
+) -> Result<Vec<usize>> {
     let target_dir = file_path
         .parent()
         .ok_or_else(|| Error::new("file path has no parent directory"))?;
 
     let mut candidates: Vec<_> = entries
         .iter()
-        .filter_map(|entry| {
+        .enumerate()
+        .filter_map(|(index, entry)| {
             let codeowners_dir = match entry.source_file.parent() {
                 Some(dir) => dir,
                 None => {
@@ -40,29 +288,21 @@ pub fn find_owners_and_tags_for_file(
                 }
             };
 
-            // Check if the CODEOWNERS directory is an ancestor of the target directory
-            if !target_dir.starts_with(codeowners_dir) {
+            // Check if the directory the pattern is anchored at is an ancestor of the target directory
+            if !target_dir.starts_with(&entry.anchor_dir) {
                 return None;
             }
 
-            // Calculate the depth as the number of components in the relative path from codeowners_dir to target_dir
-            let rel_path = match target_dir.strip_prefix(codeowners_dir) {
-                Ok(p) => p,
-                Err(_) => return None, // Should not happen due to starts_with check
+            // Calculate the depth as the number of components in the relative path from codeowners_dir to target_dir.
+            // Rules anchored above their CODEOWNERS file can match outside of it, and then rank last
+            let depth = match target_dir.strip_prefix(codeowners_dir) {
+                Ok(rel_path) => rel_path.components().count(),
+                Err(_) => usize::MAX,
             };
 
-            let depth = rel_path.components().count();
-
             // Check if the pattern matches the target file
-            let matches = {
-                entry
-                    .override_matcher
-                    .matched(file_path, false)
-                    .is_whitelist()
-            };
-
-            if matches {
-                Some((entry, depth))
+            if entry.matches(file_path) {
+                Some((index, entry, depth))
             } else {
                 None
             }
@@ -71,10 +311,10 @@ pub fn find_owners_and_tags_for_file(
 
     // Sort the candidates by depth, source file, and line number
     candidates.sort_unstable_by(|a, b| {
-        let a_entry = a.0;
-        let a_depth = a.1;
-        let b_entry = b.0;
-        let b_depth = b.1;
+        let a_entry = a.1;
+        let a_depth = a.2;
+        let b_entry = b.1;
+        let b_depth = b.2;
 
         // Primary sort by depth (ascending)
         a_depth
@@ -85,11 +325,7 @@ pub fn find_owners_and_tags_for_file(
             .then_with(|| b_entry.line_number.cmp(&a_entry.line_number))
     });
 
-    // Extract both owners and tags from the highest priority entry, if any
-    Ok(candidates
-        .first()
-        .map(|(entry, _)| (entry.owners.clone(), entry.tags.clone()))
-        .unwrap_or_default())
+    Ok(candidates.into_iter().map(|(index, _, _)| index).collect())
 }
 
 #[cfg(test)]
@@ -115,6 +351,7 @@ mod tests {
     ) -> CodeownersEntryMatcher {
         let source_path = PathBuf::from(source_file);
         let codeowners_dir = source_path.parent().unwrap();
+        let anchor_dir = codeowners_dir.to_path_buf();
 
         let mut builder = OverrideBuilder::new(codeowners_dir);
         builder.add(pattern).unwrap();
@@ -126,6 +363,7 @@ mod tests {
             pattern: pattern.to_string(),
             owners,
             tags,
+            anchor_dir,
             override_matcher,
         }
     }
@@ -134,7 +372,8 @@ mod tests {
     fn test_find_owners_and_tags_for_file_empty_entries() {
         let entries = vec![];
         let file_path = Path::new("/project/src/main.rs");
-        let result = find_owners_and_tags_for_file(file_path, &entries).unwrap();
+        let result =
+            find_owners_and_tags_for_file(file_path, &entries, OwnerMerge::Winner).unwrap();
         assert!(result.0.is_empty());
         assert!(result.1.is_empty());
     }
@@ -152,7 +391,8 @@ mod tests {
         )];
 
         let file_path = Path::new("/project/src/main.rs");
-        let result = find_owners_and_tags_for_file(file_path, &entries).unwrap();
+        let result =
+            find_owners_and_tags_for_file(file_path, &entries, OwnerMerge::Winner).unwrap();
 
         assert_eq!(result.0.len(), 1);
         assert_eq!(result.0[0], expected_owner);
@@ -185,7 +425,8 @@ mod tests {
         ];
 
         let file_path = Path::new("/project/src/main.rs");
-        let result = find_owners_and_tags_for_file(file_path, &entries).unwrap();
+        let result =
+            find_owners_and_tags_for_file(file_path, &entries, OwnerMerge::Winner).unwrap();
 
         assert_eq!(result.0.len(), 1);
         assert_eq!(result.0[0], src_owner);
@@ -218,7 +459,8 @@ mod tests {
         ];
 
         let file_path = Path::new("/project/src/main.rs");
-        let result = find_owners_and_tags_for_file(file_path, &entries).unwrap();
+        let result =
+            find_owners_and_tags_for_file(file_path, &entries, OwnerMerge::Winner).unwrap();
 
         assert_eq!(result.0.len(), 1);
         assert_eq!(result.0[0], specific_owner);
@@ -237,11 +479,181 @@ mod tests {
         )];
 
         let file_path = Path::new("/project/src/main.rs");
-        let result = find_owners_and_tags_for_file(file_path, &entries).unwrap();
+        let result =
+            find_owners_and_tags_for_file(file_path, &entries, OwnerMerge::Winner).unwrap();
 
         assert_eq!(result.0.len(), 1);
         assert_eq!(result.0[0].identifier, "@team2");
         assert_eq!(result.1.len(), 1);
         assert_eq!(result.1[0].0, "tag2");
     }
+
+    #[test]
+    fn test_owner_merge_winner_vs_union() {
+        let root_owner = create_test_owner("@root-team", OwnerType::Team);
+        let rust_owner = create_test_owner("@rust-team", OwnerType::Team);
+        let core_owner = create_test_owner("@core-team", OwnerType::Team);
+        let entries = vec![
+            create_test_codeowners_entry_matcher(
+                "/project/CODEOWNERS",
+                1,
+                "*",
+                vec![root_owner.clone()],
+                vec![create_test_tag("general")],
+            ),
+            create_test_codeowners_entry_matcher(
+                "/project/CODEOWNERS",
+                2,
+                "src/*.rs",
+                vec![rust_owner.clone(), root_owner.clone()],
+                vec![create_test_tag("rust")],
+            ),
+            create_test_codeowners_entry_matcher(
+                "/project/src/CODEOWNERS",
+                1,
+                "*.rs",
+                vec![core_owner.clone()],
+                vec![create_test_tag("rust")],
+            ),
+        ];
+        let file_path = Path::new("/project/src/main.rs");
+
+        let (owners, tags) =
+            find_owners_and_tags_for_file(file_path, &entries, OwnerMerge::Winner).unwrap();
+        assert_eq!(owners, vec![core_owner.clone()]);
+        assert_eq!(tags, vec![create_test_tag("rust")]);
+
+        // Every matching rule contributes, highest priority first, without duplicates
+        let (owners, tags) =
+            find_owners_and_tags_for_file(file_path, &entries, OwnerMerge::Union).unwrap();
+        assert_eq!(owners, vec![core_owner, rust_owner, root_owner.clone()]);
+        assert_eq!(
+            tags,
+            vec![create_test_tag("rust"), create_test_tag("general")]
+        );
+
+        // Both agree when a single rule matches
+        let readme = Path::new("/project/README.md");
+        assert_eq!(
+            find_owners_and_tags_for_file(readme, &entries, OwnerMerge::Union).unwrap(),
+            find_owners_and_tags_for_file(readme, &entries, OwnerMerge::Winner).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_resolve_batch_matches_per_file_resolution() {
+        let entries = vec![
+            create_test_codeowners_entry_matcher(
+                "/project/CODEOWNERS",
+                1,
+                "*.rs",
+                vec![create_test_owner("@rust-team", OwnerType::Team)],
+                vec![create_test_tag("rust")],
+            ),
+            create_test_codeowners_entry_matcher(
+                "/project/src/CODEOWNERS",
+                1,
+                "*.rs",
+                vec![create_test_owner("@core-team", OwnerType::Team)],
+                vec![create_test_tag("core")],
+            ),
+            create_test_codeowners_entry_matcher(
+                "/project/CODEOWNERS",
+                2,
+                "docs/**",
+                vec![create_test_owner("@docs-team", OwnerType::Team)],
+                vec![],
+            ),
+        ];
+
+        let paths: Vec<PathBuf> = [
+            "/project/main.rs",
+            "/project/src/lib.rs",
+            "/project/src/nested/mod.rs",
+            "/project/docs/guide.md",
+            "/project/README.md",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+        let batch = resolve_batch(&paths, &entries, OwnerMerge::Winner);
+
+        assert_eq!(batch.len(), paths.len());
+        for ((path, owners, tags), expected_path) in batch.iter().zip(&paths) {
+            assert_eq!(path, expected_path);
+            let (expected_owners, expected_tags) =
+                find_owners_and_tags_for_file(path, &entries, OwnerMerge::Winner).unwrap();
+            assert_eq!(owners, &expected_owners);
+            assert_eq!(tags, &expected_tags);
+        }
+        assert_eq!(batch[1].1[0].identifier, "@core-team");
+        assert!(batch[4].1.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_directory_aggregates_files_below_it() {
+        let api_leads = create_test_owner("@api-leads", OwnerType::Team);
+        let api_team = create_test_owner("@api-team", OwnerType::Team);
+        let db_team = create_test_owner("@db-team", OwnerType::Team);
+        let entries = vec![
+            create_test_codeowners_entry_matcher(
+                "/project/CODEOWNERS",
+                1,
+                "*",
+                vec![create_test_owner("@root-team", OwnerType::Team)],
+                vec![],
+            ),
+            create_test_codeowners_entry_matcher(
+                "/project/CODEOWNERS",
+                2,
+                "src/api",
+                vec![api_leads.clone()],
+                vec![create_test_tag("api")],
+            ),
+        ];
+        let file = |path: &str, owners: Vec<Owner>, tags: Vec<Tag>| FileEntry {
+            path: PathBuf::from(path),
+            owners,
+            tags,
+            inline_override: false,
+        };
+        let files = vec![
+            file(
+                "/project/src/api/routes.rs",
+                vec![api_team.clone()],
+                vec![create_test_tag("backend")],
+            ),
+            file(
+                "/project/src/api/db.rs",
+                vec![api_team.clone(), db_team.clone()],
+                vec![],
+            ),
+            file(
+                "/project/src/api/v1/schema.sql",
+                vec![db_team.clone()],
+                vec![],
+            ),
+            // Outside the directory, despite the shared prefix
+            file(
+                "/project/src/apiary.rs",
+                vec![create_test_owner("@bees", OwnerType::Team)],
+                vec![],
+            ),
+        ];
+
+        let (owners, tags) =
+            resolve_directory(Path::new("/project/src/api"), &files, &entries).unwrap();
+        assert_eq!(owners, vec![api_team, db_team, api_leads]);
+        assert_eq!(
+            tags,
+            vec![create_test_tag("backend"), create_test_tag("api")]
+        );
+
+        // An empty directory is owned by the rule matching it, if any
+        let (owners, tags) =
+            resolve_directory(Path::new("/project/docs"), &files, &entries).unwrap();
+        assert_eq!(owners[0].identifier, "@root-team");
+        assert!(tags.is_empty());
+    }
 }
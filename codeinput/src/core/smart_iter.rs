@@ -1,32 +1,102 @@
 #![allow(dead_code)]
 
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+use crate::utils::app_config::AppConfig;
+
+/// Inputs of at most this many items are iterated sequentially by default
+///
+/// Below this size rayon's scheduling overhead outweighs the gain of resolving
+/// files in parallel. `cargo bench --bench smart_iter_bench` measures the
+/// crossover on a given machine.
+pub(crate) const DEFAULT_PARALLEL_THRESHOLD: usize = 64;
+
+/// The configured `parallel_threshold`, or the default without a config
+pub(crate) fn parallel_threshold() -> usize {
+    AppConfig::fetch()
+        .map(|config| config.parallel_threshold)
+        .unwrap_or(DEFAULT_PARALLEL_THRESHOLD)
+}
+
+/// Parallel tasks per thread aimed for by [`adaptive_chunk_size`]
+///
+/// A few tasks per thread let threads that finish early take over work from
+/// slower ones, e.g. when some files match many more rules than others.
+const TASKS_PER_THREAD: usize = 4;
+
+/// The configured `chunk_size`, or 0 to pick one from the input size
+pub(crate) fn chunk_size() -> usize {
+    AppConfig::fetch()
+        .map(|config| config.chunk_size)
+        .unwrap_or(0)
+}
+
+/// Minimum number of items per parallel task when no `chunk_size` is configured
+///
+/// Splits `len` items into about [`TASKS_PER_THREAD`] tasks per thread. Larger
+/// inputs get larger chunks, so cheap items don't drown in scheduling overhead.
+/// `cargo bench --bench smart_iter_bench` compares chunk sizes on a given machine.
+fn adaptive_chunk_size(len: usize, available_threads: usize) -> usize {
+    len.div_ceil(available_threads.max(1) * TASKS_PER_THREAD)
+        .max(1)
+}
+
+/// Whether `len` items are worth iterating in parallel
+///
+/// Inputs of at most `threshold` items stay sequential, and so does every
+/// input when only one thread is available.
+fn should_parallelize(len: usize, threshold: usize, available_threads: usize) -> bool {
+    len > threshold && available_threads > 1
+}
 
 pub(crate) trait SmartIter<T: Send + Sync> {
-    fn smart_iter(&self, n: usize) -> SmartIterator<T>;
+    /// Iterate in parallel above `n` items, `chunk_size` items per task at least
+    ///
+    /// A `chunk_size` of 0 picks one from the input size and the available threads.
+    fn smart_iter(&self, n: usize, chunk_size: usize) -> SmartIterator<T>;
 }
 
 impl<T: Send + Sync> SmartIter<T> for [T] {
-    fn smart_iter(&self, n: usize) -> SmartIterator<T> {
-        if self.len() <= n {
-            SmartIterator::Sequential(self.iter())
+    fn smart_iter(&self, n: usize, chunk_size: usize) -> SmartIterator<T> {
+        let available_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        if should_parallelize(self.len(), n, available_threads) {
+            let chunk_size = match chunk_size {
+                0 => adaptive_chunk_size(self.len(), available_threads),
+                size => size,
+            };
+            SmartIterator::Parallel(self.par_iter().with_min_len(chunk_size))
         } else {
-            SmartIterator::Parallel(self.par_iter())
+            SmartIterator::Sequential(self.iter())
         }
     }
 }
 
 pub(crate) enum SmartIterator<'a, T: Send + Sync> {
     Sequential(std::slice::Iter<'a, T>),
-    Parallel(rayon::slice::Iter<'a, T>),
+    Parallel(rayon::iter::MinLen<rayon::slice::Iter<'a, T>>),
+}
+
+pub(crate) enum SmartMap<'a, T: Send + Sync, F> {
+    Parallel(rayon::iter::Map<rayon::iter::MinLen<rayon::slice::Iter<'a, T>>, F>),
+    Sequential(std::iter::Map<std::slice::Iter<'a, T>, F>),
 }
 
 pub(crate) enum SmartFilterMap<'a, T: Send + Sync, F> {
-    Parallel(rayon::iter::FilterMap<rayon::slice::Iter<'a, T>, F>),
+    Parallel(rayon::iter::FilterMap<rayon::iter::MinLen<rayon::slice::Iter<'a, T>>, F>),
     Sequential(std::iter::FilterMap<std::slice::Iter<'a, T>, F>),
 }
 
 impl<'a, T: Send + Sync> SmartIterator<'a, T> {
+    pub(crate) fn map<B: Send + Sync, F>(self, f: F) -> SmartMap<'a, T, F>
+    where
+        F: Fn(&'a T) -> B + Send + Sync,
+    {
+        match self {
+            SmartIterator::Parallel(iter) => SmartMap::Parallel(iter.map(f)),
+            SmartIterator::Sequential(iter) => SmartMap::Sequential(iter.map(f)),
+        }
+    }
+
     pub(crate) fn filter_map<B: Send + Sync, F>(self, f: F) -> SmartFilterMap<'a, T, F>
     where
         F: Fn(&'a T) -> Option<B> + Send + Sync,
@@ -38,6 +108,19 @@ impl<'a, T: Send + Sync> SmartIterator<'a, T> {
     }
 }
 
+impl<'a, T: Send + Sync, B: Send + Sync, F> SmartMap<'a, T, F>
+where
+    F: Fn(&'a T) -> B + Send + Sync,
+{
+    /// Collect the results, keeping the input order
+    pub(crate) fn collect(self) -> Vec<B> {
+        match self {
+            SmartMap::Parallel(iter) => iter.collect(),
+            SmartMap::Sequential(iter) => iter.collect(),
+        }
+    }
+}
+
 impl<'a, T: Send + Sync, B: Send + Sync, F> SmartFilterMap<'a, T, F>
 where
     F: Fn(&'a T) -> Option<B> + Send + Sync,
@@ -49,3 +132,39 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_inputs_stay_sequential() {
+        assert!(!should_parallelize(64, 64, 8));
+        assert!(should_parallelize(65, 64, 8));
+        assert!(!should_parallelize(10_000, 64, 1));
+        assert!(should_parallelize(1, 0, 2));
+
+        let items: Vec<usize> = (0..100).collect();
+        assert!(matches!(
+            items.smart_iter(100, 0),
+            SmartIterator::Sequential(_)
+        ));
+        let doubled = items.smart_iter(10, 0).map(|n| n * 2).collect();
+        assert_eq!(doubled, (0..100).map(|n| n * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_adaptive_chunk_size() {
+        assert_eq!(adaptive_chunk_size(10_000, 8), 313);
+        assert_eq!(adaptive_chunk_size(100, 8), 4);
+        assert_eq!(adaptive_chunk_size(3, 8), 1);
+        assert_eq!(adaptive_chunk_size(0, 0), 1);
+
+        // Explicit chunk sizes keep the input order
+        let items: Vec<usize> = (0..1000).collect();
+        for chunk_size in [1, 7, 1000, 5000] {
+            let doubled = items.smart_iter(0, chunk_size).map(|n| n * 2).collect();
+            assert_eq!(doubled, (0..1000).map(|n| n * 2).collect::<Vec<_>>());
+        }
+    }
+}
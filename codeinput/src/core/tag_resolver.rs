@@ -1,9 +1,12 @@
 use crate::utils::error::Result;
 use ignore::overrides::{Override, OverrideBuilder};
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use super::types::{CodeownersEntry, FileEntry, Tag};
+use super::common::build_file_map;
+use super::resolver::check_patterns;
+use super::types::{normalize_codeowners_pattern, CodeownersEntry, FileEntry, Tag};
 
 /// Find all files tagged with a specific tag
 pub fn find_files_for_tag(files: &[FileEntry], tag: &Tag) -> Vec<PathBuf> {
@@ -19,6 +22,11 @@ pub fn find_files_for_tag(files: &[FileEntry], tag: &Tag) -> Vec<PathBuf> {
         .collect()
 }
 
+/// Map each of `tags` to the files it is applied to, visiting every file entry once
+pub fn build_tags_map(files: &[FileEntry], tags: Vec<Tag>) -> HashMap<Tag, Vec<PathBuf>> {
+    build_file_map(files, tags, |file_entry| &file_entry.tags)
+}
+
 /// Find tags for a specific file based on all parsed CODEOWNERS entries
 pub fn find_tags_for_file(file_path: &Path, entries: &[CodeownersEntry]) -> Result<Vec<Tag>> {
     let target_dir = file_path.parent().ok_or_else(|| {
@@ -57,7 +65,8 @@ pub fn find_tags_for_file(file_path: &Path, entries: &[CodeownersEntry]) -> Resu
             // Check if the pattern matches the target file
             let matches = {
                 let mut builder = OverrideBuilder::new(codeowners_dir);
-                if let Err(e) = builder.add(&entry.pattern) {
+                let pattern = normalize_codeowners_pattern(&entry.pattern);
+                if let Err(e) = builder.add(&pattern) {
                     eprintln!(
                         "Invalid pattern '{}' in {}: {}",
                         entry.pattern,
@@ -110,6 +119,17 @@ pub fn find_tags_for_file(file_path: &Path, entries: &[CodeownersEntry]) -> Resu
         .unwrap_or_default())
 }
 
+/// Same as [`find_tags_for_file`], but failing on invalid patterns
+///
+/// Instead of skipping entries whose pattern is not a valid glob, returns a
+/// validation error listing all of them (see [`check_patterns`]).
+pub fn find_tags_for_file_strict(
+    file_path: &Path, entries: &[CodeownersEntry],
+) -> Result<Vec<Tag>> {
+    check_patterns(entries)?;
+    find_tags_for_file(file_path, entries)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -125,6 +145,7 @@ mod tests {
             path: PathBuf::from(path),
             owners: vec![],
             tags,
+            inline_override: false,
         }
     }
 
@@ -137,6 +158,8 @@ mod tests {
             pattern: pattern.to_string(),
             owners: vec![],
             tags,
+            comment: None,
+            raw: String::new(),
         }
     }
 
@@ -225,6 +248,33 @@ mod tests {
         assert_eq!(admin_result[0], PathBuf::from("src/api/admin.rs"));
     }
 
+    #[test]
+    fn test_build_tags_map_matches_find_files_for_tag() {
+        let rust = create_test_tag("rust");
+        let core = create_test_tag("core");
+        let unused = create_test_tag("unused");
+
+        let files: Vec<FileEntry> = (0..500)
+            .map(|i| {
+                let tags = match i % 3 {
+                    0 => vec![rust.clone()],
+                    1 => vec![rust.clone(), core.clone()],
+                    _ => vec![],
+                };
+                create_test_file_entry(&format!("src/file_{}.rs", i), tags)
+            })
+            .collect();
+
+        let tags = vec![rust.clone(), core.clone(), unused.clone()];
+        let map = build_tags_map(&files, tags.clone());
+
+        assert_eq!(map.len(), tags.len());
+        for tag in &tags {
+            assert_eq!(map[tag], find_files_for_tag(&files, tag));
+        }
+        assert!(map[&unused].is_empty());
+    }
+
     #[test]
     fn test_find_tags_for_file_no_parent() {
         let entries = vec![];
@@ -365,6 +415,22 @@ mod tests {
         assert_eq!(result[0], rust_tag);
     }
 
+    #[test]
+    fn test_find_tags_for_file_trailing_slash_directory() {
+        let docs_tag = create_test_tag("documentation");
+
+        let entries = vec![create_test_codeowners_entry(
+            "/project/CODEOWNERS",
+            1,
+            "/docs/",
+            vec![docs_tag.clone()],
+        )];
+
+        let nested_file = Path::new("/project/docs/api/readme.md");
+        let result = find_tags_for_file(nested_file, &entries).unwrap();
+        assert_eq!(result, vec![docs_tag]);
+    }
+
     #[test]
     fn test_find_tags_for_file_invalid_pattern() {
         let entries = vec![
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
 #[cfg(feature = "ignore")]
 use ignore::overrides::Override;
@@ -11,7 +11,8 @@ use serde::{Deserialize, Serialize};
 /// - `/path/to/dir/*` matches direct files only (kept as-is)
 /// - `/path/to/dir/**` matches everything recursively (kept as-is)
 /// - Other patterns are kept as-is
-fn normalize_codeowners_pattern(pattern: &str) -> String {
+#[cfg(feature = "ignore")]
+pub(crate) fn normalize_codeowners_pattern(pattern: &str) -> String {
     // If pattern ends with `/` but not `*/` or `**/`, convert to `/**`
     if pattern.ends_with('/') && !pattern.ends_with("*/") && !pattern.ends_with("**/") {
         format!("{}**", pattern)
@@ -21,13 +22,31 @@ fn normalize_codeowners_pattern(pattern: &str) -> String {
 }
 
 /// CODEOWNERS entry with source tracking
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CodeownersEntry {
     pub source_file: PathBuf,
     pub line_number: usize,
     pub pattern: String,
     pub owners: Vec<Owner>,
     pub tags: Vec<Tag>,
+    /// Trailing comment text after the `#` marker, if any
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// The original line, trimmed, for echoing in error reports
+    ///
+    /// Not serialized, so it is empty on entries loaded from a cache.
+    #[serde(skip)]
+    pub raw: String,
+}
+
+impl CodeownersEntry {
+    /// Whether this is a pattern-only line such as `*.generated`
+    ///
+    /// Like on GitHub, the files it wins for are left without owners on
+    /// purpose, unlike files that no rule matches.
+    pub fn clears_ownership(&self) -> bool {
+        self.owners.is_empty()
+    }
 }
 
 /// Inline CODEOWNERS entry for file-specific ownership
@@ -48,12 +67,51 @@ pub struct CodeownersEntryMatcher {
     pub pattern: String,
     pub owners: Vec<Owner>,
     pub tags: Vec<Tag>,
+    /// Directory the pattern is anchored at
+    pub anchor_dir: PathBuf,
     pub override_matcher: Override,
 }
 
 #[cfg(feature = "ignore")]
-pub fn codeowners_entry_to_matcher(entry: &CodeownersEntry) -> CodeownersEntryMatcher {
-    let codeowners_dir = match entry.source_file.parent() {
+impl CodeownersEntryMatcher {
+    /// Whether this entry's pattern matches `path`
+    ///
+    /// `path` is either under the anchor directory or relative to it.
+    pub fn matches(&self, path: &Path) -> bool {
+        is_within_codeowners_dir(&self.anchor_dir, path)
+            && self.override_matcher.matched(path, false).is_whitelist()
+    }
+}
+
+/// Whether `path` can be matched by patterns anchored at `codeowners_dir`
+///
+/// The override matcher strips `codeowners_dir` from paths under it but
+/// matches any other path as is, so `/local.rs` in `a/CODEOWNERS` would match
+/// `local.rs` at the repository root. Paths that are relative while the
+/// directory is absolute are taken as relative to the directory.
+#[cfg(feature = "ignore")]
+pub(crate) fn is_within_codeowners_dir(codeowners_dir: &Path, path: &Path) -> bool {
+    if path.is_relative() && codeowners_dir.is_absolute() {
+        return true;
+    }
+    let without_dot = |p: &Path| p.strip_prefix(".").unwrap_or(p).to_path_buf();
+    without_dot(path).starts_with(without_dot(codeowners_dir))
+}
+
+/// Build the matcher for a CODEOWNERS entry, anchored at its CODEOWNERS file's directory
+///
+/// With `case_insensitive`, patterns such as `*.MD` also match `readme.md`.
+#[cfg(feature = "ignore")]
+pub fn codeowners_entry_to_matcher(
+    entry: &CodeownersEntry, case_insensitive: bool,
+) -> CodeownersEntryMatcher {
+    codeowners_entry_to_anchored_matcher(entry, case_insensitive, codeowners_dir(entry))
+}
+
+/// Directory of the CODEOWNERS file an entry comes from
+#[cfg(feature = "ignore")]
+fn codeowners_dir(entry: &CodeownersEntry) -> &Path {
+    match entry.source_file.parent() {
         Some(dir) => dir,
         None => {
             eprintln!(
@@ -62,9 +120,58 @@ pub fn codeowners_entry_to_matcher(entry: &CodeownersEntry) -> CodeownersEntryMa
             );
             panic!("Invalid CODEOWNERS entry without parent directory");
         }
-    };
+    }
+}
 
-    let mut builder = ignore::overrides::OverrideBuilder::new(codeowners_dir);
+/// Build the matchers for CODEOWNERS entries of the repository at `repo`
+///
+/// `anchor` decides whether each pattern is anchored at the directory of its
+/// CODEOWNERS file or at `repo`. A pattern repeated with the same anchor
+/// directory is compiled once and shared by the entries declaring it.
+#[cfg(feature = "ignore")]
+pub fn codeowners_entry_matchers(
+    entries: &[CodeownersEntry], case_insensitive: bool, anchor: PatternAnchor, repo: &Path,
+) -> Vec<CodeownersEntryMatcher> {
+    let mut compiled: std::collections::HashMap<(&Path, &str), Override> =
+        std::collections::HashMap::new();
+    entries
+        .iter()
+        .map(|entry| {
+            let anchor_dir = match anchor {
+                PatternAnchor::FileDir => codeowners_dir(entry),
+                PatternAnchor::RepoRoot => repo,
+            };
+            let override_matcher = compiled
+                .entry((anchor_dir, entry.pattern.as_str()))
+                .or_insert_with(|| compile_pattern(entry, case_insensitive, anchor_dir))
+                .clone();
+            anchored_matcher(entry, anchor_dir, override_matcher)
+        })
+        .collect()
+}
+
+/// Build the matcher for a CODEOWNERS entry whose pattern is anchored at `anchor_dir`
+///
+/// A leading `/` then refers to `anchor_dir`, and only paths below it match.
+#[cfg(feature = "ignore")]
+pub fn codeowners_entry_to_anchored_matcher(
+    entry: &CodeownersEntry, case_insensitive: bool, anchor_dir: &Path,
+) -> CodeownersEntryMatcher {
+    let override_matcher = compile_pattern(entry, case_insensitive, anchor_dir);
+    anchored_matcher(entry, anchor_dir, override_matcher)
+}
+
+/// Compile the pattern of a CODEOWNERS entry, anchored at `anchor_dir`
+#[cfg(feature = "ignore")]
+fn compile_pattern(entry: &CodeownersEntry, case_insensitive: bool, anchor_dir: &Path) -> Override {
+    let mut builder = ignore::overrides::OverrideBuilder::new(anchor_dir);
+    if let Err(e) = builder.case_insensitive(case_insensitive) {
+        eprintln!(
+            "Failed to set case sensitivity for pattern '{}': {}",
+            entry.pattern, e
+        );
+        panic!("Failed to configure CODEOWNERS entry matcher");
+    }
 
     // Transform directory patterns to match GitHub CODEOWNERS behavior
     let pattern = normalize_codeowners_pattern(&entry.pattern);
@@ -79,7 +186,7 @@ pub fn codeowners_entry_to_matcher(entry: &CodeownersEntry) -> CodeownersEntryMa
         );
         panic!("Invalid CODEOWNERS entry pattern");
     }
-    let override_matcher: Override = match builder.build() {
+    match builder.build() {
         Ok(o) => o,
         Err(e) => {
             eprintln!(
@@ -88,32 +195,82 @@ pub fn codeowners_entry_to_matcher(entry: &CodeownersEntry) -> CodeownersEntryMa
             );
             panic!("Failed to build CODEOWNERS entry matcher");
         }
-    };
+    }
+}
 
+/// Matcher for an entry whose pattern is already compiled
+#[cfg(feature = "ignore")]
+fn anchored_matcher(
+    entry: &CodeownersEntry, anchor_dir: &Path, override_matcher: Override,
+) -> CodeownersEntryMatcher {
     CodeownersEntryMatcher {
         source_file: entry.source_file.clone(),
         line_number: entry.line_number,
         pattern: entry.pattern.clone(),
         owners: entry.owners.clone(),
         tags: entry.tags.clone(),
+        anchor_dir: anchor_dir.to_path_buf(),
         override_matcher,
     }
 }
 
 /// Detailed owner representation
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct Owner {
     pub identifier: String,
     pub owner_type: OwnerType,
 }
 
+impl std::str::FromStr for Owner {
+    type Err = std::convert::Infallible;
+
+    /// Classify an owner string the way CODEOWNERS does: `NOOWNER`, `@user`,
+    /// `@org/team` (including nested `@org/team/sub-team`), `user@example.com`,
+    /// or anything else as unknown
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let owner_type = if s.eq_ignore_ascii_case("NOOWNER") {
+            OwnerType::Unowned
+        } else if let Some(name) = s.strip_prefix('@') {
+            if name.contains('/') {
+                OwnerType::Team
+            } else {
+                OwnerType::User
+            }
+        } else if s.contains('@') {
+            OwnerType::Email
+        } else {
+            OwnerType::Unknown
+        };
+
+        Ok(Owner {
+            identifier: s.to_string(),
+            owner_type,
+        })
+    }
+}
+
+impl std::fmt::Display for Owner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.identifier)
+    }
+}
+
 /// Owner type classification
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+///
+/// Serialized in lowercase (`"team"`); the capitalized names written by older
+/// versions are still accepted when reading JSON caches.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
 pub enum OwnerType {
+    #[serde(alias = "User")]
     User,
+    #[serde(alias = "Team")]
     Team,
+    #[serde(alias = "Email")]
     Email,
+    #[serde(alias = "Unowned")]
     Unowned,
+    #[serde(alias = "Unknown")]
     Unknown,
 }
 
@@ -130,14 +287,53 @@ impl std::fmt::Display for OwnerType {
 }
 
 /// Tag representation
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct Tag(pub String);
 
+impl Tag {
+    /// Namespace of a hierarchical tag, e.g. `area` for `area/frontend`
+    ///
+    /// Returns `None` for flat tags.
+    pub fn namespace(&self) -> Option<&str> {
+        self.0.split_once('/').map(|(namespace, _)| namespace)
+    }
+
+    /// Returns true if the tag is `prefix` itself or nested under it
+    ///
+    /// A trailing `/` on `prefix` is optional, so both `area` and `area/`
+    /// match `area/frontend`, but neither matches `areas/frontend`.
+    pub fn matches_prefix(&self, prefix: &str) -> bool {
+        let prefix = prefix.trim_end_matches('/');
+        match self.0.strip_prefix(prefix) {
+            Some(rest) => rest.is_empty() || rest.starts_with('/'),
+            None => false,
+        }
+    }
+}
+
+impl std::str::FromStr for Tag {
+    type Err = std::convert::Infallible;
+
+    /// Parse a tag name, with or without the leading `#`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Tag(s.strip_prefix('#').unwrap_or(s).to_string()))
+    }
+}
+
+impl std::fmt::Display for Tag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum OutputFormat {
     Text,
     Json,
     Bincode,
+    Html,
+    /// Graphviz DOT, only for graph-shaped reports
+    Dot,
 }
 
 impl std::fmt::Display for OutputFormat {
@@ -146,19 +342,99 @@ impl std::fmt::Display for OutputFormat {
             OutputFormat::Text => write!(f, "text"),
             OutputFormat::Json => write!(f, "json"),
             OutputFormat::Bincode => write!(f, "bincode"),
+            OutputFormat::Html => write!(f, "html"),
+            OutputFormat::Dot => write!(f, "dot"),
+        }
+    }
+}
+
+/// Magic bytes opening every framed `--format bincode` output
+pub const BINCODE_MAGIC: [u8; 4] = *b"CIBN";
+/// Version of the bincode output framing
+///
+/// Bumped whenever the layout of a payload changes, so consumers reject
+/// output they would misread. Version 2 added `FileEntry::inline_override`.
+pub const BINCODE_FORMAT_VERSION: u8 = 2;
+/// Length of the header preceding a bincode payload
+pub const BINCODE_HEADER_LEN: usize = 8;
+
+/// Type of payload following a bincode output header
+///
+/// The header is `BINCODE_MAGIC`, `BINCODE_FORMAT_VERSION`, the payload tag
+/// and two reserved zero bytes, followed by the bincode-encoded payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum BincodePayload {
+    /// `Vec<FileEntry>` from `list-files`
+    Files = 1,
+    /// `Vec<(Owner, Vec<PathBuf>)>` from `list-owners`
+    Owners = 2,
+    /// `Vec<(Tag, Vec<PathBuf>)>` from `list-tags`
+    Tags = 3,
+}
+
+impl BincodePayload {
+    /// Header to write before a payload of this type
+    pub fn header(self) -> [u8; BINCODE_HEADER_LEN] {
+        let mut header = [0u8; BINCODE_HEADER_LEN];
+        header[..4].copy_from_slice(&BINCODE_MAGIC);
+        header[4] = BINCODE_FORMAT_VERSION;
+        header[5] = self as u8;
+        header
+    }
+
+    /// Read the payload type from the start of a framed output
+    ///
+    /// Returns `None` if the magic bytes, version or payload tag are not recognized.
+    pub fn from_header(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < BINCODE_HEADER_LEN
+            || bytes[..4] != BINCODE_MAGIC
+            || bytes[4] != BINCODE_FORMAT_VERSION
+        {
+            return None;
+        }
+
+        match bytes[5] {
+            1 => Some(BincodePayload::Files),
+            2 => Some(BincodePayload::Owners),
+            3 => Some(BincodePayload::Tags),
+            _ => None,
         }
     }
 }
 
 // Cache related types
 /// File entry in the ownership cache
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Entries order by path first, so sorting a list of them sorts it by path.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct FileEntry {
     pub path: PathBuf,
     pub owners: Vec<Owner>,
     pub tags: Vec<Tag>,
+    /// Whether the owners and tags come from an inline `!!!CODEOWNERS` declaration
+    #[serde(default)]
+    pub inline_override: bool,
 }
 
+impl FileEntry {
+    /// Returns true if the file has no owners, or only `NOOWNER` owners
+    pub fn is_effectively_unowned(&self) -> bool {
+        self.owners
+            .iter()
+            .all(|owner| owner.owner_type == OwnerType::Unowned)
+    }
+}
+
+/// Magic bytes opening every bincode cache file
+pub const CACHE_MAGIC: [u8; 4] = *b"CICA";
+/// Version of the bincode cache layout, following [`CACHE_MAGIC`]
+///
+/// Bincode is not self-describing, so this is bumped whenever a cached type
+/// changes and caches with another version are rebuilt. JSON caches have no
+/// header.
+pub const CACHE_FORMAT_VERSION: u8 = 7;
+
 /// Cache for storing parsed CODEOWNERS information
 #[derive(Debug)]
 pub struct CodeownersCache {
@@ -168,6 +444,70 @@ pub struct CodeownersCache {
     // Derived data for lookups
     pub owners_map: std::collections::HashMap<Owner, Vec<PathBuf>>,
     pub tags_map: std::collections::HashMap<Tag, Vec<PathBuf>>,
+    /// Whether patterns were matched case-insensitively when the cache was built
+    pub case_insensitive: bool,
+    /// How the owners of overlapping rules were combined when the cache was built
+    pub owner_merge: OwnerMerge,
+    /// Where patterns were anchored when the cache was built
+    pub anchor: PatternAnchor,
+    /// Whether symlinks were followed while walking the repository for the cache
+    pub follow_links: bool,
+    /// Whether walked paths were resolved to their real location and deduplicated
+    pub dedupe_paths: bool,
+    /// Owner aliases applied when the cache was built, old identifier to new
+    pub owner_aliases: std::collections::HashMap<String, String>,
+    /// Commit `HEAD` pointed at when the cache was built, if any
+    pub head_commit: Option<String>,
+    /// Identity map the owner map was merged with, lowercased email to handle
+    pub identity_map: std::collections::HashMap<String, String>,
+}
+
+impl CodeownersCache {
+    /// Cached file entry for `path`
+    ///
+    /// `./` components are ignored, so `src/main.rs` finds a file cached as
+    /// `./src/main.rs`.
+    pub fn file(&self, path: &Path) -> Option<&FileEntry> {
+        let normal = |path: &Path| {
+            path.components()
+                .filter(|component| *component != Component::CurDir)
+                .collect::<PathBuf>()
+        };
+        let path = normal(path);
+        self.files.iter().find(|file| normal(&file.path) == path)
+    }
+
+    /// Owners of `path`, or `None` if the file isn't in the cache
+    pub fn owners_of(&self, path: &Path) -> Option<&[Owner]> {
+        self.file(path).map(|file| file.owners.as_slice())
+    }
+
+    /// Tags of `path`, or `None` if the file isn't in the cache
+    pub fn tags_of(&self, path: &Path) -> Option<&[Tag]> {
+        self.file(path).map(|file| file.tags.as_slice())
+    }
+
+    /// Files owned by the owner with this identifier, e.g. `@org/team`
+    pub fn files_owned_by(&self, identifier: &str) -> Option<&[PathBuf]> {
+        self.owners_map
+            .iter()
+            .find(|(owner, _)| owner.identifier == identifier)
+            .map(|(_, paths)| paths.as_slice())
+    }
+
+    /// Files with this tag
+    pub fn files_tagged(&self, tag: &str) -> Option<&[PathBuf]> {
+        self.tags_map
+            .iter()
+            .find(|(t, _)| t.0 == tag)
+            .map(|(_, paths)| paths.as_slice())
+    }
+
+    /// Matchers for the cached entries, built as they were for the cache of `repo`
+    #[cfg(feature = "ignore")]
+    pub fn entry_matchers(&self, repo: &Path) -> Vec<CodeownersEntryMatcher> {
+        codeowners_entry_matchers(&self.entries, self.case_insensitive, self.anchor, repo)
+    }
 }
 
 impl Serialize for CodeownersCache {
@@ -177,7 +517,7 @@ impl Serialize for CodeownersCache {
     {
         use serde::ser::SerializeStruct;
 
-        let mut state = serializer.serialize_struct("CodeownersCache", 4)?;
+        let mut state = serializer.serialize_struct("CodeownersCache", 13)?;
         state.serialize_field("hash", &self.hash)?;
         state.serialize_field("entries", &self.entries)?;
         state.serialize_field("files", &self.files)?;
@@ -190,6 +530,14 @@ impl Serialize for CodeownersCache {
         // Convert tags_map to a serializable format
         let tags_map_serializable: Vec<(&Tag, &Vec<PathBuf>)> = self.tags_map.iter().collect();
         state.serialize_field("tags_map", &tags_map_serializable)?;
+        state.serialize_field("case_insensitive", &self.case_insensitive)?;
+        state.serialize_field("owner_merge", &self.owner_merge)?;
+        state.serialize_field("anchor", &self.anchor)?;
+        state.serialize_field("follow_links", &self.follow_links)?;
+        state.serialize_field("dedupe_paths", &self.dedupe_paths)?;
+        state.serialize_field("owner_aliases", &self.owner_aliases)?;
+        state.serialize_field("head_commit", &self.head_commit)?;
+        state.serialize_field("identity_map", &self.identity_map)?;
 
         state.end()
     }
@@ -207,6 +555,22 @@ impl<'de> Deserialize<'de> for CodeownersCache {
             files: Vec<FileEntry>,
             owners_map: Vec<(Owner, Vec<PathBuf>)>,
             tags_map: Vec<(Tag, Vec<PathBuf>)>,
+            #[serde(default)]
+            case_insensitive: bool,
+            #[serde(default)]
+            owner_merge: OwnerMerge,
+            #[serde(default)]
+            anchor: PatternAnchor,
+            #[serde(default)]
+            follow_links: bool,
+            #[serde(default)]
+            dedupe_paths: bool,
+            #[serde(default)]
+            owner_aliases: std::collections::HashMap<String, String>,
+            #[serde(default)]
+            head_commit: Option<String>,
+            #[serde(default)]
+            identity_map: std::collections::HashMap<String, String>,
         }
 
         let helper = CodeownersCacheHelper::deserialize(deserializer)?;
@@ -228,6 +592,14 @@ impl<'de> Deserialize<'de> for CodeownersCache {
             files: helper.files,
             owners_map,
             tags_map,
+            case_insensitive: helper.case_insensitive,
+            owner_merge: helper.owner_merge,
+            anchor: helper.anchor,
+            follow_links: helper.follow_links,
+            dedupe_paths: helper.dedupe_paths,
+            owner_aliases: helper.owner_aliases,
+            head_commit: helper.head_commit,
+            identity_map: helper.identity_map,
         })
     }
 }
@@ -238,6 +610,28 @@ pub enum CacheEncoding {
     Json,
 }
 
+/// Which matching CODEOWNERS rules give a file its owners and tags
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OwnerMerge {
+    /// Only the highest-priority rule, as on GitHub
+    #[default]
+    Winner,
+    /// Every matching rule, owners and tags deduplicated
+    Union,
+}
+
+/// Directory the patterns of a CODEOWNERS file are anchored at
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PatternAnchor {
+    /// The directory of the CODEOWNERS file, so nested files only cover their directory
+    #[default]
+    FileDir,
+    /// The repository root, as if every rule were in a top-level CODEOWNERS file
+    RepoRoot,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -317,6 +711,257 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_owner_from_str_display_round_trip() {
+        let cases = [
+            ("@alice", OwnerType::User),
+            ("@org/team", OwnerType::Team),
+            ("alice@example.com", OwnerType::Email),
+            ("NOOWNER", OwnerType::Unowned),
+            ("plaintext", OwnerType::Unknown),
+        ];
+
+        for (input, expected_type) in cases {
+            let owner: Owner = input.parse().unwrap();
+            assert_eq!(owner.owner_type, expected_type);
+            assert_eq!(owner.to_string(), input);
+
+            let reparsed: Owner = owner.to_string().parse().unwrap();
+            assert_eq!(reparsed, owner);
+        }
+    }
+
+    #[test]
+    fn test_owner_type_json_is_lowercase_and_accepts_old_names() {
+        assert_eq!(
+            serde_json::to_string(&OwnerType::Team).unwrap(),
+            r#""team""#
+        );
+        let old: OwnerType = serde_json::from_str(r#""Team""#).unwrap();
+        assert_eq!(old, OwnerType::Team);
+        let new: OwnerType = serde_json::from_str(r#""unowned""#).unwrap();
+        assert_eq!(new, OwnerType::Unowned);
+    }
+
+    #[test]
+    fn test_tag_from_str_display_round_trip() {
+        let tag: Tag = "security".parse().unwrap();
+        assert_eq!(tag, Tag("security".to_string()));
+        assert_eq!(tag.to_string(), "security");
+
+        // The leading `#` used in CODEOWNERS files is optional
+        let tag: Tag = "#security".parse().unwrap();
+        assert_eq!(tag, Tag("security".to_string()));
+
+        let reparsed: Tag = tag.to_string().parse().unwrap();
+        assert_eq!(reparsed, tag);
+    }
+
+    #[test]
+    fn test_tag_namespace_and_prefix() {
+        let tag = Tag("area/frontend/forms".to_string());
+        assert_eq!(tag.namespace(), Some("area"));
+        assert!(tag.matches_prefix("area/"));
+        assert!(tag.matches_prefix("area"));
+        assert!(tag.matches_prefix("area/frontend/"));
+        assert!(!tag.matches_prefix("area/front"));
+        assert!(!tag.matches_prefix("ar"));
+
+        let flat = Tag("security".to_string());
+        assert_eq!(flat.namespace(), None);
+        assert!(flat.matches_prefix("security"));
+        assert!(!Tag("areas/backend".to_string()).matches_prefix("area/"));
+    }
+
+    #[test]
+    fn test_file_entry_is_effectively_unowned() {
+        let noowner = Owner {
+            identifier: "NOOWNER".to_string(),
+            owner_type: OwnerType::Unowned,
+        };
+        let user = Owner {
+            identifier: "@alice".to_string(),
+            owner_type: OwnerType::User,
+        };
+
+        // No owners at all
+        let entry = FileEntry {
+            path: PathBuf::from("src/main.rs"),
+            owners: vec![],
+            tags: vec![],
+            inline_override: false,
+        };
+        assert!(entry.is_effectively_unowned());
+
+        // Explicitly marked NOOWNER
+        let entry = FileEntry {
+            path: PathBuf::from("src/generated.rs"),
+            owners: vec![noowner.clone()],
+            tags: vec![],
+            inline_override: false,
+        };
+        assert!(entry.is_effectively_unowned());
+
+        // NOOWNER alongside a real owner is still owned
+        let entry = FileEntry {
+            path: PathBuf::from("src/lib.rs"),
+            owners: vec![noowner, user],
+            tags: vec![],
+            inline_override: false,
+        };
+        assert!(!entry.is_effectively_unowned());
+    }
+
+    #[cfg(feature = "ignore")]
+    #[test]
+    fn test_codeowners_entry_to_matcher_trailing_slash_matches_nested_files() {
+        let entry = CodeownersEntry {
+            source_file: PathBuf::from("/project/CODEOWNERS"),
+            line_number: 1,
+            pattern: "/docs/".to_string(),
+            owners: vec![],
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        };
+
+        let matcher = codeowners_entry_to_matcher(&entry, false);
+        let is_match = |path: &str| {
+            matcher
+                .override_matcher
+                .matched(PathBuf::from(path), false)
+                .is_whitelist()
+        };
+
+        assert!(is_match("/project/docs/readme.md"));
+        assert!(is_match("/project/docs/api/readme.md"));
+        assert!(!is_match("/project/src/docs/readme.md"));
+    }
+
+    #[cfg(feature = "ignore")]
+    #[test]
+    fn test_codeowners_entry_to_matcher_leading_slash_anchors_to_nested_dir() {
+        let entry = |source_file: &str, pattern: &str| CodeownersEntry {
+            source_file: PathBuf::from(source_file),
+            line_number: 0,
+            pattern: pattern.to_string(),
+            owners: vec![],
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        };
+
+        let matcher =
+            codeowners_entry_to_matcher(&entry("/project/a/CODEOWNERS", "/local.rs"), false);
+        assert!(matcher.matches(Path::new("/project/a/local.rs")));
+        assert!(!matcher.matches(Path::new("/project/b/local.rs")));
+        assert!(!matcher.matches(Path::new("/project/local.rs")));
+        assert!(!matcher.matches(Path::new("/project/a/sub/local.rs")));
+        assert!(!matcher.matches(Path::new("/local.rs")));
+
+        let matcher =
+            codeowners_entry_to_matcher(&entry("/project/a/CODEOWNERS", "/src/*.rs"), false);
+        assert!(matcher.matches(Path::new("/project/a/src/lib.rs")));
+        assert!(!matcher.matches(Path::new("/project/src/lib.rs")));
+        assert!(!matcher.matches(Path::new("/project/b/src/lib.rs")));
+
+        // Relative repository paths, as produced when the repo is `.`
+        let matcher = codeowners_entry_to_matcher(&entry("./a/CODEOWNERS", "/local.rs"), false);
+        assert!(matcher.matches(Path::new("./a/local.rs")));
+        assert!(!matcher.matches(Path::new("./b/local.rs")));
+        assert!(!matcher.matches(Path::new("./local.rs")));
+        assert!(!matcher.matches(Path::new("local.rs")));
+    }
+
+    #[cfg(feature = "ignore")]
+    #[test]
+    fn test_codeowners_entry_matchers_anchor_modes() {
+        let entries = vec![CodeownersEntry {
+            source_file: PathBuf::from("/project/a/CODEOWNERS"),
+            line_number: 0,
+            pattern: "/src/*.rs".to_string(),
+            owners: vec![],
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        }];
+        let repo = Path::new("/project");
+
+        let matchers = codeowners_entry_matchers(&entries, false, PatternAnchor::FileDir, repo);
+        assert!(matchers[0].matches(Path::new("/project/a/src/lib.rs")));
+        assert!(!matchers[0].matches(Path::new("/project/src/lib.rs")));
+
+        // The leading slash now refers to the repository root
+        let matchers = codeowners_entry_matchers(&entries, false, PatternAnchor::RepoRoot, repo);
+        assert!(matchers[0].matches(Path::new("/project/src/lib.rs")));
+        assert!(!matchers[0].matches(Path::new("/project/a/src/lib.rs")));
+        assert_eq!(matchers[0].anchor_dir, repo);
+    }
+
+    #[cfg(feature = "ignore")]
+    #[test]
+    fn test_codeowners_entry_matchers_share_patterns_per_directory() {
+        let entry = |source_file: &str, line_number: usize| CodeownersEntry {
+            source_file: PathBuf::from(source_file),
+            line_number,
+            pattern: "/src/*.rs".to_string(),
+            owners: vec![],
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        };
+        let entries = vec![
+            entry("/project/a/CODEOWNERS", 0),
+            entry("/project/b/CODEOWNERS", 0),
+            entry("/project/a/CODEOWNERS", 1),
+        ];
+
+        let matchers = codeowners_entry_matchers(
+            &entries,
+            false,
+            PatternAnchor::FileDir,
+            Path::new("/project"),
+        );
+
+        // The same pattern in another directory is compiled for that directory
+        assert!(matchers[0].matches(Path::new("/project/a/src/lib.rs")));
+        assert!(!matchers[0].matches(Path::new("/project/b/src/lib.rs")));
+        assert!(matchers[1].matches(Path::new("/project/b/src/lib.rs")));
+        assert!(!matchers[1].matches(Path::new("/project/a/src/lib.rs")));
+        assert!(matchers[2].matches(Path::new("/project/a/src/lib.rs")));
+        assert_eq!(matchers[2].line_number, 1);
+    }
+
+    #[cfg(feature = "ignore")]
+    #[test]
+    fn test_codeowners_entry_to_matcher_ignore_case() {
+        let entry = CodeownersEntry {
+            source_file: PathBuf::from("/project/CODEOWNERS"),
+            line_number: 1,
+            pattern: "*.MD".to_string(),
+            owners: vec![Owner {
+                identifier: "@docs".to_string(),
+                owner_type: OwnerType::Team,
+            }],
+            tags: vec![],
+            comment: None,
+            raw: String::new(),
+        };
+        let readme = PathBuf::from("/project/readme.md");
+
+        let matcher = codeowners_entry_to_matcher(&entry, false);
+        assert!(!matcher
+            .override_matcher
+            .matched(&readme, false)
+            .is_whitelist());
+
+        let matcher = codeowners_entry_to_matcher(&entry, true);
+        assert!(matcher
+            .override_matcher
+            .matched(&readme, false)
+            .is_whitelist());
+    }
+
     #[cfg(feature = "ignore")]
     #[test]
     fn test_codeowners_entry_to_matcher_directory_pattern_github_behavior() {
@@ -353,9 +998,11 @@ mod tests {
                 owner_type: OwnerType::Team,
             }],
             tags: vec![],
+            comment: None,
+            raw: String::new(),
         };
 
-        let matcher = codeowners_entry_to_matcher(&entry);
+        let matcher = codeowners_entry_to_matcher(&entry, false);
 
         // Test files that should match
         let test_files = vec![
@@ -407,4 +1054,41 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_file_entries_sort_by_path() {
+        let file = |path: &str, owner: &str| FileEntry {
+            path: PathBuf::from(path),
+            owners: vec![owner.parse().unwrap()],
+            tags: vec![Tag("core".to_string())],
+            inline_override: false,
+        };
+
+        let mut files = [
+            file("src/main.rs", "@org/rust"),
+            file("README.md", "@org/docs"),
+            file("src/lib.rs", "@org/rust"),
+        ];
+        files.sort();
+        let paths: Vec<&str> = files.iter().map(|f| f.path.to_str().unwrap()).collect();
+        assert_eq!(paths, vec!["README.md", "src/lib.rs", "src/main.rs"]);
+
+        assert_eq!(files[1], file("src/lib.rs", "@org/rust"));
+        assert_ne!(files[1], file("src/lib.rs", "@org/other"));
+        assert!(file("src/lib.rs", "@org/zzz") < file("src/main.rs", "@org/aaa"));
+    }
+
+    #[test]
+    fn test_bincode_header_rejects_other_framing_versions() {
+        let header = BincodePayload::Files.header();
+        assert_eq!(
+            BincodePayload::from_header(&header),
+            Some(BincodePayload::Files)
+        );
+
+        // Files payloads without `inline_override` were framed as version 1
+        let mut old = header;
+        old[4] = 1;
+        assert_eq!(BincodePayload::from_header(&old), None);
+    }
 }
@@ -10,3 +10,6 @@ pub use core::types::*;
 pub mod core;
 #[cfg(not(feature = "types"))]
 pub mod utils;
+
+#[cfg(not(feature = "types"))]
+pub use core::cache::{load_cache, open_cache};
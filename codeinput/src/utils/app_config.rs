@@ -1,17 +1,20 @@
 use config::builder::DefaultState;
-use config::{Config, ConfigBuilder, Environment};
+use config::{Config, ConfigBuilder, Environment, Source};
 use lazy_static::{__Deref, lazy_static};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::RwLock;
 
 use super::error::Result;
-use super::types::LogLevel;
+use super::types::{ConfigSource, HashMode, LogLevel};
 
 // CONFIG static variable. It's actually an AppConfig
 // inside an RwLock.
 lazy_static! {
     pub static ref BUILDER: RwLock<ConfigBuilder<DefaultState>> = RwLock::new(Config::builder());
+    // Layer that last set each key, kept in step with BUILDER
+    static ref SOURCES: RwLock<HashMap<String, ConfigSource>> = RwLock::new(HashMap::new());
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -19,6 +22,14 @@ pub struct AppConfig {
     pub debug: bool,
     pub log_level: LogLevel,
     pub cache_file: String,
+    pub skip_dirs: Vec<String>,
+    pub cache_ttl_secs: u64,
+    pub hash_mode: HashMode,
+    pub parallel_threshold: usize,
+    pub chunk_size: usize,
+    pub max_output_bytes: u64,
+    pub progress_json: bool,
+    pub max_inline_scan_bytes: u64,
 }
 
 impl AppConfig {
@@ -29,22 +40,24 @@ impl AppConfig {
         // Embed file into executable
         // This macro will embed the configuration file into the
         // executable. Check include_str! for more info.
+        let mut sources = HashMap::new();
         if let Some(config_contents) = default_config {
             //let contents = include_str!(config_file_path);
-            builder = builder.add_source(config::File::from_str(
-                config_contents,
-                config::FileFormat::Toml,
-            ));
+            let defaults = config::File::from_str(config_contents, config::FileFormat::Toml);
+            record_sources(&mut sources, &defaults, ConfigSource::Default);
+            builder = builder.add_source(defaults);
         }
 
         // Merge settings with env variables
-        builder = builder.add_source(Environment::with_prefix("CODEINPUT")); // TODO: Merge settings with Clap Settings Arguments
+        record_sources(&mut sources, &environment(), ConfigSource::Env);
+        builder = builder.add_source(environment());
 
         // Save Config to RwLoc
         {
             let mut w = BUILDER.write()?;
             *w = builder;
         }
+        *SOURCES.write()? = sources;
 
         Ok(())
     }
@@ -61,17 +74,44 @@ impl AppConfig {
             AppConfig::set("log_level", &value.to_string())?;
         }
 
+        if args.contains_id("hash_mode") {
+            let value: &HashMode = args.get_one("hash_mode").unwrap_or(&HashMode::Full);
+            AppConfig::set("hash_mode", &value.to_string())?;
+        }
+
+        if args.contains_id("chunk_size") {
+            let value: &usize = args.get_one("chunk_size").unwrap_or(&0);
+            AppConfig::set("chunk_size", &value.to_string())?;
+        }
+
+        if args.contains_id("max_output_bytes") {
+            let value: &u64 = args.get_one("max_output_bytes").unwrap_or(&0);
+            AppConfig::set("max_output_bytes", &value.to_string())?;
+        }
+
+        if args.get_flag("progress_json") {
+            AppConfig::set("progress_json", "true")?;
+        }
+
         Ok(())
     }
 
     pub fn merge_config(config_file: Option<&Path>) -> Result<()> {
         // Merge settings with config file if there is one
         if let Some(config_file_path) = config_file {
+            {
+                let mut sources = SOURCES.write().unwrap();
+                let file = file_layer(config_file_path);
+                record_sources(&mut sources, &file, ConfigSource::File);
+                record_sources(&mut sources, &environment(), ConfigSource::Env);
+            }
             {
                 let mut w = BUILDER.write().unwrap();
-                *w = w.clone().add_source(config::File::with_name(
-                    config_file_path.to_str().unwrap_or(""),
-                ));
+                *w = w
+                    .clone()
+                    .add_source(file_layer(config_file_path))
+                    // Environment variables still take precedence over the file
+                    .add_source(environment());
             }
         }
         Ok(())
@@ -83,6 +123,10 @@ impl AppConfig {
             let mut w = BUILDER.write().unwrap();
             *w = w.clone().set_override(key, value)?;
         }
+        SOURCES
+            .write()
+            .unwrap()
+            .insert(key.to_string(), ConfigSource::Cli);
 
         Ok(())
     }
@@ -95,6 +139,17 @@ impl AppConfig {
         Ok(BUILDER.read()?.deref().clone().build()?.get::<T>(key)?)
     }
 
+    /// Layer the effective value of `key` comes from
+    ///
+    /// Keys no layer has set are reported as [`ConfigSource::Default`].
+    pub fn source_of(key: &str) -> ConfigSource {
+        SOURCES
+            .read()
+            .ok()
+            .and_then(|sources| sources.get(key).copied())
+            .unwrap_or_default()
+    }
+
     // Get CONFIG
     // This clones Config (from RwLock<Config>) into a new AppConfig object.
     // This means you have to fetch this again if you changed the configuration.
@@ -111,6 +166,53 @@ impl AppConfig {
     }
 }
 
+/// Config file layer for `path`
+///
+/// A TOML file with a `[tool.codeinput]` table, such as a project file shared
+/// with other tools, only contributes that table. Any other file is read as a
+/// whole. The layer is a single source either way.
+fn file_layer(path: &Path) -> Vec<Box<dyn Source + Send + Sync>> {
+    let embedded = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| content.parse::<toml::Table>().ok())
+        .and_then(|mut table| match table.remove("tool")? {
+            toml::Value::Table(mut tool) => tool.remove("codeinput"),
+            _ => None,
+        });
+    let source: Box<dyn Source + Send + Sync> = match embedded {
+        Some(toml::Value::Table(table)) => Box::new(config::File::from_str(
+            &table.to_string(),
+            config::FileFormat::Toml,
+        )),
+        _ => Box::new(config::File::with_name(path.to_str().unwrap_or(""))),
+    };
+    vec![source]
+}
+
+/// Environment variable layer, e.g. `CODEINPUT_CACHE_FILE` or `CODEINPUT_LOG_LEVEL`
+///
+/// Precedence is CLI arguments > environment > config file > defaults. List
+/// settings such as `CODEINPUT_SKIP_DIRS` are comma-separated.
+fn environment() -> Environment {
+    Environment::with_prefix("CODEINPUT")
+        .try_parsing(true)
+        .list_separator(",")
+        .with_list_parse_key("skip_dirs")
+}
+
+/// Mark every top-level key set by `source` as coming from `layer`
+///
+/// A source that fails to load is skipped; building the config reports the error.
+fn record_sources(
+    sources: &mut HashMap<String, ConfigSource>, source: &dyn Source, layer: ConfigSource,
+) {
+    if let Ok(values) = source.collect() {
+        for key in values.into_keys() {
+            sources.insert(key, layer);
+        }
+    }
+}
+
 // Coerce Config into AppConfig
 
 impl TryFrom<Config> for AppConfig {
@@ -121,6 +223,14 @@ impl TryFrom<Config> for AppConfig {
             debug: config.get_bool("debug")?,
             log_level: config.get::<LogLevel>("log_level")?,
             cache_file: config.get::<String>("cache_file")?,
+            skip_dirs: config.get::<Vec<String>>("skip_dirs")?,
+            cache_ttl_secs: config.get::<u64>("cache_ttl_secs")?,
+            hash_mode: config.get::<HashMode>("hash_mode")?,
+            parallel_threshold: config.get::<usize>("parallel_threshold")?,
+            chunk_size: config.get::<usize>("chunk_size")?,
+            max_output_bytes: config.get::<u64>("max_output_bytes")?,
+            progress_json: config.get_bool("progress_json")?,
+            max_inline_scan_bytes: config.get::<u64>("max_inline_scan_bytes")?,
         })
     }
 }
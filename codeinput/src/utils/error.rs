@@ -4,10 +4,25 @@ use thiserror::Error;
 /// Result alias
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Broad classification of an error, used to pick a process exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorKind {
+    /// Any error without a more specific kind
+    #[default]
+    Other,
+    /// User input or repository contents failed validation
+    Validation,
+    /// The cache file does not exist or cannot be opened
+    CacheMissing,
+    /// Reading or writing the filesystem failed
+    Io,
+}
+
 /// Error type for this library.
 #[derive(Error, Debug)]
 pub struct Error {
     pub msg: String,
+    pub kind: ErrorKind,
     #[cfg(feature = "nightly")]
     backtrace: std::backtrace::Backtrace,
     source: Option<Box<dyn std::error::Error + Send + Sync>>,
@@ -25,6 +40,7 @@ impl Default for Error {
     fn default() -> Self {
         Error {
             msg: "".to_string(),
+            kind: ErrorKind::Other,
             #[cfg(feature = "nightly")]
             backtrace: std::backtrace::Backtrace::capture(),
             source: None,
@@ -37,15 +53,24 @@ impl Error {
     pub fn new(msg: &str) -> Self {
         Error {
             msg: msg.to_string(),
+            kind: ErrorKind::Other,
             #[cfg(feature = "nightly")]
             backtrace: std::backtrace::Backtrace::capture(),
             source: None,
         }
     }
+    /// Create a new Error instance of the given kind.
+    pub fn with_kind(kind: ErrorKind, msg: &str) -> Self {
+        Error {
+            kind,
+            ..Error::new(msg)
+        }
+    }
     /// Create a new Error instance with a source error.
     pub fn with_source(msg: &str, source: Box<dyn std::error::Error + Send + Sync>) -> Self {
         Error {
             msg: msg.to_string(),
+            kind: ErrorKind::Other,
             #[cfg(feature = "nightly")]
             backtrace: std::backtrace::Backtrace::capture(),
             source: Some(source),
@@ -57,6 +82,7 @@ impl From<config::ConfigError> for Error {
     fn from(err: config::ConfigError) -> Self {
         Error {
             msg: String::from("Config Error"),
+            kind: ErrorKind::Other,
             #[cfg(feature = "nightly")]
             backtrace: std::backtrace::Backtrace::capture(),
             source: Some(Box::new(err)),
@@ -68,6 +94,7 @@ impl<T> From<std::sync::PoisonError<T>> for Error {
     fn from(_err: std::sync::PoisonError<T>) -> Self {
         Error {
             msg: String::from("Poison Error"),
+            kind: ErrorKind::Other,
             #[cfg(feature = "nightly")]
             backtrace: std::backtrace::Backtrace::capture(),
             source: None,
@@ -79,6 +106,7 @@ impl From<std::io::Error> for Error {
     fn from(err: std::io::Error) -> Self {
         Error {
             msg: String::from("IO Error"),
+            kind: ErrorKind::Io,
             #[cfg(feature = "nightly")]
             backtrace: std::backtrace::Backtrace::capture(),
             source: Some(Box::new(err)),
@@ -90,6 +118,7 @@ impl From<clap::Error> for Error {
     fn from(err: clap::Error) -> Self {
         Error {
             msg: String::from("Clap Error"),
+            kind: ErrorKind::Other,
             #[cfg(feature = "nightly")]
             backtrace: std::backtrace::Backtrace::capture(),
             source: Some(Box::new(err)),
@@ -101,6 +130,7 @@ impl From<log::SetLoggerError> for Error {
     fn from(err: log::SetLoggerError) -> Self {
         Error {
             msg: String::from("Logger Error"),
+            kind: ErrorKind::Other,
             #[cfg(feature = "nightly")]
             backtrace: std::backtrace::Backtrace::capture(),
             source: Some(Box::new(err)),
@@ -112,6 +142,7 @@ impl From<git2::Error> for Error {
     fn from(err: git2::Error) -> Self {
         Error {
             msg: String::from("Git Error"),
+            kind: ErrorKind::Other,
             #[cfg(feature = "nightly")]
             backtrace: std::backtrace::Backtrace::capture(),
             source: Some(Box::new(err)),
@@ -7,7 +7,7 @@ use slog_syslog::Facility;
 
 use super::app_config::AppConfig;
 use super::error::Result;
-use super::types::LogLevel;
+use super::types::{HashMode, LogLevel};
 
 pub fn setup_logging() -> Result<slog_scope::GlobalLoggerGuard> {
     // Setup Logging
@@ -19,6 +19,14 @@ pub fn setup_logging() -> Result<slog_scope::GlobalLoggerGuard> {
         debug: false,
         log_level: LogLevel::Info,
         cache_file: ".codeowners.cache".to_string(),
+        skip_dirs: vec![],
+        cache_ttl_secs: 0,
+        hash_mode: HashMode::Full,
+        parallel_threshold: 64,
+        chunk_size: 0,
+        max_output_bytes: 0,
+        progress_json: false,
+        max_inline_scan_bytes: 8192,
     });
     
     let log_level = match config.log_level {
@@ -39,6 +47,14 @@ pub fn default_root_logger() -> Result<slog::Logger> {
         debug: false,
         log_level: LogLevel::Info,
         cache_file: ".codeowners.cache".to_string(),
+        skip_dirs: vec![],
+        cache_ttl_secs: 0,
+        hash_mode: HashMode::Full,
+        parallel_threshold: 64,
+        chunk_size: 0,
+        max_output_bytes: 0,
+        progress_json: false,
+        max_inline_scan_bytes: 8192,
     });
     
     let slog_level = match config.log_level {
@@ -27,6 +27,48 @@ impl std::fmt::Display for LogLevel {
     }
 }
 
+/// How the repository state is hashed to decide whether the cache is stale
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HashMode {
+    /// HEAD, index and uncommitted changes in the working tree
+    #[default]
+    #[serde(rename = "full")]
+    Full,
+    /// HEAD and index only; uncommitted changes do not invalidate the cache
+    #[serde(rename = "head-only")]
+    HeadOnly,
+    /// No git hashing; the cache is rebuilt every time unless `cache_ttl_secs` is set
+    #[serde(rename = "none")]
+    None,
+}
+
+impl std::fmt::Display for HashMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match *self {
+            HashMode::Full => "full",
+            HashMode::HeadOnly => "head-only",
+            HashMode::None => "none",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for HashMode {
+    type Err = super::error::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "full" => Ok(HashMode::Full),
+            "head-only" => Ok(HashMode::HeadOnly),
+            "none" => Ok(HashMode::None),
+            _ => Err(super::error::Error::new(&format!(
+                "Invalid hash mode: {}. Valid options: full, head-only, none",
+                s
+            ))),
+        }
+    }
+}
+
 impl FromStr for LogLevel {
     type Err = super::error::Error;
 
@@ -40,3 +82,29 @@ impl FromStr for LogLevel {
         }
     }
 }
+
+/// Configuration layer that a setting's effective value comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// The embedded default configuration
+    #[default]
+    Default,
+    /// The file passed with `--config`
+    File,
+    /// A `CODEINPUT_*` environment variable
+    Env,
+    /// A command line argument
+    Cli,
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match *self {
+            ConfigSource::Default => "default",
+            ConfigSource::File => "file",
+            ConfigSource::Env => "env",
+            ConfigSource::Cli => "cli",
+        };
+        write!(f, "{}", s)
+    }
+}